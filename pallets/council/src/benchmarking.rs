@@ -0,0 +1,27 @@
+//! Benchmarking setup for pallet-council
+#![cfg(feature = "runtime-benchmarks")]
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Council;
+use frame_benchmarking::v2::*;
+use frame_system::RawOrigin;
+use sp_std::prelude::*;
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn set_members() {
+		let members: Vec<T::AccountId> =
+			(0..T::MaxMembers::get()).map(|i| account("member", i, 0)).collect();
+
+		#[extrinsic_call]
+		set_members(RawOrigin::Root, members.clone());
+
+		assert_eq!(Members::<T>::get().len(), members.len());
+	}
+
+	impl_benchmark_test_suite!(Council, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,123 @@
+//! A pallet holding the council's signatories. The runtime derives the council's multisig
+//! account from them, and only root can change them, so calls dispatched from the council's
+//! origin can never change the set of accounts that authorises them.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::pallet_prelude::*;
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod migrations;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use crate::*;
+	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The maximum number of council members.
+		#[pallet::constant]
+		type MaxMembers: Get<u32>;
+	}
+
+	/// The council's members, sorted by account and without duplicates.
+	#[pallet::storage]
+	pub type Members<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxMembers>, ValueQuery>;
+
+	/// Genesis configuration.
+	#[pallet::genesis_config]
+	#[derive(frame_support::DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		/// The council's initial members. The council has no members if it is empty.
+		pub members: Vec<T::AccountId>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			if !self.members.is_empty() {
+				let members = Pallet::<T>::sorted_members(self.members.clone())
+					.expect("Too many council members in genesis config");
+				Members::<T>::put(members);
+			}
+		}
+	}
+
+	// Pallets use events to inform users when important changes are made.
+	// https://docs.substrate.io/main-docs/build/events-errors/
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The council's members were replaced. [members]
+		MembersChanged { members: Vec<T::AccountId> },
+	}
+
+	// Errors inform users that something went wrong.
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The council must have at least one member.
+		NoMembers,
+		/// More members than `MaxMembers`.
+		TooManyMembers,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Replace the council's members. Duplicates are ignored.
+		///
+		/// Only root can call this, rather than a configurable origin, so the council can't be
+		/// given control over its own members.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_members(origin: OriginFor<T>, members: Vec<T::AccountId>) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let members = Self::sorted_members(members)?;
+			Members::<T>::put(&members);
+
+			Self::deposit_event(Event::MembersChanged { members: members.into_inner() });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Sort and deduplicate a set of members, checking it is neither empty nor larger than
+		/// `MaxMembers`.
+		pub fn sorted_members(
+			mut members: Vec<T::AccountId>,
+		) -> Result<BoundedVec<T::AccountId, T::MaxMembers>, DispatchError> {
+			members.sort();
+			members.dedup();
+			ensure!(!members.is_empty(), Error::<T>::NoMembers);
+			BoundedVec::try_from(members).map_err(|_| Error::<T>::TooManyMembers.into())
+		}
+
+		/// The number of members that must approve the council's operations, a majority of
+		/// them.
+		pub fn threshold() -> u32 {
+			(Members::<T>::decode_len().unwrap_or(0) / 2 + 1) as u32
+		}
+	}
+}
@@ -0,0 +1,47 @@
+//! Storage migrations for the council pallet.
+
+use super::*;
+use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+pub mod v1 {
+	use super::*;
+
+	/// Migrate pallet storage from version 0 to version 1.
+	///
+	/// Chains created before the pallet was added have no council members. They are set to
+	/// `InitialMembers`, the accounts the council was derived from until then.
+	pub struct MigrateToV1<T, InitialMembers>(PhantomData<(T, InitialMembers)>);
+
+	impl<T: Config, InitialMembers: Get<Vec<T::AccountId>>> OnRuntimeUpgrade
+		for MigrateToV1<T, InitialMembers>
+	{
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			if !Members::<T>::exists() {
+				if let Ok(members) = Pallet::<T>::sorted_members(InitialMembers::get()) {
+					Members::<T>::put(members);
+				}
+			}
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(2, 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 1,
+				"Storage version was not updated by the migration"
+			);
+			ensure!(Members::<T>::decode_len().unwrap_or(0) > 0, "The council has no members");
+			Ok(())
+		}
+	}
+}
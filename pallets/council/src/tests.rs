@@ -0,0 +1,74 @@
+use crate::{migrations::v1::MigrateToV1, mock::*, Error, Event, Members};
+use frame_support::{
+	assert_noop, assert_ok, parameter_types,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+use sp_runtime::DispatchError;
+
+#[test]
+fn genesis_config_sorts_members() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Members::<Test>::get().into_inner(), vec![1, 2, 3]);
+		assert_eq!(Council::threshold(), 2);
+		assert_eq!(Council::on_chain_storage_version(), 1);
+	});
+}
+
+#[test]
+fn only_root_can_set_members() {
+	new_test_ext().execute_with(|| {
+		// Members can't change the council, even together.
+		for who in [1, 2, 3] {
+			assert_noop!(
+				Council::set_members(RuntimeOrigin::signed(who), vec![who]),
+				DispatchError::BadOrigin
+			);
+		}
+
+		assert_ok!(Council::set_members(RuntimeOrigin::root(), vec![5, 4, 5]));
+		assert_eq!(Members::<Test>::get().into_inner(), vec![4, 5]);
+		assert_eq!(Council::threshold(), 2);
+		System::assert_last_event(Event::MembersChanged { members: vec![4, 5] }.into());
+	});
+}
+
+#[test]
+fn set_members_checks_size() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Council::set_members(RuntimeOrigin::root(), vec![]), Error::<Test>::NoMembers);
+		assert_noop!(
+			Council::set_members(RuntimeOrigin::root(), vec![1, 2, 3, 4]),
+			Error::<Test>::TooManyMembers
+		);
+	});
+}
+
+parameter_types! {
+	pub InitialMembers: Vec<u64> = vec![9, 8];
+}
+
+#[test]
+fn migration_to_v1_sets_initial_members() {
+	new_test_ext().execute_with(|| {
+		// Simulate a chain created before the pallet was added.
+		Members::<Test>::kill();
+		StorageVersion::new(0).put::<Council>();
+
+		MigrateToV1::<Test, InitialMembers>::on_runtime_upgrade();
+
+		assert_eq!(Council::on_chain_storage_version(), 1);
+		assert_eq!(Members::<Test>::get().into_inner(), vec![8, 9]);
+	});
+}
+
+#[test]
+fn migration_to_v1_keeps_existing_members() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<Council>();
+
+		MigrateToV1::<Test, InitialMembers>::on_runtime_upgrade();
+
+		assert_eq!(Council::on_chain_storage_version(), 1);
+		assert_eq!(Members::<Test>::get().into_inner(), vec![1, 2, 3]);
+	});
+}
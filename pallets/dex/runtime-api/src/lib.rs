@@ -0,0 +1,30 @@
+//! Runtime API for previewing swaps of pallet-dex before signing them, and reading the average
+//! prices of its pools.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_dex::AssetKind;
+use sp_runtime::FixedU128;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait DexApi<AssetId, Balance, BlockNumber>
+	where
+		AssetId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+	{
+		/// The amount of the last asset of `path` that `swap_exact_in_path` would give for
+		/// `amount` of the first, or `None` if the path is invalid or its pools can't take the
+		/// swap.
+		fn quote_path(path: Vec<AssetKind<AssetId>>, amount: Balance) -> Option<Balance>;
+
+		/// The average price of `base` in `quote` over at least the last `window` blocks, or
+		/// `None` if they have no pool or its prices haven't been observed for that long.
+		fn twap(
+			base: AssetKind<AssetId>,
+			quote: AssetKind<AssetId>,
+			window: BlockNumber,
+		) -> Option<FixedU128>;
+	}
+}
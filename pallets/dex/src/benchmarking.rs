@@ -6,29 +6,349 @@ use super::*;
 use crate::Pallet as Dex;
 use frame_benchmarking::v2::*;
 use frame_system::RawOrigin;
+use sp_runtime::traits::Saturating;
+
+/// Create sufficient asset `i` and mint plenty of it to `who`.
+fn create_asset<T: Config>(i: u32, who: &T::AccountId) -> AssetIdOf<T> {
+	let id = T::BenchmarkHelper::asset_id(i);
+	let _ = <T::Fungibles as fungibles::Create<T::AccountId>>::create(
+		id,
+		who.clone(),
+		true,
+		One::one(),
+	);
+	let amount = 1_000_000_000u32.into();
+	let _ = <T::Fungibles as fungibles::Mutate<T::AccountId>>::mint_into(id, who, amount);
+	id
+}
+
+/// Fund the whitelisted caller with native tokens, enough for the deposits of a path's pools, and
+/// two assets, returning the caller and the assets.
+fn setup_assets<T: Config>() -> (T::AccountId, AssetKindOf<T>, AssetKindOf<T>) {
+	let caller: T::AccountId = whitelisted_caller();
+	let balance = <T::NativeBalance as fungible::Inspect<T::AccountId>>::minimum_balance()
+		.saturating_mul(1_000u32.into())
+		.saturating_add(T::PoolDeposit::get().saturating_mul(T::MaxHops::get().into()));
+	let _ = <T::NativeBalance as fungible::Mutate<T::AccountId>>::set_balance(&caller, balance);
+	let asset_a = AssetKind::Asset(create_asset::<T>(1, &caller));
+	let asset_b = AssetKind::Asset(create_asset::<T>(2, &caller));
+	(caller, asset_a, asset_b)
+}
+
+/// Create a pool of two assets and add liquidity to it from the whitelisted caller.
+fn setup_pool<T: Config>() -> Result<(T::AccountId, AssetKindOf<T>, AssetKindOf<T>), BenchmarkError>
+{
+	let (caller, asset_a, asset_b) = setup_assets::<T>();
+	let origin = RawOrigin::Signed(caller.clone());
+	Dex::<T>::create_pool(origin.clone().into(), asset_a, asset_b)?;
+	let amount = 1_000_000u32.into();
+	Dex::<T>::add_liquidity(
+		origin.into(),
+		asset_a,
+		asset_b,
+		amount,
+		amount,
+		Zero::zero(),
+		Zero::zero(),
+	)?;
+	Ok((caller, asset_a, asset_b))
+}
+
+/// Create pools between each pair of consecutive assets of an `n` asset path, with liquidity
+/// from the whitelisted caller, and return the caller and the path.
+fn setup_path<T: Config>(n: u32) -> Result<(T::AccountId, Vec<AssetKindOf<T>>), BenchmarkError> {
+	let (caller, _, _) = setup_assets::<T>();
+	let path: Vec<_> = (1..=n).map(|i| AssetKind::Asset(create_asset::<T>(i, &caller))).collect();
+	let origin = RawOrigin::Signed(caller.clone());
+	let amount = 1_000_000u32.into();
+	for pair in path.windows(2) {
+		Dex::<T>::create_pool(origin.clone().into(), pair[0], pair[1])?;
+		Dex::<T>::add_liquidity(
+			origin.clone().into(),
+			pair[0],
+			pair[1],
+			amount,
+			amount,
+			Zero::zero(),
+			Zero::zero(),
+		)?;
+	}
+	Ok((caller, path))
+}
+
+/// Create a pool and a funded incentive for it, with rewards in a third asset, and return the
+/// whitelisted caller, who holds the pool's LP tokens.
+fn setup_incentive<T: Config>() -> Result<T::AccountId, BenchmarkError> {
+	let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+	let reward_asset = create_asset::<T>(3, &caller);
+	let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	Dex::<T>::create_incentive(origin, asset_a, asset_b, reward_asset, 100u32.into())?;
+	Dex::<T>::fund_incentive(RawOrigin::Signed(caller.clone()).into(), 0, 1_000_000u32.into())?;
+	Ok(caller)
+}
 
 #[benchmarks]
 mod benchmarks {
 	use super::*;
 
 	#[benchmark]
-	fn do_something() {
-		let value = 100u32.into();
-		let caller: T::AccountId = whitelisted_caller();
+	fn create_pool() {
+		let (caller, asset_a, asset_b) = setup_assets::<T>();
+		#[extrinsic_call]
+		create_pool(RawOrigin::Signed(caller), asset_a, asset_b);
+
+		assert!(Pools::<T>::contains_key(Dex::<T>::pool_id(asset_a, asset_b)));
+	}
+
+	#[benchmark]
+	fn add_liquidity() -> Result<(), BenchmarkError> {
+		let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+		let amount = 1_000u32.into();
+		#[extrinsic_call]
+		add_liquidity(
+			RawOrigin::Signed(caller),
+			asset_a,
+			asset_b,
+			amount,
+			amount,
+			Zero::zero(),
+			Zero::zero(),
+		);
+
+		let reserve: AssetBalanceOf<T> = 1_001_000u32.into();
+		assert_eq!(Dex::<T>::reserves(asset_a, asset_b), Some((reserve, reserve)));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn remove_liquidity() -> Result<(), BenchmarkError> {
+		let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+		let amount = 1_000u32.into();
+		#[extrinsic_call]
+		remove_liquidity(
+			RawOrigin::Signed(caller),
+			asset_a,
+			asset_b,
+			amount,
+			Zero::zero(),
+			Zero::zero(),
+		);
+
+		let reserve: AssetBalanceOf<T> = 999_000u32.into();
+		assert_eq!(Dex::<T>::reserves(asset_a, asset_b), Some((reserve, reserve)));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_exact_in() -> Result<(), BenchmarkError> {
+		let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+		let amount = 1_000u32.into();
+		#[extrinsic_call]
+		swap_exact_in(RawOrigin::Signed(caller), asset_a, asset_b, amount, Zero::zero());
+
+		let reserve: AssetBalanceOf<T> = 1_001_000u32.into();
+		assert_eq!(Dex::<T>::reserves(asset_a, asset_b).map(|(a, _)| a), Some(reserve));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_exact_out() -> Result<(), BenchmarkError> {
+		let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+		let amount = 1_000u32.into();
+		#[extrinsic_call]
+		swap_exact_out(RawOrigin::Signed(caller), asset_a, asset_b, amount, 1_000_000u32.into());
+
+		let reserve: AssetBalanceOf<T> = 999_000u32.into();
+		assert_eq!(Dex::<T>::reserves(asset_a, asset_b).map(|(_, b)| b), Some(reserve));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn swap_exact_in_path(n: Linear<2, { T::MaxHops::get() }>) -> Result<(), BenchmarkError> {
+		let (caller, path) = setup_path::<T>(n)?;
+		let amount_in = 1_000u32.into();
+		let amount_out = Dex::<T>::quote_path(&path, amount_in)?.pop().unwrap_or_default();
+		let asset_out = T::BenchmarkHelper::asset_id(n);
+		let balance = <T::Fungibles as fungibles::Inspect<T::AccountId>>::balance;
+		let before = balance(asset_out, &caller);
+		let path = BoundedVec::truncate_from(path);
+		#[extrinsic_call]
+		swap_exact_in_path(RawOrigin::Signed(caller.clone()), path, amount_in, Zero::zero());
+
+		assert_eq!(balance(asset_out, &caller), before + amount_out);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn place_limit_order() -> Result<(), BenchmarkError> {
+		let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+		let amount = 1_000u32.into();
+		#[extrinsic_call]
+		place_limit_order(RawOrigin::Signed(caller), asset_a, asset_b, amount, amount);
+
+		assert!(Orders::<T>::contains_key(0));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn cancel_order() -> Result<(), BenchmarkError> {
+		let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+		let amount = 1_000u32.into();
+		let origin = RawOrigin::Signed(caller.clone());
+		Dex::<T>::place_limit_order(origin.into(), asset_a, asset_b, amount, amount)?;
+		#[extrinsic_call]
+		cancel_order(RawOrigin::Signed(caller), 0);
+
+		assert!(!Orders::<T>::contains_key(0));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn create_incentive() -> Result<(), BenchmarkError> {
+		let (caller, asset_a, asset_b) = setup_pool::<T>()?;
+		let reward_asset = create_asset::<T>(3, &caller);
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		#[extrinsic_call]
+		create_incentive(origin as T::RuntimeOrigin, asset_a, asset_b, reward_asset, 100u32.into());
+
+		assert!(Incentives::<T>::contains_key(0));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn fund_incentive() -> Result<(), BenchmarkError> {
+		let caller = setup_incentive::<T>()?;
+		#[extrinsic_call]
+		fund_incentive(RawOrigin::Signed(caller), 0, 1_000u32.into());
+
+		let rewards_left: AssetBalanceOf<T> = 1_001_000u32.into();
+		assert_eq!(Incentives::<T>::get(0).map(|i| i.rewards_left), Some(rewards_left));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn stake() -> Result<(), BenchmarkError> {
+		let caller = setup_incentive::<T>()?;
+		let origin = RawOrigin::Signed(caller.clone());
+		Dex::<T>::stake(origin.clone().into(), 0, 1_000u32.into())?;
+		frame_system::Pallet::<T>::set_block_number(10u32.into());
+		#[extrinsic_call]
+		stake(origin, 0, 1_000u32.into());
+
+		let staked: AssetBalanceOf<T> = 2_000u32.into();
+		assert_eq!(Stakes::<T>::get(0, &caller).map(|s| s.amount), Some(staked));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn unstake() -> Result<(), BenchmarkError> {
+		let caller = setup_incentive::<T>()?;
+		let origin = RawOrigin::Signed(caller.clone());
+		Dex::<T>::stake(origin.clone().into(), 0, 2_000u32.into())?;
+		frame_system::Pallet::<T>::set_block_number(10u32.into());
+		#[extrinsic_call]
+		unstake(origin, 0, 1_000u32.into());
+
+		let staked: AssetBalanceOf<T> = 1_000u32.into();
+		assert_eq!(Stakes::<T>::get(0, &caller).map(|s| s.amount), Some(staked));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn claim_rewards() -> Result<(), BenchmarkError> {
+		let caller = setup_incentive::<T>()?;
+		let origin = RawOrigin::Signed(caller.clone());
+		Dex::<T>::stake(origin.clone().into(), 0, 1_000u32.into())?;
+		frame_system::Pallet::<T>::set_block_number(10u32.into());
+		#[extrinsic_call]
+		claim_rewards(origin, 0);
+
+		assert!(Dex::<T>::pending_rewards(0, &caller).is_zero());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn approve_fee_asset() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let asset = T::BenchmarkHelper::asset_id(1);
 		#[extrinsic_call]
-		do_something(RawOrigin::Signed(caller), value);
+		approve_fee_asset(origin as T::RuntimeOrigin, asset);
 
-		assert_eq!(Something::<T>::get(), Some(value));
+		assert!(FeeAssets::<T>::contains_key(asset));
+		Ok(())
 	}
 
 	#[benchmark]
-	fn cause_error() {
-		Something::<T>::put(100u32);
+	fn remove_fee_asset() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let asset = T::BenchmarkHelper::asset_id(1);
+		FeeAssets::<T>::insert(asset, ());
+		#[extrinsic_call]
+		remove_fee_asset(origin as T::RuntimeOrigin, asset);
+
+		assert!(!FeeAssets::<T>::contains_key(asset));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_fee_asset() {
 		let caller: T::AccountId = whitelisted_caller();
+		let asset = T::BenchmarkHelper::asset_id(1);
+		FeeAssets::<T>::insert(asset, ());
+		#[extrinsic_call]
+		set_fee_asset(RawOrigin::Signed(caller.clone()), Some(asset));
+
+		assert_eq!(AccountFeeAsset::<T>::get(&caller), Some(asset));
+	}
+
+	#[benchmark]
+	fn set_swap_fee() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let fee = Permill::from_percent(2);
+		#[extrinsic_call]
+		set_swap_fee(origin as T::RuntimeOrigin, fee);
+
+		assert_eq!(SwapFee::<T>::get(), fee);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_pool_creation_restricted() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		#[extrinsic_call]
+		set_pool_creation_restricted(origin as T::RuntimeOrigin, true);
+
+		assert!(PoolCreationRestricted::<T>::get());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_pool_creator() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let who: T::AccountId = whitelisted_caller();
+		#[extrinsic_call]
+		set_pool_creator(origin as T::RuntimeOrigin, who.clone(), true);
+
+		assert!(PoolCreators::<T>::contains_key(&who));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_protocol_fee_recipient() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let recipient: T::AccountId = whitelisted_caller();
 		#[extrinsic_call]
-		cause_error(RawOrigin::Signed(caller));
+		set_protocol_fee_recipient(origin as T::RuntimeOrigin, Some(recipient.clone()));
 
-		assert_eq!(Something::<T>::get(), Some(101u32));
+		assert_eq!(ProtocolFeeRecipient::<T>::get(), Some(recipient));
+		Ok(())
 	}
 
 	impl_benchmark_test_suite!(Dex, crate::mock::new_test_ext(), crate::mock::Test);
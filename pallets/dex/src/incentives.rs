@@ -0,0 +1,104 @@
+//! Liquidity mining. An incentive shares a fixed amount of a reward asset each block between the
+//! accounts staking its pool's LP tokens, in proportion to their stakes, for as long as it has
+//! funded rewards left.
+//!
+//! Rewards are tracked with an index of the rewards earned by one staked LP token since the
+//! incentive was created, which grows each block by that block's rewards over the total staked.
+//! A staker is owed its stake times the growth of the index since its rewards were last paid.
+
+use crate::{
+	AssetBalanceOf, AssetIdOf, AssetKind, Config, Error, Event, Incentive, Incentives, Pallet,
+	Stake, Stakes,
+};
+use frame_support::{pallet_prelude::*, traits::tokens::Preservation};
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_runtime::{
+	traits::{AccountIdConversion, Saturating, UniqueSaturatedFrom, UniqueSaturatedInto, Zero},
+	FixedPointNumber, FixedU128,
+};
+
+type IncentiveOf<T> = Incentive<AssetIdOf<T>, AssetBalanceOf<T>, BlockNumberFor<T>>;
+
+impl<T: Config> Pallet<T> {
+	/// The account holding an incentive's rewards and staked LP tokens.
+	pub fn incentive_account(incentive_id: u32) -> T::AccountId {
+		T::PalletId::get().into_sub_account_truncating((b"incentiv", incentive_id))
+	}
+
+	/// The rewards an account is owed by an incentive, up to the current block.
+	pub fn pending_rewards(incentive_id: u32, who: &T::AccountId) -> AssetBalanceOf<T> {
+		match (Incentives::<T>::get(incentive_id), Stakes::<T>::get(incentive_id, who)) {
+			(Some(mut incentive), Some(stake)) => {
+				Self::accrue(&mut incentive);
+				Self::rewards_owed(&incentive, &stake)
+			},
+			_ => Zero::zero(),
+		}
+	}
+
+	/// An incentive, with its rewards shared out up to the current block.
+	pub(crate) fn accrued_incentive(incentive_id: u32) -> Result<IncentiveOf<T>, Error<T>> {
+		let mut incentive =
+			Incentives::<T>::get(incentive_id).ok_or(Error::<T>::IncentiveNotFound)?;
+		Self::accrue(&mut incentive);
+		Ok(incentive)
+	}
+
+	/// Share out an incentive's rewards for the blocks since it was last updated. Nothing is
+	/// shared out while nothing is staked, and no more than the rewards left.
+	fn accrue(incentive: &mut IncentiveOf<T>) {
+		let now = frame_system::Pallet::<T>::block_number();
+		let blocks: u128 = now.saturating_sub(incentive.last_block).unique_saturated_into();
+		incentive.last_block = now;
+		if incentive.total_staked.is_zero() {
+			return
+		}
+
+		let reward_per_block: u128 = incentive.reward_per_block.unique_saturated_into();
+		let rewards_left: u128 = incentive.rewards_left.unique_saturated_into();
+		let total_staked: u128 = incentive.total_staked.unique_saturated_into();
+		let rewards = reward_per_block.saturating_mul(blocks).min(rewards_left);
+		incentive.rewards_left = AssetBalanceOf::<T>::unique_saturated_from(rewards_left - rewards);
+		incentive.reward_per_share = incentive
+			.reward_per_share
+			.saturating_add(FixedU128::saturating_from_rational(rewards, total_staked));
+	}
+
+	/// The index of an incentive times a stake of `amount`.
+	pub(crate) fn reward_debt(incentive: &IncentiveOf<T>, amount: AssetBalanceOf<T>) -> u128 {
+		incentive.reward_per_share.saturating_mul_int(amount.unique_saturated_into())
+	}
+
+	/// The rewards owed to a stake by an accrued incentive.
+	fn rewards_owed(
+		incentive: &IncentiveOf<T>,
+		stake: &Stake<AssetBalanceOf<T>>,
+	) -> AssetBalanceOf<T> {
+		let owed = Self::reward_debt(incentive, stake.amount).saturating_sub(stake.reward_debt);
+		AssetBalanceOf::<T>::unique_saturated_from(owed)
+	}
+
+	/// Pay the rewards owed to a stake by an accrued incentive. The stake's debt must be updated
+	/// afterwards.
+	pub(crate) fn pay_rewards(
+		incentive_id: u32,
+		incentive: &IncentiveOf<T>,
+		who: &T::AccountId,
+		stake: &Stake<AssetBalanceOf<T>>,
+	) -> DispatchResult {
+		let amount = Self::rewards_owed(incentive, stake);
+		if amount.is_zero() {
+			return Ok(())
+		}
+
+		Self::transfer(
+			AssetKind::Asset(incentive.reward_asset),
+			&Self::incentive_account(incentive_id),
+			who,
+			amount,
+			Preservation::Expendable,
+		)?;
+		Self::deposit_event(Event::RewardsPaid { incentive_id, who: who.clone(), amount });
+		Ok(())
+	}
+}
@@ -1,9 +1,24 @@
+//! A decentralised exchange of constant-product liquidity pools, in the style of Uniswap v2. A
+//! pool holds reserves of two assets, either of which can be the native token, in an account
+//! derived from the pair. Liquidity providers deposit both assets at the pool's ratio in exchange
+//! for the pool's LP token, an asset created with the pool, and swaps trade one asset for the
+//! other while keeping the product of the reserves from decreasing. A fee is taken from the input
+//! of each swap and left in the pool for its liquidity providers, less a protocol share paid to
+//! the protocol fee recipient if one is set. `AdminOrigin` governs the pallet's parameters: the
+//! swap fee, the recipient, who may create pools, fee assets and incentives. Limit orders wait in
+//! an order book until a pool's price reaches them, and are then filled against the pool.
+//! Incentives reward the liquidity providers of a pool who stake its LP tokens.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::traits::fungibles;
-/// Edit this file to define custom logic or remove it if it is not needed.
-/// Learn more about FRAME and the core library of Substrate FRAME pallets:
-/// <https://docs.substrate.io/reference/frame-pallets/>
+use frame_support::{
+	pallet_prelude::*,
+	storage::with_storage_layer,
+	traits::{
+		fungible, fungibles,
+		tokens::{Fortitude, Precision, Preservation},
+	},
+	PalletId,
+};
 pub use pallet::*;
 
 #[cfg(test)]
@@ -15,7 +30,12 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
-use frame_support::traits::fungible;
+mod incentives;
+mod oracle;
+mod orders;
+mod payment;
+pub use oracle::PriceOracle;
+pub use payment::{AssetFeeAdapter, AssetPrice, TwapPrice};
 
 pub type AssetIdOf<T> = <<T as Config>::Fungibles as fungibles::Inspect<
 	<T as frame_system::Config>::AccountId,
@@ -29,12 +49,26 @@ pub type AssetBalanceOf<T> = <<T as Config>::Fungibles as fungibles::Inspect<
 	<T as frame_system::Config>::AccountId,
 >>::Balance;
 
+/// The native token or an asset of `Fungibles`.
+pub type AssetKindOf<T> = AssetKind<AssetIdOf<T>>;
+
+/// A pool's pair of assets, ordered by their encoding.
+pub type PoolIdOf<T> = (AssetKindOf<T>, AssetKindOf<T>);
+
+use sp_core::U256;
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational_with_rounding,
+	traits::{
+		AccountIdConversion, CheckedAdd, Convert, One, Saturating, UniqueSaturatedFrom,
+		UniqueSaturatedInto, Zero,
+	},
+	FixedU128, Permill, Rounding,
+};
+use sp_std::prelude::*;
+
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::{
-		pallet_prelude::*,
-		traits::{fungible, fungibles},
-	};
+	use crate::*;
 	use frame_system::pallet_prelude::*;
 
 	#[pallet::pallet]
@@ -49,7 +83,7 @@ pub mod pallet {
 		/// Type to access the Balances Pallet.
 		type NativeBalance: fungible::Inspect<Self::AccountId>
 			+ fungible::Mutate<Self::AccountId>
-			+ fungible::hold::Inspect<Self::AccountId>
+			+ fungible::hold::Inspect<Self::AccountId, Reason = Self::RuntimeHoldReason>
 			+ fungible::hold::Mutate<Self::AccountId>
 			+ fungible::freeze::Inspect<Self::AccountId>
 			+ fungible::freeze::Mutate<Self::AccountId>;
@@ -58,33 +92,462 @@ pub mod pallet {
 		type Fungibles: fungibles::Inspect<Self::AccountId>
 			+ fungibles::Mutate<Self::AccountId>
 			+ fungibles::Create<Self::AccountId>;
+
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
+
+		/// The pallet's id, used to derive the accounts of pools.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// The origin allowed to set the swap fee, the pool creation allowlist, the protocol fee
+		/// recipient and the assets fees can be paid in, and to create incentives.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The fee taken from the input of each swap until `AdminOrigin` sets another.
+		#[pallet::constant]
+		type DefaultSwapFee: Get<Permill>;
+
+		/// The part of each swap fee paid to the protocol fee recipient, when one is set, instead
+		/// of being left in the pool.
+		#[pallet::constant]
+		type ProtocolFeeShare: Get<Permill>;
+
+		/// The amount of LP tokens minted to the pool itself, and never redeemable, when
+		/// liquidity is first added to it. This keeps the value of one LP token from being
+		/// inflated until it can't be minted in practice.
+		#[pallet::constant]
+		type MintMinLiquidity: Get<AssetBalanceOf<Self>>;
+
+		/// The asset id of the LP token of the pool with the given index. Ids taken by existing
+		/// assets are skipped.
+		type LpTokenId: Convert<u32, AssetIdOf<Self>>;
+
+		/// The maximum number of assets in the path of a multi-hop swap, including the first and
+		/// the last.
+		#[pallet::constant]
+		type MaxHops: Get<u32>;
+
+		/// The minimum number of blocks between snapshots of a pool's price accumulators.
+		#[pallet::constant]
+		type ObservationPeriod: Get<BlockNumberFor<Self>>;
+
+		/// The number of snapshots of a pool's price accumulators kept. Average prices can be
+		/// read over windows of up to about `ObservationPeriod * (MaxObservations - 1)` blocks.
+		#[pallet::constant]
+		type MaxObservations: Get<u32>;
+
+		/// The maximum number of pools whose price accumulators are brought up to date in
+		/// `on_initialize`.
+		#[pallet::constant]
+		type MaxPoolsObservedPerBlock: Get<u32>;
+
+		/// The maximum number of open limit orders for a pair of assets. Once a pair's book is
+		/// full, a new order evicts the order furthest from being filled, if it is closer.
+		#[pallet::constant]
+		type MaxOrdersPerPair: Get<u32>;
+
+		/// The maximum number of open limit orders an account can have.
+		#[pallet::constant]
+		type MaxOrdersPerAccount: Get<u32>;
+
+		/// The native tokens held from the owner of a limit order while it is open, so order
+		/// books aren't filled with orders too small to be worth matching.
+		#[pallet::constant]
+		type OrderDeposit: Get<BalanceOf<Self>>;
+
+		/// The native tokens held from the creator of a pool for as long as the pool exists. Each
+		/// pool adds an account, an LP token, an order book and an oracle observation.
+		#[pallet::constant]
+		type PoolDeposit: Get<BalanceOf<Self>>;
+
+		/// Creates assets for benchmarks.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper<AssetIdOf<Self>>;
+	}
+
+	/// The native token, or an asset of `Fungibles`.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub enum AssetKind<AssetId> {
+		Native,
+		Asset(AssetId),
+	}
+
+	/// A liquidity pool.
+	#[derive(Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub struct PoolInfo<AssetId> {
+		/// The asset minted to liquidity providers in exchange for their share of the reserves.
+		pub lp_token: AssetId,
+	}
+
+	/// The prices of a pool's assets, each in the other, and their sums over blocks. The
+	/// assets are in the order of the pool's id.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub struct PriceAccumulator<BlockNumber> {
+		/// The block the accumulator was last updated in.
+		pub block: BlockNumber,
+		/// The price of the first asset in the second since `block`.
+		pub price_a: FixedU128,
+		/// The price of the second asset in the first since `block`.
+		pub price_b: FixedU128,
+		/// The sum of the first asset's price over each block until `block`, as the inner value
+		/// of a `FixedU128`. Wraps on overflow.
+		pub price_a_cumulative: u128,
+		/// The sum of the second asset's price over each block until `block`, as the inner value
+		/// of a `FixedU128`. Wraps on overflow.
+		pub price_b_cumulative: u128,
+	}
+
+	/// A snapshot of a pool's cumulative prices.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub struct Observation<BlockNumber> {
+		pub block: BlockNumber,
+		pub price_a_cumulative: u128,
+		pub price_b_cumulative: u128,
+	}
+
+	/// An order to sell an exact amount of one asset for at least a minimum amount of another.
+	#[derive(Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub struct LimitOrder<AccountId, AssetKind, Balance, Deposit> {
+		/// The account that placed the order, and receives its output.
+		pub owner: AccountId,
+		pub asset_in: AssetKind,
+		pub asset_out: AssetKind,
+		/// The amount of `asset_in` sold, put aside while the order is open.
+		pub amount_in: Balance,
+		/// The least amount of `asset_out` the order is filled for.
+		pub min_amount_out: Balance,
+		/// The native tokens held from the owner while the order is open.
+		pub deposit: Deposit,
+	}
+
+	/// A reward program for the liquidity providers of a pool, who earn a share of each block's
+	/// rewards in proportion to the LP tokens they stake.
+	#[derive(Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub struct Incentive<AssetId, Balance, BlockNumber> {
+		/// The pool's LP token, which is staked.
+		pub lp_token: AssetId,
+		/// The asset rewards are paid in.
+		pub reward_asset: AssetId,
+		/// The rewards shared between the stakers each block.
+		pub reward_per_block: Balance,
+		/// The funded rewards not yet shared out. Rewards stop when they run out.
+		pub rewards_left: Balance,
+		/// The LP tokens staked.
+		pub total_staked: Balance,
+		/// The rewards earned by one staked LP token since the incentive was created.
+		pub reward_per_share: FixedU128,
+		/// The block rewards were last shared out in.
+		pub last_block: BlockNumber,
+	}
+
+	/// An account's stake in an incentive.
+	#[derive(
+		Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen,
+	)]
+	pub struct Stake<Balance> {
+		/// The LP tokens staked.
+		pub amount: Balance,
+		/// The incentive's `reward_per_share` times `amount` when the staker's rewards were last
+		/// paid, which is not owed to them.
+		pub reward_debt: u128,
 	}
 
-	// The pallet's runtime storage items.
-	// https://docs.substrate.io/main-docs/build/runtime-storage/
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// The native tokens sold by an open limit order.
+		#[codec(index = 0)]
+		LimitOrder,
+		/// The deposit of an open limit order.
+		#[codec(index = 1)]
+		OrderDeposit,
+		/// The deposit of a pool's creator.
+		#[codec(index = 2)]
+		PoolDeposit,
+	}
+
+	#[pallet::type_value]
+	pub fn DefaultSwapFeeValue<T: Config>() -> Permill {
+		T::DefaultSwapFee::get()
+	}
+
+	/// The fee taken from the input of each swap and left in the pool.
+	#[pallet::storage]
+	pub type SwapFee<T: Config> = StorageValue<_, Permill, ValueQuery, DefaultSwapFeeValue<T>>;
+
+	/// Whether only the accounts in `PoolCreators` can create pools.
+	#[pallet::storage]
+	pub type PoolCreationRestricted<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The accounts allowed to create pools while pool creation is restricted.
+	#[pallet::storage]
+	pub type PoolCreators<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+	/// The account paid the protocol's share of swap fees. The whole fee is left in the pool
+	/// while this is unset.
+	#[pallet::storage]
+	pub type ProtocolFeeRecipient<T: Config> = StorageValue<_, T::AccountId>;
+
+	/// Liquidity pools, keyed by their pair of assets. A pool's reserves are the balances of its
+	/// account.
+	#[pallet::storage]
+	pub type Pools<T: Config> =
+		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, PoolInfo<AssetIdOf<T>>>;
+
+	/// The index of the next pool to be created, from which its LP token id is derived.
+	#[pallet::storage]
+	pub type NextPoolIndex<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Pools by their index. Indices skipped along with LP token ids taken by other assets have
+	/// no pool.
+	#[pallet::storage]
+	pub type PoolsByIndex<T: Config> = StorageMap<_, Twox64Concat, u32, PoolIdOf<T>>;
+
+	/// The price accumulators of pools that have had liquidity.
+	#[pallet::storage]
+	pub type PriceAccumulators<T: Config> =
+		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, PriceAccumulator<BlockNumberFor<T>>>;
+
+	/// Snapshots of pools' price accumulators, oldest first.
+	#[pallet::storage]
+	pub type Observations<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		PoolIdOf<T>,
+		BoundedVec<Observation<BlockNumberFor<T>>, T::MaxObservations>,
+		ValueQuery,
+	>;
+
+	/// The index of the next pool to be observed in `on_initialize`.
+	#[pallet::storage]
+	pub type ObservationCursor<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Open limit orders, by id.
+	#[pallet::storage]
+	pub type Orders<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u32,
+		LimitOrder<T::AccountId, AssetKindOf<T>, AssetBalanceOf<T>, BalanceOf<T>>,
+	>;
+
+	/// The number of open limit orders of each account.
+	#[pallet::storage]
+	pub type OrderCounts<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// The amount of each asset put aside by each account's open limit orders, which is frozen on
+	/// the account through `pallet_assets`' `Freezer`.
+	#[pallet::storage]
+	pub type OrderFunds<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AssetIdOf<T>,
+		Blake2_128Concat,
+		T::AccountId,
+		AssetBalanceOf<T>,
+	>;
+
+	/// The ids of the open limit orders of each pool's pair of assets, oldest first.
+	#[pallet::storage]
+	pub type OrderBooks<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		PoolIdOf<T>,
+		BoundedVec<u32, T::MaxOrdersPerPair>,
+		ValueQuery,
+	>;
+
+	/// The id of the next limit order to be placed.
 	#[pallet::storage]
-	#[pallet::getter(fn something)]
-	// Learn more about declaring storage items:
-	// https://docs.substrate.io/main-docs/build/runtime-storage/#declaring-storage-items
-	pub type Something<T> = StorageValue<_, u32>;
+	pub type NextOrderId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// The index of the next pool whose order book is matched in `on_idle`.
+	#[pallet::storage]
+	pub type OrderCursor<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Liquidity mining incentives, by id.
+	#[pallet::storage]
+	pub type Incentives<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u32,
+		Incentive<AssetIdOf<T>, AssetBalanceOf<T>, BlockNumberFor<T>>,
+	>;
+
+	/// The id of the next incentive to be created.
+	#[pallet::storage]
+	pub type NextIncentiveId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// The stakes of accounts in incentives.
+	#[pallet::storage]
+	pub type Stakes<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		u32,
+		Blake2_128Concat,
+		T::AccountId,
+		Stake<AssetBalanceOf<T>>,
+	>;
+
+	/// Assets that transaction fees can be paid in.
+	#[pallet::storage]
+	pub type FeeAssets<T: Config> = StorageMap<_, Blake2_128Concat, AssetIdOf<T>, (), OptionQuery>;
+
+	/// The asset an account has chosen to pay its transaction fees in.
+	#[pallet::storage]
+	pub type AccountFeeAsset<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, AssetIdOf<T>, OptionQuery>;
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/main-docs/build/events-errors/
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Event documentation should end with an array that provides descriptive names for event
-		/// parameters. [something, who]
-		SomethingStored { something: u32, who: T::AccountId },
+		/// A pool was created. [creator, pool_id, pool_account, lp_token]
+		PoolCreated {
+			creator: T::AccountId,
+			pool_id: PoolIdOf<T>,
+			pool_account: T::AccountId,
+			lp_token: AssetIdOf<T>,
+		},
+		/// Liquidity was added to a pool. [who, pool_id, amount_a, amount_b, lp_minted]
+		LiquidityAdded {
+			who: T::AccountId,
+			pool_id: PoolIdOf<T>,
+			amount_a: AssetBalanceOf<T>,
+			amount_b: AssetBalanceOf<T>,
+			lp_minted: AssetBalanceOf<T>,
+		},
+		/// Liquidity was removed from a pool. [who, pool_id, amount_a, amount_b, lp_burned]
+		LiquidityRemoved {
+			who: T::AccountId,
+			pool_id: PoolIdOf<T>,
+			amount_a: AssetBalanceOf<T>,
+			amount_b: AssetBalanceOf<T>,
+			lp_burned: AssetBalanceOf<T>,
+		},
+		/// An asset was swapped for another. [who, asset_in, asset_out, amount_in, amount_out]
+		SwapExecuted {
+			who: T::AccountId,
+			asset_in: AssetKindOf<T>,
+			asset_out: AssetKindOf<T>,
+			amount_in: AssetBalanceOf<T>,
+			amount_out: AssetBalanceOf<T>,
+		},
+		/// Transaction fees can now be paid in an asset.
+		FeeAssetApproved { asset: AssetIdOf<T> },
+		/// Transaction fees can no longer be paid in an asset.
+		FeeAssetRemoved { asset: AssetIdOf<T> },
+		/// An account chose the asset it pays its transaction fees in, or `None` for the native
+		/// token.
+		FeeAssetSet { who: T::AccountId, asset: Option<AssetIdOf<T>> },
+		/// An account paid a transaction fee in an asset, after refunding unused weight.
+		AssetFeePaid { who: T::AccountId, asset: AssetIdOf<T>, fee: AssetBalanceOf<T> },
+		/// A limit order was placed. [order_id, who, asset_in, asset_out, amount_in,
+		/// min_amount_out]
+		LimitOrderPlaced {
+			order_id: u32,
+			who: T::AccountId,
+			asset_in: AssetKindOf<T>,
+			asset_out: AssetKindOf<T>,
+			amount_in: AssetBalanceOf<T>,
+			min_amount_out: AssetBalanceOf<T>,
+		},
+		/// A limit order was cancelled by its owner. [order_id, who]
+		LimitOrderCancelled { order_id: u32, who: T::AccountId },
+		/// A limit order was evicted from its full order book by an order closer to being filled,
+		/// and its funds and deposit returned to its owner. [order_id, who]
+		LimitOrderEvicted { order_id: u32, who: T::AccountId },
+		/// A limit order was filled against its pool. [order_id, who, amount_in, amount_out]
+		LimitOrderFilled {
+			order_id: u32,
+			who: T::AccountId,
+			amount_in: AssetBalanceOf<T>,
+			amount_out: AssetBalanceOf<T>,
+		},
+		/// An incentive was created for a pool. [incentive_id, pool_id, reward_asset,
+		/// reward_per_block]
+		IncentiveCreated {
+			incentive_id: u32,
+			pool_id: PoolIdOf<T>,
+			reward_asset: AssetIdOf<T>,
+			reward_per_block: AssetBalanceOf<T>,
+		},
+		/// Rewards were added to an incentive. [incentive_id, who, amount]
+		IncentiveFunded { incentive_id: u32, who: T::AccountId, amount: AssetBalanceOf<T> },
+		/// LP tokens were staked in an incentive. [incentive_id, who, amount]
+		Staked { incentive_id: u32, who: T::AccountId, amount: AssetBalanceOf<T> },
+		/// LP tokens were unstaked from an incentive. [incentive_id, who, amount]
+		Unstaked { incentive_id: u32, who: T::AccountId, amount: AssetBalanceOf<T> },
+		/// A staker was paid its rewards from an incentive. [incentive_id, who, amount]
+		RewardsPaid { incentive_id: u32, who: T::AccountId, amount: AssetBalanceOf<T> },
+		/// The swap fee was set. [fee]
+		SwapFeeSet { fee: Permill },
+		/// Pool creation was restricted to the allowlist, or opened to all. [restricted]
+		PoolCreationRestrictedSet { restricted: bool },
+		/// An account was added to or removed from the pool creation allowlist. [who, allowed]
+		PoolCreatorSet { who: T::AccountId, allowed: bool },
+		/// The protocol fee recipient was set, or unset. [recipient]
+		ProtocolFeeRecipientSet { recipient: Option<T::AccountId> },
 	}
 
 	// Errors inform users that something went wrong.
 	#[pallet::error]
 	pub enum Error<T> {
-		/// Error names should be descriptive.
-		NoneValue,
-		/// Errors should have helpful documentation associated with them.
-		StorageOverflow,
+		/// A pool can't trade an asset for itself.
+		IdenticalAssets,
+		/// A pool already exists for the pair of assets.
+		PoolAlreadyExists,
+		/// No pool exists for the pair of assets.
+		PoolNotFound,
+		/// Amounts must be greater than zero.
+		ZeroAmount,
+		/// The pool's reserves are too small for the operation.
+		InsufficientLiquidity,
+		/// The liquidity added is too small to mint any LP tokens.
+		InsufficientLiquidityMinted,
+		/// An amount is below the minimum the signer accepts.
+		AmountBelowMinimum,
+		/// An amount is above the maximum the signer accepts.
+		AmountAboveMaximum,
+		/// An arithmetic operation overflowed.
+		Overflow,
+		/// A swap path must have between two and `MaxHops` assets, none of them twice.
+		InvalidPath,
+		/// Transaction fees cannot be paid in the asset.
+		FeeAssetNotApproved,
+		/// The pair of assets already has `MaxOrdersPerPair` open limit orders, none of them
+		/// further from being filled than the new order.
+		OrderBookFull,
+		/// The signer already has `MaxOrdersPerAccount` open limit orders.
+		TooManyOrders,
+		/// No open limit order has the id.
+		OrderNotFound,
+		/// Only the owner of a limit order can cancel it.
+		NotOrderOwner,
+		/// No incentive has the id.
+		IncentiveNotFound,
+		/// The signer has staked fewer LP tokens in the incentive.
+		InsufficientStake,
+		/// The swap fee must be less than the whole of the input.
+		InvalidSwapFee,
+		/// Pool creation is restricted, and the signer is not on the allowlist.
+		NotPoolCreator,
+		/// The asset does not exist.
+		AssetNotFound,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_now: BlockNumberFor<T>) -> Weight {
+			Self::observe_pools()
+		}
+
+		fn on_idle(_now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::match_orders(remaining_weight)
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -92,43 +555,924 @@ pub mod pallet {
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// An example dispatchable that takes a singles value as a parameter, writes the value to
-		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
+		/// Create an empty pool for a pair of assets, along with its LP token. While pool creation
+		/// is restricted, only accounts on the allowlist can create pools. Both assets must exist,
+		/// and `PoolDeposit` is held from the creator for as long as the pool exists.
 		#[pallet::call_index(0)]
-		#[pallet::weight(Weight::default())]
-		pub fn do_something(origin: OriginFor<T>, something: u32) -> DispatchResult {
-			// Check that the extrinsic was signed and get the signer.
-			// This function will return an error if the extrinsic is not signed.
-			// https://docs.substrate.io/main-docs/build/origins/
-			let who = ensure_signed(origin)?;
+		#[pallet::weight(T::DbWeight::get().reads_writes(9, 8))]
+		pub fn create_pool(
+			origin: OriginFor<T>,
+			asset_a: AssetKindOf<T>,
+			asset_b: AssetKindOf<T>,
+		) -> DispatchResult {
+			let creator = ensure_signed(origin)?;
+			ensure!(
+				!PoolCreationRestricted::<T>::get() || PoolCreators::<T>::contains_key(&creator),
+				Error::<T>::NotPoolCreator
+			);
+			ensure!(asset_a != asset_b, Error::<T>::IdenticalAssets);
+			let pool_id = Self::pool_id(asset_a, asset_b);
+			ensure!(!Pools::<T>::contains_key(&pool_id), Error::<T>::PoolAlreadyExists);
+			for asset in [asset_a, asset_b] {
+				if let AssetKind::Asset(id) = asset {
+					ensure!(
+						<T::Fungibles as fungibles::Inspect<T::AccountId>>::asset_exists(id),
+						Error::<T>::AssetNotFound
+					);
+				}
+			}
+
+			let deposit = T::PoolDeposit::get();
+			if !deposit.is_zero() {
+				T::NativeBalance::hold(&HoldReason::PoolDeposit.into(), &creator, deposit)?;
+			}
+
+			// The pool account holds the reserves, and must exist to receive assets that are not
+			// sufficient.
+			let pool_account = Self::pool_account(&pool_id);
+			frame_system::Pallet::<T>::inc_providers(&pool_account);
 
-			// Update storage.
-			<Something<T>>::put(something);
+			let (index, lp_token) = Self::next_lp_token();
+			<T::Fungibles as fungibles::Create<T::AccountId>>::create(
+				lp_token,
+				pool_account.clone(),
+				false,
+				One::one(),
+			)?;
+			Pools::<T>::insert(&pool_id, PoolInfo { lp_token });
+			PoolsByIndex::<T>::insert(index, &pool_id);
 
-			// Emit an event.
-			Self::deposit_event(Event::SomethingStored { something, who });
-			// Return a successful DispatchResultWithPostInfo
+			Self::deposit_event(Event::PoolCreated { creator, pool_id, pool_account, lp_token });
 			Ok(())
 		}
 
-		/// An example dispatchable that may throw a custom error.
+		/// Deposit both assets of a pool in exchange for its LP tokens. The first deposit sets
+		/// the pool's price. Later deposits are made at the pool's current ratio, using as much
+		/// of the desired amounts as possible, and fail if either amount falls below its minimum.
 		#[pallet::call_index(1)]
-		#[pallet::weight(Weight::default())]
-		pub fn cause_error(origin: OriginFor<T>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			// Read a value from storage.
-			match <Something<T>>::get() {
-				// Return an error if the value has not been set.
-				None => Err(Error::<T>::NoneValue.into()),
-				Some(old) => {
-					// Increment the value read from storage; will error in the event of overflow.
-					let new = old.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
-					// Update the value in storage with the incremented result.
-					<Something<T>>::put(new);
-					Ok(())
+		#[pallet::weight(T::DbWeight::get().reads_writes(9, 8))]
+		pub fn add_liquidity(
+			origin: OriginFor<T>,
+			asset_a: AssetKindOf<T>,
+			asset_b: AssetKindOf<T>,
+			amount_a_desired: AssetBalanceOf<T>,
+			amount_b_desired: AssetBalanceOf<T>,
+			amount_a_min: AssetBalanceOf<T>,
+			amount_b_min: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				!amount_a_desired.is_zero() && !amount_b_desired.is_zero(),
+				Error::<T>::ZeroAmount
+			);
+			let (pool_id, pool) = Self::get_pool(asset_a, asset_b)?;
+			let pool_account = Self::pool_account(&pool_id);
+			let reserve_a = Self::balance(asset_a, &pool_account);
+			let reserve_b = Self::balance(asset_b, &pool_account);
+			let total_supply = Self::lp_supply(pool.lp_token);
+
+			let (amount_a, amount_b) = if total_supply.is_zero() {
+				(amount_a_desired, amount_b_desired)
+			} else {
+				let amount_b_optimal = Self::quote(amount_a_desired, reserve_a, reserve_b)?;
+				if amount_b_optimal <= amount_b_desired {
+					(amount_a_desired, amount_b_optimal)
+				} else {
+					let amount_a_optimal = Self::quote(amount_b_desired, reserve_b, reserve_a)?;
+					(amount_a_optimal.min(amount_a_desired), amount_b_desired)
+				}
+			};
+			ensure!(
+				amount_a >= amount_a_min && amount_b >= amount_b_min,
+				Error::<T>::AmountBelowMinimum
+			);
+
+			let lp_minted = if total_supply.is_zero() {
+				let liquidity = Self::initial_liquidity(amount_a, amount_b)?;
+				let locked = T::MintMinLiquidity::get();
+				ensure!(liquidity > locked, Error::<T>::InsufficientLiquidityMinted);
+				if !locked.is_zero() {
+					Self::mint_lp(pool.lp_token, &pool_account, locked)?;
+				}
+				liquidity - locked
+			} else {
+				Self::mul_div(amount_a, total_supply, reserve_a, Rounding::Down)?
+					.min(Self::mul_div(amount_b, total_supply, reserve_b, Rounding::Down)?)
+			};
+			ensure!(!lp_minted.is_zero(), Error::<T>::InsufficientLiquidityMinted);
+
+			Self::transfer(asset_a, &who, &pool_account, amount_a, Preservation::Preserve)?;
+			Self::transfer(asset_b, &who, &pool_account, amount_b, Preservation::Preserve)?;
+			Self::mint_lp(pool.lp_token, &who, lp_minted)?;
+			Self::update_price(&pool_id, true);
+
+			Self::deposit_event(Event::LiquidityAdded {
+				who,
+				pool_id,
+				amount_a,
+				amount_b,
+				lp_minted,
+			});
+			Ok(())
+		}
+
+		/// Burn LP tokens of a pool in exchange for the share of its reserves they represent.
+		/// Fails if the amount of either asset falls below its minimum.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(9, 8))]
+		pub fn remove_liquidity(
+			origin: OriginFor<T>,
+			asset_a: AssetKindOf<T>,
+			asset_b: AssetKindOf<T>,
+			lp_amount: AssetBalanceOf<T>,
+			amount_a_min: AssetBalanceOf<T>,
+			amount_b_min: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!lp_amount.is_zero(), Error::<T>::ZeroAmount);
+			let (pool_id, pool) = Self::get_pool(asset_a, asset_b)?;
+			let pool_account = Self::pool_account(&pool_id);
+			let total_supply = Self::lp_supply(pool.lp_token);
+			ensure!(lp_amount <= total_supply, Error::<T>::InsufficientLiquidity);
+
+			let reserve_a = Self::balance(asset_a, &pool_account);
+			let reserve_b = Self::balance(asset_b, &pool_account);
+			let amount_a = Self::mul_div(lp_amount, reserve_a, total_supply, Rounding::Down)?;
+			let amount_b = Self::mul_div(lp_amount, reserve_b, total_supply, Rounding::Down)?;
+			ensure!(
+				amount_a >= amount_a_min && amount_b >= amount_b_min,
+				Error::<T>::AmountBelowMinimum
+			);
+
+			<T::Fungibles as fungibles::Mutate<T::AccountId>>::burn_from(
+				pool.lp_token,
+				&who,
+				lp_amount,
+				Precision::Exact,
+				Fortitude::Polite,
+			)?;
+			Self::transfer(asset_a, &pool_account, &who, amount_a, Preservation::Expendable)?;
+			Self::transfer(asset_b, &pool_account, &who, amount_b, Preservation::Expendable)?;
+			Self::update_price(&pool_id, true);
+
+			Self::deposit_event(Event::LiquidityRemoved {
+				who,
+				pool_id,
+				amount_a,
+				amount_b,
+				lp_burned: lp_amount,
+			});
+			Ok(())
+		}
+
+		/// Swap an exact amount of one asset for as much of another as the pool gives, failing
+		/// if that is less than `amount_out_min`.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(9, 8))]
+		pub fn swap_exact_in(
+			origin: OriginFor<T>,
+			asset_in: AssetKindOf<T>,
+			asset_out: AssetKindOf<T>,
+			amount_in: AssetBalanceOf<T>,
+			amount_out_min: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount_in.is_zero(), Error::<T>::ZeroAmount);
+			let (pool_id, _) = Self::get_pool(asset_in, asset_out)?;
+			let pool_account = Self::pool_account(&pool_id);
+
+			let amount_out = Self::get_amount_out(
+				amount_in,
+				Self::balance(asset_in, &pool_account),
+				Self::balance(asset_out, &pool_account),
+			)?;
+			ensure!(amount_out >= amount_out_min, Error::<T>::AmountBelowMinimum);
+
+			Self::swap_path(&who, &[asset_in, asset_out], &[amount_in, amount_out])
+		}
+
+		/// Swap as little of one asset as the pool takes for an exact amount of another, failing
+		/// if that is more than `amount_in_max`.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(9, 8))]
+		pub fn swap_exact_out(
+			origin: OriginFor<T>,
+			asset_in: AssetKindOf<T>,
+			asset_out: AssetKindOf<T>,
+			amount_out: AssetBalanceOf<T>,
+			amount_in_max: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount_out.is_zero(), Error::<T>::ZeroAmount);
+			let (pool_id, _) = Self::get_pool(asset_in, asset_out)?;
+			let pool_account = Self::pool_account(&pool_id);
+
+			let amount_in = Self::get_amount_in(
+				amount_out,
+				Self::balance(asset_in, &pool_account),
+				Self::balance(asset_out, &pool_account),
+			)?;
+			ensure!(amount_in <= amount_in_max, Error::<T>::AmountAboveMaximum);
+
+			Self::swap_path(&who, &[asset_in, asset_out], &[amount_in, amount_out])
+		}
+
+		/// Swap an exact amount of the first asset of a path for as much of the last as the
+		/// path's pools give, through each pair of consecutive assets' pool in turn. Fails if
+		/// that is less than `amount_out_min`. A path can't visit an asset twice.
+		#[pallet::call_index(8)]
+		#[pallet::weight(Pallet::<T>::swap_weight(path.len() as u32))]
+		pub fn swap_exact_in_path(
+			origin: OriginFor<T>,
+			path: BoundedVec<AssetKindOf<T>, T::MaxHops>,
+			amount_in: AssetBalanceOf<T>,
+			amount_out_min: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount_in.is_zero(), Error::<T>::ZeroAmount);
+
+			let amounts = Self::quote_path(&path, amount_in)?;
+			let amount_out = amounts.last().copied().unwrap_or_else(Zero::zero);
+			ensure!(amount_out >= amount_out_min, Error::<T>::AmountBelowMinimum);
+
+			Self::swap_path(&who, &path, &amounts)
+		}
+
+		/// Allow transaction fees to be paid in an asset. Must be called by `AdminOrigin`.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn approve_fee_asset(origin: OriginFor<T>, asset: AssetIdOf<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			FeeAssets::<T>::insert(asset, ());
+			Self::deposit_event(Event::FeeAssetApproved { asset });
+			Ok(())
+		}
+
+		/// Stop transaction fees being paid in an asset. Accounts that chose the asset pay in the
+		/// native token instead. Must be called by `AdminOrigin`.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_fee_asset(origin: OriginFor<T>, asset: AssetIdOf<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(FeeAssets::<T>::contains_key(asset), Error::<T>::FeeAssetNotApproved);
+
+			FeeAssets::<T>::remove(asset);
+			Self::deposit_event(Event::FeeAssetRemoved { asset });
+			Ok(())
+		}
+
+		/// Choose the asset the signer pays its transaction fees in, or `None` to pay in the
+		/// native token.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_fee_asset(origin: OriginFor<T>, asset: Option<AssetIdOf<T>>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			match asset {
+				Some(asset) => {
+					ensure!(FeeAssets::<T>::contains_key(asset), Error::<T>::FeeAssetNotApproved);
+					AccountFeeAsset::<T>::insert(&who, asset);
+				},
+				None => AccountFeeAsset::<T>::remove(&who),
+			}
+			Self::deposit_event(Event::FeeAssetSet { who, asset });
+			Ok(())
+		}
+
+		/// Place an order to sell an exact amount of one asset for at least `min_amount_out` of
+		/// another. The amount sold and `OrderDeposit` are put aside until the order is filled or
+		/// cancelled. The order is filled against the assets' pool in `on_idle`, once the pool
+		/// gives at least `min_amount_out`. If the pair's order book is full, the order evicts
+		/// the order furthest from being filled, if it is closer to being filled.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(
+			10u64.saturating_add(T::MaxOrdersPerPair::get().into()),
+			15,
+		))]
+		pub fn place_limit_order(
+			origin: OriginFor<T>,
+			asset_in: AssetKindOf<T>,
+			asset_out: AssetKindOf<T>,
+			amount_in: AssetBalanceOf<T>,
+			min_amount_out: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount_in.is_zero() && !min_amount_out.is_zero(), Error::<T>::ZeroAmount);
+			ensure!(
+				OrderCounts::<T>::get(&who) < T::MaxOrdersPerAccount::get(),
+				Error::<T>::TooManyOrders
+			);
+			let (pool_id, _) = Self::get_pool(asset_in, asset_out)?;
+
+			let deposit = T::OrderDeposit::get();
+			let order = LimitOrder {
+				owner: who.clone(),
+				asset_in,
+				asset_out,
+				amount_in,
+				min_amount_out,
+				deposit,
+			};
+			if OrderBooks::<T>::decode_len(&pool_id).unwrap_or(0) as u32 >=
+				T::MaxOrdersPerPair::get()
+			{
+				Self::evict_furthest_order(&pool_id, &order)?;
+			}
+
+			let order_id = NextOrderId::<T>::get();
+			OrderBooks::<T>::try_mutate(&pool_id, |book| book.try_push(order_id))
+				.map_err(|_| Error::<T>::OrderBookFull)?;
+			if !deposit.is_zero() {
+				T::NativeBalance::hold(&HoldReason::OrderDeposit.into(), &who, deposit)?;
+			}
+			Self::hold_order_funds(asset_in, &who, amount_in)?;
+			Orders::<T>::insert(order_id, order);
+			OrderCounts::<T>::mutate(&who, |count| *count = count.saturating_add(1));
+			NextOrderId::<T>::put(order_id.wrapping_add(1));
+
+			Self::deposit_event(Event::LimitOrderPlaced {
+				order_id,
+				who,
+				asset_in,
+				asset_out,
+				amount_in,
+				min_amount_out,
+			});
+			Ok(())
+		}
+
+		/// Cancel an open limit order of the signer, returning the amount it put aside and its
+		/// deposit.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(6, 7))]
+		pub fn cancel_order(origin: OriginFor<T>, order_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let order = Orders::<T>::get(order_id).ok_or(Error::<T>::OrderNotFound)?;
+			ensure!(order.owner == who, Error::<T>::NotOrderOwner);
+
+			Self::release_order_funds(order.asset_in, &who, order.amount_in)?;
+			Self::close_order(&order)?;
+			Self::remove_order(order_id, &order);
+
+			Self::deposit_event(Event::LimitOrderCancelled { order_id, who });
+			Ok(())
+		}
+
+		/// Create an incentive for the liquidity providers of a pool, sharing `reward_per_block`
+		/// of `reward_asset` each block between the accounts staking its LP tokens. The rewards
+		/// must be funded with `fund_incentive`. Must be called by `AdminOrigin`.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn create_incentive(
+			origin: OriginFor<T>,
+			asset_a: AssetKindOf<T>,
+			asset_b: AssetKindOf<T>,
+			reward_asset: AssetIdOf<T>,
+			reward_per_block: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(!reward_per_block.is_zero(), Error::<T>::ZeroAmount);
+			let (pool_id, pool) = Self::get_pool(asset_a, asset_b)?;
+
+			let incentive_id = NextIncentiveId::<T>::get();
+			// The incentive account holds the rewards and the staked LP tokens, and must exist to
+			// receive assets that are not sufficient.
+			frame_system::Pallet::<T>::inc_providers(&Self::incentive_account(incentive_id));
+			Incentives::<T>::insert(
+				incentive_id,
+				Incentive {
+					lp_token: pool.lp_token,
+					reward_asset,
+					reward_per_block,
+					rewards_left: Zero::zero(),
+					total_staked: Zero::zero(),
+					reward_per_share: Zero::zero(),
+					last_block: frame_system::Pallet::<T>::block_number(),
 				},
+			);
+			NextIncentiveId::<T>::put(incentive_id.wrapping_add(1));
+
+			Self::deposit_event(Event::IncentiveCreated {
+				incentive_id,
+				pool_id,
+				reward_asset,
+				reward_per_block,
+			});
+			Ok(())
+		}
+
+		/// Add rewards to an incentive from the signer.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn fund_incentive(
+			origin: OriginFor<T>,
+			incentive_id: u32,
+			amount: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let mut incentive = Self::accrued_incentive(incentive_id)?;
+
+			Self::transfer(
+				AssetKind::Asset(incentive.reward_asset),
+				&who,
+				&Self::incentive_account(incentive_id),
+				amount,
+				Preservation::Preserve,
+			)?;
+			incentive.rewards_left =
+				incentive.rewards_left.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			Incentives::<T>::insert(incentive_id, incentive);
+
+			Self::deposit_event(Event::IncentiveFunded { incentive_id, who, amount });
+			Ok(())
+		}
+
+		/// Stake LP tokens of an incentive's pool, paying out the signer's rewards so far.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 6))]
+		pub fn stake(
+			origin: OriginFor<T>,
+			incentive_id: u32,
+			amount: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let mut incentive = Self::accrued_incentive(incentive_id)?;
+			let mut stake = Stakes::<T>::get(incentive_id, &who).unwrap_or_default();
+			Self::pay_rewards(incentive_id, &incentive, &who, &stake)?;
+
+			Self::transfer(
+				AssetKind::Asset(incentive.lp_token),
+				&who,
+				&Self::incentive_account(incentive_id),
+				amount,
+				Preservation::Expendable,
+			)?;
+			stake.amount = stake.amount.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			incentive.total_staked =
+				incentive.total_staked.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			stake.reward_debt = Self::reward_debt(&incentive, stake.amount);
+			Stakes::<T>::insert(incentive_id, &who, stake);
+			Incentives::<T>::insert(incentive_id, incentive);
+
+			Self::deposit_event(Event::Staked { incentive_id, who, amount });
+			Ok(())
+		}
+
+		/// Unstake LP tokens from an incentive, paying out the signer's rewards so far.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 6))]
+		pub fn unstake(
+			origin: OriginFor<T>,
+			incentive_id: u32,
+			amount: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let mut incentive = Self::accrued_incentive(incentive_id)?;
+			let mut stake = Stakes::<T>::get(incentive_id, &who).unwrap_or_default();
+			ensure!(stake.amount >= amount, Error::<T>::InsufficientStake);
+			Self::pay_rewards(incentive_id, &incentive, &who, &stake)?;
+
+			Self::transfer(
+				AssetKind::Asset(incentive.lp_token),
+				&Self::incentive_account(incentive_id),
+				&who,
+				amount,
+				Preservation::Expendable,
+			)?;
+			stake.amount -= amount;
+			incentive.total_staked = incentive.total_staked.saturating_sub(amount);
+			if stake.amount.is_zero() {
+				Stakes::<T>::remove(incentive_id, &who);
+			} else {
+				stake.reward_debt = Self::reward_debt(&incentive, stake.amount);
+				Stakes::<T>::insert(incentive_id, &who, stake);
+			}
+			Incentives::<T>::insert(incentive_id, incentive);
+
+			Self::deposit_event(Event::Unstaked { incentive_id, who, amount });
+			Ok(())
+		}
+
+		/// Pay out the signer's rewards from an incentive.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(4, 4))]
+		pub fn claim_rewards(origin: OriginFor<T>, incentive_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let incentive = Self::accrued_incentive(incentive_id)?;
+			let mut stake = Stakes::<T>::get(incentive_id, &who).unwrap_or_default();
+			Self::pay_rewards(incentive_id, &incentive, &who, &stake)?;
+
+			if !stake.amount.is_zero() {
+				stake.reward_debt = Self::reward_debt(&incentive, stake.amount);
+				Stakes::<T>::insert(incentive_id, &who, stake);
+			}
+			Incentives::<T>::insert(incentive_id, incentive);
+			Ok(())
+		}
+
+		/// Set the fee taken from the input of each swap. Must be called by `AdminOrigin`.
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_swap_fee(origin: OriginFor<T>, fee: Permill) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(fee < Permill::one(), Error::<T>::InvalidSwapFee);
+
+			SwapFee::<T>::put(fee);
+			Self::deposit_event(Event::SwapFeeSet { fee });
+			Ok(())
+		}
+
+		/// Restrict pool creation to the accounts on the allowlist, or open it to all. Must be
+		/// called by `AdminOrigin`.
+		#[pallet::call_index(17)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_pool_creation_restricted(
+			origin: OriginFor<T>,
+			restricted: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			PoolCreationRestricted::<T>::put(restricted);
+			Self::deposit_event(Event::PoolCreationRestrictedSet { restricted });
+			Ok(())
+		}
+
+		/// Add an account to the pool creation allowlist, or remove it. Must be called by
+		/// `AdminOrigin`.
+		#[pallet::call_index(18)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_pool_creator(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			allowed: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			if allowed {
+				PoolCreators::<T>::insert(&who, ());
+			} else {
+				PoolCreators::<T>::remove(&who);
 			}
+			Self::deposit_event(Event::PoolCreatorSet { who, allowed });
+			Ok(())
+		}
+
+		/// Set the account paid the protocol's share of swap fees, or `None` to leave the whole
+		/// fee in the pools. Must be called by `AdminOrigin`.
+		#[pallet::call_index(19)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_protocol_fee_recipient(
+			origin: OriginFor<T>,
+			recipient: Option<T::AccountId>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ProtocolFeeRecipient::<T>::set(recipient.clone());
+			Self::deposit_event(Event::ProtocolFeeRecipientSet { recipient });
+			Ok(())
 		}
 	}
 }
+
+/// Creates assets for benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper<AssetId> {
+	/// The id of the `i`th asset created by a benchmark.
+	fn asset_id(i: u32) -> AssetId;
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl<AssetId: From<u32>> BenchmarkHelper<AssetId> for () {
+	fn asset_id(i: u32) -> AssetId {
+		i.into()
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The id of the pool for a pair of assets, which orders them by their encoding.
+	pub fn pool_id(asset_a: AssetKindOf<T>, asset_b: AssetKindOf<T>) -> PoolIdOf<T> {
+		if asset_a.encode() <= asset_b.encode() {
+			(asset_a, asset_b)
+		} else {
+			(asset_b, asset_a)
+		}
+	}
+
+	/// The account holding a pool's reserves.
+	pub fn pool_account(pool_id: &PoolIdOf<T>) -> T::AccountId {
+		T::PalletId::get().into_sub_account_truncating(pool_id)
+	}
+
+	/// The reserves of the pool for a pair of assets, in the order given, or `None` if no pool
+	/// exists for them.
+	pub fn reserves(
+		asset_a: AssetKindOf<T>,
+		asset_b: AssetKindOf<T>,
+	) -> Option<(AssetBalanceOf<T>, AssetBalanceOf<T>)> {
+		let pool_id = Self::pool_id(asset_a, asset_b);
+		if !Pools::<T>::contains_key(&pool_id) {
+			return None
+		}
+		let pool_account = Self::pool_account(&pool_id);
+		Some((Self::balance(asset_a, &pool_account), Self::balance(asset_b, &pool_account)))
+	}
+
+	/// The amount of the output asset a swap of `amount_in` gives, after the swap fee.
+	pub fn get_amount_out(
+		amount_in: AssetBalanceOf<T>,
+		reserve_in: AssetBalanceOf<T>,
+		reserve_out: AssetBalanceOf<T>,
+	) -> Result<AssetBalanceOf<T>, Error<T>> {
+		ensure!(!reserve_in.is_zero() && !reserve_out.is_zero(), Error::<T>::InsufficientLiquidity);
+		let amount_in: u128 = amount_in.unique_saturated_into();
+		let amount_in_with_fee = SwapFee::<T>::get().left_from_one().mul_floor(amount_in);
+		let reserve_in: u128 = reserve_in.unique_saturated_into();
+		let denominator = reserve_in.checked_add(amount_in_with_fee).ok_or(Error::<T>::Overflow)?;
+		let amount_out = multiply_by_rational_with_rounding(
+			amount_in_with_fee,
+			reserve_out.unique_saturated_into(),
+			denominator,
+			Rounding::Down,
+		)
+		.ok_or(Error::<T>::Overflow)?;
+		let amount_out = Self::from_u128(amount_out)?;
+		ensure!(!amount_out.is_zero(), Error::<T>::InsufficientLiquidity);
+		Ok(amount_out)
+	}
+
+	/// The amount of the input asset a swap giving `amount_out` takes, including the swap fee.
+	pub fn get_amount_in(
+		amount_out: AssetBalanceOf<T>,
+		reserve_in: AssetBalanceOf<T>,
+		reserve_out: AssetBalanceOf<T>,
+	) -> Result<AssetBalanceOf<T>, Error<T>> {
+		ensure!(
+			!reserve_in.is_zero() && amount_out < reserve_out,
+			Error::<T>::InsufficientLiquidity
+		);
+		let amount_in =
+			Self::mul_div(reserve_in, amount_out, reserve_out - amount_out, Rounding::Up)?;
+		let amount_in_with_fee = multiply_by_rational_with_rounding(
+			amount_in.unique_saturated_into(),
+			Permill::ACCURACY.into(),
+			SwapFee::<T>::get().left_from_one().deconstruct().into(),
+			Rounding::Up,
+		)
+		.ok_or(Error::<T>::Overflow)?;
+		Self::from_u128(amount_in_with_fee)
+	}
+
+	/// The amount of `reserve_b`'s asset worth `amount` of `reserve_a`'s at the pool's price.
+	pub fn quote(
+		amount: AssetBalanceOf<T>,
+		reserve_a: AssetBalanceOf<T>,
+		reserve_b: AssetBalanceOf<T>,
+	) -> Result<AssetBalanceOf<T>, Error<T>> {
+		ensure!(!reserve_a.is_zero() && !reserve_b.is_zero(), Error::<T>::InsufficientLiquidity);
+		Self::mul_div(amount, reserve_b, reserve_a, Rounding::Down)
+	}
+
+	fn get_pool(
+		asset_a: AssetKindOf<T>,
+		asset_b: AssetKindOf<T>,
+	) -> Result<(PoolIdOf<T>, PoolInfo<AssetIdOf<T>>), Error<T>> {
+		ensure!(asset_a != asset_b, Error::<T>::IdenticalAssets);
+		let pool_id = Self::pool_id(asset_a, asset_b);
+		let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+		Ok((pool_id, pool))
+	}
+
+	/// The amounts of each asset of a swap path, starting with `amount_in` of the first asset
+	/// and ending with the amount of the last one the swap gives.
+	pub fn quote_path(
+		path: &[AssetKindOf<T>],
+		amount_in: AssetBalanceOf<T>,
+	) -> Result<Vec<AssetBalanceOf<T>>, DispatchError> {
+		ensure!(
+			path.len() >= 2 && path.len() <= T::MaxHops::get() as usize,
+			Error::<T>::InvalidPath
+		);
+		// A pool visited twice would be quoted at reserves its first swap has already moved.
+		ensure!(
+			path.iter().enumerate().all(|(i, asset)| !path[i + 1..].contains(asset)),
+			Error::<T>::InvalidPath
+		);
+
+		let mut amounts = Vec::with_capacity(path.len());
+		let mut amount = amount_in;
+		amounts.push(amount);
+		for pair in path.windows(2) {
+			let (reserve_in, reserve_out) =
+				Self::reserves(pair[0], pair[1]).ok_or(Error::<T>::PoolNotFound)?;
+			amount = Self::get_amount_out(amount, reserve_in, reserve_out)?;
+			amounts.push(amount);
+		}
+		Ok(amounts)
+	}
+
+	/// The weight of a swap along a path of `assets` assets.
+	pub fn swap_weight(assets: u32) -> Weight {
+		let hops = assets.saturating_sub(1) as u64;
+		T::DbWeight::get()
+			.reads_writes(3, 2)
+			.saturating_add(T::DbWeight::get().reads_writes(7, 6).saturating_mul(hops))
+	}
+
+	/// Swap along a path of pools, given the amounts of each asset of the path. The first asset
+	/// moves from the signer into the first pool, each pool's output moves into the next pool,
+	/// and the last pool's output moves to the signer. The protocol's share of each pool's fee is
+	/// then paid out of the pool.
+	fn swap_path(
+		who: &T::AccountId,
+		path: &[AssetKindOf<T>],
+		amounts: &[AssetBalanceOf<T>],
+	) -> DispatchResult {
+		let pool_ids: Vec<_> =
+			path.windows(2).map(|pair| Self::pool_id(pair[0], pair[1])).collect();
+		let pool_accounts: Vec<_> = pool_ids.iter().map(Self::pool_account).collect();
+		let (amount_in, amount_out) = match (amounts.first(), amounts.last()) {
+			(Some(amount_in), Some(amount_out))
+				if !pool_accounts.is_empty() && pool_accounts.len() + 1 == amounts.len() =>
+				(*amount_in, *amount_out),
+			_ => return Err(Error::<T>::InvalidPath.into()),
+		};
+
+		Self::transfer(path[0], who, &pool_accounts[0], amount_in, Preservation::Preserve)?;
+		for (i, pool_account) in pool_accounts.iter().enumerate() {
+			let dest = pool_accounts.get(i + 1).unwrap_or(who);
+			Self::transfer(
+				path[i + 1],
+				pool_account,
+				dest,
+				amounts[i + 1],
+				Preservation::Expendable,
+			)?;
+		}
+		if let Some(recipient) = ProtocolFeeRecipient::<T>::get() {
+			for (i, pool_account) in pool_accounts.iter().enumerate() {
+				Self::pay_protocol_fee(path[i], pool_account, &recipient, amounts[i]);
+			}
+		}
+		for pool_id in &pool_ids {
+			Self::update_price(pool_id, true);
+		}
+
+		Self::deposit_event(Event::SwapExecuted {
+			who: who.clone(),
+			asset_in: path[0],
+			asset_out: path[path.len() - 1],
+			amount_in,
+			amount_out,
+		});
+		Ok(())
+	}
+
+	/// Pay the protocol's share of the fee on a pool's input of `amount_in` to the recipient.
+	/// The share is at most the fee, so the product of the pool's reserves still doesn't
+	/// decrease. If it can't be paid, for example because the recipient can't hold the asset,
+	/// it is left in the pool.
+	fn pay_protocol_fee(
+		asset: AssetKindOf<T>,
+		pool_account: &T::AccountId,
+		recipient: &T::AccountId,
+		amount_in: AssetBalanceOf<T>,
+	) {
+		let amount_in: u128 = amount_in.unique_saturated_into();
+		let fee = SwapFee::<T>::get().mul_floor(amount_in);
+		let share = AssetBalanceOf::<T>::unique_saturated_from(T::ProtocolFeeShare::get() * fee);
+		if share.is_zero() {
+			return
+		}
+		let _ = with_storage_layer(|| {
+			Self::transfer(asset, pool_account, recipient, share, Preservation::Expendable)
+		});
+	}
+
+	/// Take the index and LP token id of the next pool, skipping ids of existing assets.
+	fn next_lp_token() -> (u32, AssetIdOf<T>) {
+		let mut index = NextPoolIndex::<T>::get();
+		let mut lp_token = T::LpTokenId::convert(index);
+		while <T::Fungibles as fungibles::Inspect<T::AccountId>>::asset_exists(lp_token) {
+			index = index.saturating_add(1);
+			lp_token = T::LpTokenId::convert(index);
+		}
+		NextPoolIndex::<T>::put(index.saturating_add(1));
+		(index, lp_token)
+	}
+
+	/// The LP tokens minted for the first deposit into a pool, the geometric mean of the
+	/// deposited amounts.
+	fn initial_liquidity(
+		amount_a: AssetBalanceOf<T>,
+		amount_b: AssetBalanceOf<T>,
+	) -> Result<AssetBalanceOf<T>, Error<T>> {
+		let amount_a: u128 = amount_a.unique_saturated_into();
+		let amount_b: u128 = amount_b.unique_saturated_into();
+		let liquidity = U256::from(amount_a).saturating_mul(U256::from(amount_b)).integer_sqrt();
+		Self::from_u128(liquidity.low_u128())
+	}
+
+	fn lp_supply(lp_token: AssetIdOf<T>) -> AssetBalanceOf<T> {
+		<T::Fungibles as fungibles::Inspect<T::AccountId>>::total_issuance(lp_token)
+	}
+
+	fn mint_lp(
+		lp_token: AssetIdOf<T>,
+		who: &T::AccountId,
+		amount: AssetBalanceOf<T>,
+	) -> DispatchResult {
+		<T::Fungibles as fungibles::Mutate<T::AccountId>>::mint_into(lp_token, who, amount)?;
+		Ok(())
+	}
+
+	/// The balance of an account in the native token or an asset. Native balances are converted
+	/// to the assets' balance type.
+	fn balance(asset: AssetKindOf<T>, who: &T::AccountId) -> AssetBalanceOf<T> {
+		match asset {
+			AssetKind::Native => {
+				let balance: u128 =
+					<T::NativeBalance as fungible::Inspect<T::AccountId>>::balance(who)
+						.unique_saturated_into();
+				AssetBalanceOf::<T>::unique_saturated_from(balance)
+			},
+			AssetKind::Asset(id) =>
+				<T::Fungibles as fungibles::Inspect<T::AccountId>>::balance(id, who),
+		}
+	}
+
+	fn transfer(
+		asset: AssetKindOf<T>,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: AssetBalanceOf<T>,
+		preservation: Preservation,
+	) -> DispatchResult {
+		match asset {
+			AssetKind::Native => {
+				<T::NativeBalance as fungible::Mutate<T::AccountId>>::transfer(
+					from,
+					to,
+					Self::to_native(amount)?,
+					preservation,
+				)?;
+			},
+			AssetKind::Asset(id) => {
+				<T::Fungibles as fungibles::Mutate<T::AccountId>>::transfer(
+					id,
+					from,
+					to,
+					amount,
+					preservation,
+				)?;
+			},
+		}
+		Ok(())
+	}
+
+	/// `a * b / c`, without overflowing in the product.
+	fn mul_div(
+		a: AssetBalanceOf<T>,
+		b: AssetBalanceOf<T>,
+		c: AssetBalanceOf<T>,
+		rounding: Rounding,
+	) -> Result<AssetBalanceOf<T>, Error<T>> {
+		let result = multiply_by_rational_with_rounding(
+			a.unique_saturated_into(),
+			b.unique_saturated_into(),
+			c.unique_saturated_into(),
+			rounding,
+		)
+		.ok_or(Error::<T>::Overflow)?;
+		Self::from_u128(result)
+	}
+
+	fn from_u128(amount: u128) -> Result<AssetBalanceOf<T>, Error<T>> {
+		AssetBalanceOf::<T>::try_from(amount).map_err(|_| Error::<T>::Overflow)
+	}
+
+	/// An amount in the assets' balance type as an amount of the native token.
+	fn to_native(amount: AssetBalanceOf<T>) -> Result<BalanceOf<T>, Error<T>> {
+		let amount: u128 = amount.unique_saturated_into();
+		BalanceOf::<T>::try_from(amount).map_err(|_| Error::<T>::Overflow)
+	}
+
+	/// The asset an account pays its transaction fees in, if it chose one that is still approved.
+	pub fn fee_asset(who: &T::AccountId) -> Option<AssetIdOf<T>> {
+		AccountFeeAsset::<T>::get(who).filter(|asset| FeeAssets::<T>::contains_key(asset))
+	}
+}
+
+/// Prices assets at the spot price of their pool with the native token.
+impl<T: Config> AssetPrice<AssetIdOf<T>, BalanceOf<T>, AssetBalanceOf<T>> for Pallet<T> {
+	fn native_to_asset(asset: AssetIdOf<T>, amount: BalanceOf<T>) -> Option<AssetBalanceOf<T>> {
+		let (reserve_native, reserve_asset) =
+			Self::reserves(AssetKind::Native, AssetKind::Asset(asset))?;
+		let amount: u128 = amount.unique_saturated_into();
+		let amount = AssetBalanceOf::<T>::try_from(amount).ok()?;
+		Self::quote(amount, reserve_native, reserve_asset).ok()
+	}
+}
@@ -1,10 +1,19 @@
 use crate as pallet_dex;
-use frame_support::traits::{AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, ConstU64};
+use frame_support::{
+	parameter_types,
+	traits::{
+		AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, ConstU64, ConstU8, Currency,
+		OnUnbalanced,
+	},
+	weights::IdentityFee,
+	PalletId,
+};
 use frame_system::{EnsureRoot, EnsureSigned};
+use pallet_transaction_payment::CurrencyAdapter;
 use sp_core::H256;
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup},
-	BuildStorage,
+	traits::{BlakeTwo256, Convert, IdentityLookup},
+	BuildStorage, Permill,
 };
 
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -17,6 +26,7 @@ frame_support::construct_runtime!(
 		System: frame_system,
 		Balances: pallet_balances,
 		Assets: pallet_assets,
+		TransactionPayment: pallet_transaction_payment,
 		Dex: pallet_dex,
 	}
 );
@@ -57,7 +67,7 @@ impl pallet_balances::Config for Test {
 	type MaxLocks = ConstU32<10>;
 	type MaxReserves = ();
 	type ReserveIdentifier = [u8; 8];
-	type RuntimeHoldReason = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type FreezeIdentifier = ();
 	type MaxHolds = ConstU32<10>;
 	type MaxFreezes = ConstU32<10>;
@@ -77,7 +87,7 @@ impl pallet_assets::Config for Test {
 	type MetadataDepositPerByte = ConstU128<1>;
 	type ApprovalDeposit = ConstU128<1>;
 	type StringLimit = ConstU32<50>;
-	type Freezer = ();
+	type Freezer = Dex;
 	type Extra = ();
 	type CallbackHandle = ();
 	type WeightInfo = ();
@@ -86,10 +96,74 @@ impl pallet_assets::Config for Test {
 	type BenchmarkHelper = ();
 }
 
+// Asset 1 is worth half of the native token, and other assets have no price.
+pub struct HalfPrice;
+impl pallet_dex::AssetPrice<u32, Balance, Balance> for HalfPrice {
+	fn native_to_asset(asset: u32, amount: Balance) -> Option<Balance> {
+		(asset == 1).then(|| amount * 2)
+	}
+}
+
+/// The account transaction fees are paid to.
+pub const FEE_ACCOUNT: u64 = 99;
+
+// Pay transaction fees to `FEE_ACCOUNT`.
+pub struct DealWithFees;
+impl OnUnbalanced<pallet_balances::NegativeImbalance<Test>> for DealWithFees {
+	fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Test>) {
+		Balances::resolve_creating(&FEE_ACCOUNT, amount);
+	}
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type OnChargeTransaction = pallet_dex::AssetFeeAdapter<
+		HalfPrice,
+		Balances,
+		DealWithFees,
+		CurrencyAdapter<Balances, DealWithFees>,
+	>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = IdentityFee<Balance>;
+	type LengthToFee = IdentityFee<Balance>;
+	type FeeMultiplierUpdate = ();
+}
+
+parameter_types! {
+	pub const DexPalletId: PalletId = PalletId(*b"py/dexpl");
+	pub const DefaultSwapFee: Permill = Permill::from_percent(1);
+	pub const ProtocolFeeShare: Permill = Permill::from_percent(50);
+}
+
+// LP tokens are numbered from 1000.
+pub struct LpTokenIds;
+impl Convert<u32, u32> for LpTokenIds {
+	fn convert(index: u32) -> u32 {
+		1_000 + index
+	}
+}
+
 impl pallet_dex::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type NativeBalance = Balances;
 	type Fungibles = Assets;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type PalletId = DexPalletId;
+	type AdminOrigin = EnsureRoot<u64>;
+	type DefaultSwapFee = DefaultSwapFee;
+	type ProtocolFeeShare = ProtocolFeeShare;
+	type MintMinLiquidity = ConstU128<100>;
+	type LpTokenId = LpTokenIds;
+	type MaxHops = ConstU32<3>;
+	type ObservationPeriod = ConstU64<10>;
+	type MaxObservations = ConstU32<3>;
+	type MaxPoolsObservedPerBlock = ConstU32<2>;
+	type MaxOrdersPerPair = ConstU32<2>;
+	type MaxOrdersPerAccount = ConstU32<2>;
+	type OrderDeposit = ConstU128<10>;
+	type PoolDeposit = ConstU128<100>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
 }
 
 // Build genesis storage according to the mock runtime.
@@ -0,0 +1,141 @@
+//! Time-weighted average prices of pools. Each pool accumulates the price of each of its assets
+//! in the other, summed over blocks, and snapshots the accumulators every `ObservationPeriod`
+//! blocks. The average price over a window is the difference between the current accumulators
+//! and a snapshot from before the window, divided by the blocks between them.
+
+use crate::{
+	AssetKindOf, Config, Observation, ObservationCursor, Observations, Pallet, PoolIdOf,
+	PoolsByIndex, PriceAccumulator, PriceAccumulators,
+};
+use frame_support::pallet_prelude::*;
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_runtime::{
+	traits::{CheckedSub, Saturating, UniqueSaturatedInto, Zero},
+	FixedPointNumber, FixedU128,
+};
+
+/// Time-weighted average prices of assets.
+pub trait PriceOracle<AssetKind, BlockNumber> {
+	/// The average price of `base` in `quote`, as the amount of `quote` worth one unit of `base`,
+	/// over at least the last `window` blocks. `None` if the assets have no pool, or its prices
+	/// have not been observed for that long.
+	fn twap(base: AssetKind, quote: AssetKind, window: BlockNumber) -> Option<FixedU128>;
+}
+
+impl<BlockNumber: Copy + Saturating + UniqueSaturatedInto<u128>> PriceAccumulator<BlockNumber> {
+	/// The accumulators brought forward to `now`, at the prices they were last updated with.
+	/// The cumulative prices wrap on overflow, so only differences between them are meaningful.
+	pub fn at(mut self, now: BlockNumber) -> Self {
+		let elapsed: u128 = now.saturating_sub(self.block).unique_saturated_into();
+		self.price_a_cumulative = self
+			.price_a_cumulative
+			.wrapping_add(self.price_a.into_inner().wrapping_mul(elapsed));
+		self.price_b_cumulative = self
+			.price_b_cumulative
+			.wrapping_add(self.price_b.into_inner().wrapping_mul(elapsed));
+		self.block = now;
+		self
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Bring a pool's price accumulators up to the current block, and snapshot them if
+	/// `ObservationPeriod` has passed since the last snapshot. When the pool's reserves have
+	/// changed, its prices are updated to the new reserves, which count from the next block.
+	pub(crate) fn update_price(pool_id: &PoolIdOf<T>, reserves_changed: bool) {
+		let now = frame_system::Pallet::<T>::block_number();
+		let mut accumulator = match PriceAccumulators::<T>::get(pool_id) {
+			Some(accumulator) => accumulator.at(now),
+			None if reserves_changed => PriceAccumulator {
+				block: now,
+				price_a: Zero::zero(),
+				price_b: Zero::zero(),
+				price_a_cumulative: 0,
+				price_b_cumulative: 0,
+			},
+			None => return,
+		};
+		if reserves_changed {
+			let (reserve_a, reserve_b) =
+				Self::reserves(pool_id.0, pool_id.1).unwrap_or((Zero::zero(), Zero::zero()));
+			let (reserve_a, reserve_b): (u128, u128) =
+				(reserve_a.unique_saturated_into(), reserve_b.unique_saturated_into());
+			accumulator.price_a =
+				FixedU128::checked_from_rational(reserve_b, reserve_a).unwrap_or_default();
+			accumulator.price_b =
+				FixedU128::checked_from_rational(reserve_a, reserve_b).unwrap_or_default();
+		}
+		PriceAccumulators::<T>::insert(pool_id, accumulator);
+
+		Observations::<T>::mutate(pool_id, |observations| {
+			let due = observations
+				.last()
+				.map_or(true, |last| now.saturating_sub(last.block) >= T::ObservationPeriod::get());
+			if due {
+				if observations.len() as u32 >= T::MaxObservations::get() {
+					observations.remove(0);
+				}
+				let _ = observations.try_push(Observation {
+					block: now,
+					price_a_cumulative: accumulator.price_a_cumulative,
+					price_b_cumulative: accumulator.price_b_cumulative,
+				});
+			}
+		});
+	}
+
+	/// Bring the accumulators of up to `MaxPoolsObservedPerBlock` pools up to the current block,
+	/// picking up from where the previous block left off, so pools without trades are still
+	/// observed.
+	pub(crate) fn observe_pools() -> Weight {
+		let pool_count = crate::NextPoolIndex::<T>::get();
+		if pool_count.is_zero() {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let pools = T::MaxPoolsObservedPerBlock::get().min(pool_count);
+		let mut cursor = ObservationCursor::<T>::get();
+		for _ in 0..pools {
+			if cursor >= pool_count {
+				cursor = 0;
+			}
+			if let Some(pool_id) = PoolsByIndex::<T>::get(cursor) {
+				Self::update_price(&pool_id, false);
+			}
+			cursor = cursor.saturating_add(1);
+		}
+		ObservationCursor::<T>::put(cursor);
+
+		T::DbWeight::get()
+			.reads_writes(2, 1)
+			.saturating_add(T::DbWeight::get().reads_writes(3, 2).saturating_mul(pools.into()))
+	}
+}
+
+impl<T: Config> PriceOracle<AssetKindOf<T>, BlockNumberFor<T>> for Pallet<T> {
+	fn twap(
+		base: AssetKindOf<T>,
+		quote: AssetKindOf<T>,
+		window: BlockNumberFor<T>,
+	) -> Option<FixedU128> {
+		if window.is_zero() || base == quote {
+			return None
+		}
+		let pool_id = Self::pool_id(base, quote);
+		let now = frame_system::Pallet::<T>::block_number();
+		let current = PriceAccumulators::<T>::get(&pool_id)?.at(now);
+
+		// The latest snapshot from at or before the start of the window.
+		let start = now.checked_sub(&window)?;
+		let observations = Observations::<T>::get(&pool_id);
+		let then = observations.iter().rev().find(|observation| observation.block <= start)?;
+
+		let elapsed: u128 = now.saturating_sub(then.block).unique_saturated_into();
+		let cumulative = if base == pool_id.0 {
+			current.price_a_cumulative.wrapping_sub(then.price_a_cumulative)
+		} else {
+			current.price_b_cumulative.wrapping_sub(then.price_b_cumulative)
+		};
+		Some(FixedU128::from_inner(cumulative / elapsed))
+	}
+}
@@ -0,0 +1,288 @@
+//! Limit orders, filled against the pools. An order waits in the order book of its pair of assets
+//! until the pair's pool gives at least its minimum output for its input, and is then filled with
+//! a swap in `on_idle`. Orders are filled whole, oldest first within each book.
+//!
+//! Once a book holds `MaxOrdersPerPair` orders, a new order evicts the order furthest from being
+//! filled, if the new order is closer to being filled than it, so orders that will never be filled
+//! can't keep others out of the book. An order's distance from being filled is its minimum output
+//! over what its input is worth at the pool's price, which compares orders in both directions.
+//!
+//! The amount an order sells is put aside on the owner's account while it is open. Native tokens
+//! are held. `pallet_assets` doesn't support holds, so assets are frozen instead, through this
+//! pallet's implementation of `FrozenBalance`, which the runtime must set as `pallet_assets`'
+//! `Freezer`. Each order also holds `OrderDeposit` from its owner, and an account can have at most
+//! `MaxOrdersPerAccount` open orders.
+
+use crate::{
+	AssetBalanceOf, AssetIdOf, AssetKind, AssetKindOf, BalanceOf, Config, Error, Event, HoldReason,
+	LimitOrder, NextPoolIndex, OrderBooks, OrderCounts, OrderCursor, OrderFunds, Orders, Pallet,
+	PoolIdOf, PoolsByIndex,
+};
+use frame_support::{
+	pallet_prelude::*,
+	storage::with_storage_layer,
+	traits::{
+		fungibles,
+		tokens::{Fortitude, Precision, Preservation},
+	},
+};
+use sp_runtime::{
+	traits::{Bounded, UniqueSaturatedInto, Zero},
+	FixedPointNumber, FixedU128, TokenError,
+};
+use sp_std::prelude::*;
+
+type LimitOrderOf<T> = LimitOrder<
+	<T as frame_system::Config>::AccountId,
+	AssetKindOf<T>,
+	AssetBalanceOf<T>,
+	BalanceOf<T>,
+>;
+
+impl<T: Config> Pallet<T> {
+	/// Put aside the amount an order sells.
+	pub(crate) fn hold_order_funds(
+		asset: AssetKindOf<T>,
+		who: &T::AccountId,
+		amount: AssetBalanceOf<T>,
+	) -> DispatchResult {
+		match asset {
+			AssetKind::Native => T::NativeBalance::hold(
+				&HoldReason::LimitOrder.into(),
+				who,
+				Self::to_native(amount)?,
+			),
+			AssetKind::Asset(id) => {
+				// What other orders froze is already excluded.
+				let available =
+					<T::Fungibles as fungibles::Inspect<T::AccountId>>::reducible_balance(
+						id,
+						who,
+						Preservation::Preserve,
+						Fortitude::Polite,
+					);
+				ensure!(amount <= available, TokenError::FundsUnavailable);
+				OrderFunds::<T>::mutate(id, who, |frozen| {
+					*frozen = Some(frozen.unwrap_or_else(Zero::zero).saturating_add(amount))
+				});
+				Ok(())
+			},
+		}
+	}
+
+	/// Return the amount an order put aside to its owner.
+	pub(crate) fn release_order_funds(
+		asset: AssetKindOf<T>,
+		who: &T::AccountId,
+		amount: AssetBalanceOf<T>,
+	) -> DispatchResult {
+		match asset {
+			AssetKind::Native => {
+				T::NativeBalance::release(
+					&HoldReason::LimitOrder.into(),
+					who,
+					Self::to_native(amount)?,
+					Precision::Exact,
+				)?;
+				Ok(())
+			},
+			AssetKind::Asset(id) => {
+				OrderFunds::<T>::mutate_exists(id, who, |frozen| {
+					*frozen = frozen
+						.map(|frozen| frozen.saturating_sub(amount))
+						.filter(|frozen| !frozen.is_zero())
+				});
+				Ok(())
+			},
+		}
+	}
+
+	/// Return an order's deposit to its owner, and stop counting the order among the owner's.
+	pub(crate) fn close_order(order: &LimitOrderOf<T>) -> DispatchResult {
+		if !order.deposit.is_zero() {
+			T::NativeBalance::release(
+				&HoldReason::OrderDeposit.into(),
+				&order.owner,
+				order.deposit,
+				Precision::BestEffort,
+			)?;
+		}
+		OrderCounts::<T>::mutate_exists(&order.owner, |count| {
+			*count = count.and_then(|count| count.checked_sub(1)).filter(|count| !count.is_zero())
+		});
+		Ok(())
+	}
+
+	/// Remove an order from storage and from its order book.
+	pub(crate) fn remove_order(order_id: u32, order: &LimitOrderOf<T>) {
+		Orders::<T>::remove(order_id);
+		OrderBooks::<T>::mutate(Self::pool_id(order.asset_in, order.asset_out), |book| {
+			book.retain(|id| *id != order_id)
+		});
+	}
+
+	/// How far an order is from being filled, as its minimum output over what its input is
+	/// worth at the price of the pool with `reserves`, given in the order of the pool's id.
+	pub(crate) fn order_distance(
+		order: &LimitOrderOf<T>,
+		pool_id: &PoolIdOf<T>,
+		reserves: (AssetBalanceOf<T>, AssetBalanceOf<T>),
+	) -> FixedU128 {
+		let (reserve_in, reserve_out) =
+			if order.asset_in == pool_id.0 { reserves } else { (reserves.1, reserves.0) };
+		let worth: u128 = Self::quote(order.amount_in, reserve_in, reserve_out)
+			.map(|worth| worth.unique_saturated_into())
+			.unwrap_or_default();
+		let min_amount_out: u128 = order.min_amount_out.unique_saturated_into();
+		FixedU128::checked_from_rational(min_amount_out, worth).unwrap_or(FixedU128::max_value())
+	}
+
+	/// Make room in a pool's full order book for `order` by evicting the order furthest from
+	/// being filled, returning its funds and deposit to its owner. Fails if no order is further
+	/// from being filled than `order`.
+	pub(crate) fn evict_furthest_order(
+		pool_id: &PoolIdOf<T>,
+		order: &LimitOrderOf<T>,
+	) -> DispatchResult {
+		let reserves = Self::reserves(pool_id.0, pool_id.1).ok_or(Error::<T>::PoolNotFound)?;
+		let distance = Self::order_distance(order, pool_id, reserves);
+		// Of orders equally far from being filled, the newest is evicted.
+		let furthest = OrderBooks::<T>::get(pool_id)
+			.into_iter()
+			.filter_map(|order_id| Orders::<T>::get(order_id).map(|order| (order_id, order)))
+			.map(|(order_id, order)| {
+				(Self::order_distance(&order, pool_id, reserves), order_id, order)
+			})
+			.max_by_key(|(distance, _, _)| *distance);
+
+		match furthest {
+			Some((furthest_distance, order_id, evicted)) if furthest_distance > distance => {
+				Self::release_order_funds(evicted.asset_in, &evicted.owner, evicted.amount_in)?;
+				Self::close_order(&evicted)?;
+				Self::remove_order(order_id, &evicted);
+				Self::deposit_event(Event::LimitOrderEvicted { order_id, who: evicted.owner });
+				Ok(())
+			},
+			_ => Err(Error::<T>::OrderBookFull.into()),
+		}
+	}
+
+	/// The weight of checking whether an order can be filled.
+	fn check_order_weight() -> Weight {
+		T::DbWeight::get().reads(4)
+	}
+
+	/// The weight of filling an order, on top of checking it.
+	fn fill_order_weight() -> Weight {
+		T::DbWeight::get().reads_writes(4, 7).saturating_add(Self::swap_weight(2))
+	}
+
+	/// Fill the orders whose pools have reached their limits, using at most `limit` weight.
+	/// Order books are visited in turn by their pool's index, picking up from where the previous
+	/// call left off, and each at most once per call.
+	pub(crate) fn match_orders(limit: Weight) -> Weight {
+		let pool_count = NextPoolIndex::<T>::get();
+		let mut used = T::DbWeight::get().reads_writes(2, 1);
+		if pool_count.is_zero() || !limit.all_gte(used) {
+			return used
+		}
+
+		let mut cursor = OrderCursor::<T>::get();
+		for _ in 0..pool_count {
+			// Leave room to read the next book, and check and fill at least one of its orders.
+			let next = used
+				.saturating_add(T::DbWeight::get().reads(2))
+				.saturating_add(Self::check_order_weight())
+				.saturating_add(Self::fill_order_weight());
+			if !limit.all_gte(next) {
+				break
+			}
+			if cursor >= pool_count {
+				cursor = 0;
+			}
+			used = used.saturating_add(T::DbWeight::get().reads(1));
+			if let Some(pool_id) = PoolsByIndex::<T>::get(cursor) {
+				used = used.saturating_add(Self::match_book(&pool_id, limit.saturating_sub(used)));
+			}
+			cursor = cursor.saturating_add(1);
+		}
+		OrderCursor::<T>::put(cursor);
+		used
+	}
+
+	/// Fill the orders of a pool's order book that its price has reached, using at most `limit`
+	/// weight.
+	fn match_book(pool_id: &PoolIdOf<T>, limit: Weight) -> Weight {
+		let mut used = T::DbWeight::get().reads(1);
+		let book = OrderBooks::<T>::get(pool_id);
+		let mut filled = Vec::new();
+		for &order_id in book.iter() {
+			let next = used.saturating_add(Self::check_order_weight());
+			if !limit.all_gte(next.saturating_add(Self::fill_order_weight())) {
+				break
+			}
+			used = next;
+
+			let order = match Orders::<T>::get(order_id) {
+				Some(order) => order,
+				None => continue,
+			};
+			// Each fill moves the pool's price, so the reserves are read again for every order.
+			let amount_out = Self::reserves(order.asset_in, order.asset_out)
+				.and_then(|(reserve_in, reserve_out)| {
+					Self::get_amount_out(order.amount_in, reserve_in, reserve_out).ok()
+				})
+				.filter(|amount_out| *amount_out >= order.min_amount_out);
+			if let Some(amount_out) = amount_out {
+				used = used.saturating_add(Self::fill_order_weight());
+				// A fill that fails is rolled back, and the order tried again later.
+				if with_storage_layer(|| Self::fill_order(order_id, &order, amount_out)).is_ok() {
+					filled.push(order_id);
+				}
+			}
+		}
+
+		if !filled.is_empty() {
+			OrderBooks::<T>::mutate(pool_id, |book| book.retain(|id| !filled.contains(id)));
+		}
+		used
+	}
+
+	/// Fill an order with a swap from its owner's account, after returning the amount it put
+	/// aside, and return its deposit.
+	fn fill_order(
+		order_id: u32,
+		order: &LimitOrderOf<T>,
+		amount_out: AssetBalanceOf<T>,
+	) -> DispatchResult {
+		Self::release_order_funds(order.asset_in, &order.owner, order.amount_in)?;
+		Self::swap_path(
+			&order.owner,
+			&[order.asset_in, order.asset_out],
+			&[order.amount_in, amount_out],
+		)?;
+		Self::close_order(order)?;
+		Orders::<T>::remove(order_id);
+
+		Self::deposit_event(Event::LimitOrderFilled {
+			order_id,
+			who: order.owner.clone(),
+			amount_in: order.amount_in,
+			amount_out,
+		});
+		Ok(())
+	}
+}
+
+/// Freezes the assets put aside by open limit orders on their owners' accounts.
+impl<T: Config> pallet_assets::FrozenBalance<AssetIdOf<T>, T::AccountId, AssetBalanceOf<T>>
+	for Pallet<T>
+{
+	fn frozen_balance(asset: AssetIdOf<T>, who: &T::AccountId) -> Option<AssetBalanceOf<T>> {
+		OrderFunds::<T>::get(asset, who)
+	}
+
+	fn died(asset: AssetIdOf<T>, who: &T::AccountId) {
+		OrderFunds::<T>::remove(asset, who);
+	}
+}
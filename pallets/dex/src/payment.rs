@@ -0,0 +1,209 @@
+//! A transaction payment adapter that charges fees in an asset chosen by the signer.
+
+use crate::{
+	AssetBalanceOf, AssetIdOf, AssetKind, BalanceOf, Config, Error, Event, Pallet, PriceOracle,
+	ProtocolFeeRecipient,
+};
+use frame_support::traits::{
+	fungibles, tokens::Preservation, Currency, ExistenceRequirement, Get, OnUnbalanced,
+	WithdrawReasons,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use pallet_transaction_payment::{NegativeImbalanceOf, OnChargeTransaction};
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational_with_rounding,
+	traits::{DispatchInfoOf, PostDispatchInfoOf, UniqueSaturatedFrom, UniqueSaturatedInto, Zero},
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	DispatchError, FixedPointNumber, Rounding,
+};
+use sp_std::marker::PhantomData;
+
+type CallOf<T> = <T as frame_system::Config>::RuntimeCall;
+type BalanceOfCurrency<C, T> = <C as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Prices assets in the native token.
+pub trait AssetPrice<AssetId, Balance, AssetBalance> {
+	/// The amount of `asset` worth `amount` of the native token, or `None` if the asset has no
+	/// price.
+	fn native_to_asset(asset: AssetId, amount: Balance) -> Option<AssetBalance>;
+}
+
+impl<AssetId, Balance, AssetBalance> AssetPrice<AssetId, Balance, AssetBalance> for () {
+	fn native_to_asset(_: AssetId, _: Balance) -> Option<AssetBalance> {
+		None
+	}
+}
+
+/// Prices assets at their average price in their native token pool over the last `W` blocks, so
+/// the price can't be moved for a block by trading against the pool.
+pub struct TwapPrice<T, W>(PhantomData<(T, W)>);
+
+impl<T: Config, W: Get<BlockNumberFor<T>>> AssetPrice<AssetIdOf<T>, BalanceOf<T>, AssetBalanceOf<T>>
+	for TwapPrice<T, W>
+{
+	fn native_to_asset(asset: AssetIdOf<T>, amount: BalanceOf<T>) -> Option<AssetBalanceOf<T>> {
+		let price = Pallet::<T>::twap(AssetKind::Native, AssetKind::Asset(asset), W::get())?;
+		let amount: u128 = amount.unique_saturated_into();
+		Some(AssetBalanceOf::<T>::unique_saturated_from(price.saturating_mul_int(amount)))
+	}
+}
+
+/// Charges the fee in the signer's fee asset when it has chosen an approved one with a pool
+/// with the native token, converting the native fee with `P`, and defers to `OCT` otherwise. The
+/// asset fee is transferred from the signer into the pool before dispatch, and the part of it for
+/// unused weight is given back after dispatch, at the rate it was charged at. The rest is swapped
+/// through the pool into the native token `C`, which is handed to `OU`, the handler `OCT` gives
+/// native fees to. If the pool can't swap it, the rest is given back too.
+pub struct AssetFeeAdapter<P, C, OU, OCT>(PhantomData<(P, C, OU, OCT)>);
+
+impl<T, P, C, OU, OCT> OnChargeTransaction<T> for AssetFeeAdapter<P, C, OU, OCT>
+where
+	T: Config + pallet_transaction_payment::Config,
+	P: AssetPrice<AssetIdOf<T>, BalanceOf<T>, AssetBalanceOf<T>>,
+	C: Currency<T::AccountId>,
+	OU: OnUnbalanced<NegativeImbalanceOf<C, T>>,
+	OCT: OnChargeTransaction<T>,
+{
+	type Balance = OCT::Balance;
+	/// What `OCT` withdrew, or the asset, the amount of it and the native fee it was charged for.
+	type LiquidityInfo = (OCT::LiquidityInfo, Option<(AssetIdOf<T>, AssetBalanceOf<T>, u128)>);
+
+	fn withdraw_fee(
+		who: &T::AccountId,
+		call: &CallOf<T>,
+		info: &DispatchInfoOf<CallOf<T>>,
+		fee: Self::Balance,
+		tip: Self::Balance,
+	) -> Result<Self::LiquidityInfo, TransactionValidityError> {
+		let asset = match Pallet::<T>::fee_asset(who) {
+			Some(asset) if !fee.is_zero() => asset,
+			_ => return OCT::withdraw_fee(who, call, info, fee, tip).map(|inner| (inner, None)),
+		};
+		// The fee is swapped through the pool after dispatch.
+		frame_support::ensure!(
+			Pallet::<T>::reserves(AssetKind::Native, AssetKind::Asset(asset)).is_some(),
+			InvalidTransaction::Payment
+		);
+
+		let native: u128 = fee.unique_saturated_into();
+		let amount = P::native_to_asset(asset, BalanceOf::<T>::unique_saturated_from(native))
+			.ok_or(InvalidTransaction::Payment)?;
+		// The signer is kept alive, so whatever is given back after dispatch can be.
+		<T::Fungibles as fungibles::Mutate<T::AccountId>>::transfer(
+			asset,
+			who,
+			&fee_pool_account::<T>(asset),
+			amount,
+			Preservation::Preserve,
+		)
+		.map_err(|_| InvalidTransaction::Payment)?;
+		Ok((Default::default(), Some((asset, amount, native))))
+	}
+
+	fn correct_and_deposit_fee(
+		who: &T::AccountId,
+		dispatch_info: &DispatchInfoOf<CallOf<T>>,
+		post_info: &PostDispatchInfoOf<CallOf<T>>,
+		corrected_fee: Self::Balance,
+		tip: Self::Balance,
+		already_withdrawn: Self::LiquidityInfo,
+	) -> Result<(), TransactionValidityError> {
+		let (asset, paid, native) = match already_withdrawn {
+			(_, Some(charged)) => charged,
+			(inner, None) =>
+				return OCT::correct_and_deposit_fee(
+					who,
+					dispatch_info,
+					post_info,
+					corrected_fee,
+					tip,
+					inner,
+				),
+		};
+
+		let paid_u128: u128 = paid.unique_saturated_into();
+		let fee = multiply_by_rational_with_rounding(
+			paid_u128,
+			corrected_fee.unique_saturated_into(),
+			native,
+			Rounding::Up,
+		)
+		.unwrap_or(paid_u128)
+		.min(paid_u128);
+		let mut fee = AssetBalanceOf::<T>::unique_saturated_from(fee);
+		let refund = paid - fee;
+		give_back::<T>(asset, who, refund)?;
+
+		if !fee.is_zero() {
+			match swap_fee::<T, C>(asset, fee) {
+				Ok(imbalance) => OU::on_unbalanced(imbalance),
+				Err(_) => {
+					give_back::<T>(asset, who, fee)?;
+					fee = Zero::zero();
+				},
+			}
+		}
+		Pallet::<T>::deposit_event(Event::AssetFeePaid { who: who.clone(), asset, fee });
+		Ok(())
+	}
+}
+
+/// The account of the pool of an asset with the native token, which asset fees are paid into.
+fn fee_pool_account<T: Config>(asset: AssetIdOf<T>) -> T::AccountId {
+	Pallet::<T>::pool_account(&Pallet::<T>::pool_id(AssetKind::Native, AssetKind::Asset(asset)))
+}
+
+/// Give back part of an asset fee from the pool it was paid into.
+fn give_back<T: Config>(
+	asset: AssetIdOf<T>,
+	who: &T::AccountId,
+	amount: AssetBalanceOf<T>,
+) -> Result<(), TransactionValidityError> {
+	if amount.is_zero() {
+		return Ok(())
+	}
+	<T::Fungibles as fungibles::Mutate<T::AccountId>>::transfer(
+		asset,
+		&fee_pool_account::<T>(asset),
+		who,
+		amount,
+		Preservation::Preserve,
+	)
+	.map(|_| ())
+	.map_err(|_| InvalidTransaction::Payment.into())
+}
+
+/// Swap an asset fee, already paid into the asset's pool with the native token, into the native
+/// token. The native token the fee buys at the reserves before it was paid, less the swap fee, is
+/// withdrawn from the pool. The protocol's share of the swap fee is paid as for any swap. Nothing
+/// is withdrawn if the fee can't buy any native token, and the caller gives the fee back.
+fn swap_fee<T, C>(
+	asset: AssetIdOf<T>,
+	fee: AssetBalanceOf<T>,
+) -> Result<NegativeImbalanceOf<C, T>, DispatchError>
+where
+	T: Config + pallet_transaction_payment::Config,
+	C: Currency<T::AccountId>,
+{
+	let (reserve_native, reserve_asset) =
+		Pallet::<T>::reserves(AssetKind::Native, AssetKind::Asset(asset))
+			.ok_or(Error::<T>::PoolNotFound)?;
+	let pool_id = Pallet::<T>::pool_id(AssetKind::Native, AssetKind::Asset(asset));
+	let pool_account = Pallet::<T>::pool_account(&pool_id);
+
+	let native =
+		Pallet::<T>::get_amount_out(fee, reserve_asset.saturating_sub(fee), reserve_native)?;
+	let native: u128 = native.unique_saturated_into();
+	let imbalance = C::withdraw(
+		&pool_account,
+		BalanceOfCurrency::<C, T>::unique_saturated_from(native),
+		WithdrawReasons::FEE,
+		ExistenceRequirement::KeepAlive,
+	)?;
+
+	if let Some(recipient) = ProtocolFeeRecipient::<T>::get() {
+		Pallet::<T>::pay_protocol_fee(AssetKind::Asset(asset), &pool_account, &recipient, fee);
+	}
+	Pallet::<T>::update_price(&pool_id, true);
+	Ok(imbalance)
+}
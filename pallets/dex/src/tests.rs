@@ -1,24 +1,915 @@
-use crate::{mock::*, Error, Event};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*, AccountFeeAsset, AssetKind, AssetPrice, Error, Event, FeeAssets, HoldReason,
+	Incentives, NextPoolIndex, Observations, OrderBooks, OrderCounts, OrderFunds, Orders,
+	PoolCreators, PoolInfo, Pools, PriceAccumulators, PriceOracle, ProtocolFeeRecipient, Stakes,
+	SwapFee,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::{DispatchInfo, PostDispatchInfo},
+	traits::{
+		fungible::{InspectHold, Mutate},
+		fungibles::{self, Inspect},
+		tokens::{Fortitude, Preservation},
+		ConstU32, Hooks,
+	},
+	weights::Weight,
+	BoundedVec,
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{
+	traits::SignedExtension, DispatchError, FixedPointNumber, FixedU128, Permill, TokenError,
+};
+
+fn remark_call() -> RuntimeCall {
+	RuntimeCall::System(frame_system::Call::remark { remark: vec![] })
+}
+
+const A: AssetKind<u32> = AssetKind::Asset(1);
+const B: AssetKind<u32> = AssetKind::Asset(2);
+const C: AssetKind<u32> = AssetKind::Asset(3);
+
+fn path(assets: &[AssetKind<u32>]) -> BoundedVec<AssetKind<u32>, ConstU32<3>> {
+	BoundedVec::truncate_from(assets.to_vec())
+}
+
+// Create sufficient asset `id` and mint `amount` of it to `who`.
+fn create_asset(id: u32, who: u64, amount: u128) {
+	assert_ok!(Assets::force_create(RuntimeOrigin::root(), id.into(), 1, true, 1));
+	assert_ok!(Assets::mint(RuntimeOrigin::signed(1), id.into(), who, amount));
+}
+
+// Create a pool of assets 1 and 2 with 10_000 of each from account 1, and give account 2
+// 1_000_000 of each. Both accounts hold native tokens, so they can hold LP tokens.
+fn setup_pool() {
+	assert_ok!(Balances::set_balance(&1, 1_000));
+	assert_ok!(Balances::set_balance(&2, 1_000));
+	for id in [1, 2] {
+		create_asset(id, 1, 1_000_000);
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(1), id.into(), 2, 1_000_000));
+	}
+	assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), A, B));
+	assert_ok!(Dex::add_liquidity(RuntimeOrigin::signed(1), A, B, 10_000, 10_000, 0, 0));
+}
+
+#[test]
+fn create_pool_works() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 150));
+		create_asset(1, 1, 1_000_000);
+		create_asset(2, 1, 1_000_000);
+
+		assert_noop!(
+			Dex::create_pool(RuntimeOrigin::signed(1), A, A),
+			Error::<Test>::IdenticalAssets
+		);
+		assert_noop!(
+			Dex::create_pool(RuntimeOrigin::signed(1), A, AssetKind::Asset(9)),
+			Error::<Test>::AssetNotFound
+		);
+
+		// The pair is ordered, so it makes no difference which asset comes first.
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), B, A));
+		let pool_id = (A, B);
+		assert_eq!(Pools::<Test>::get(pool_id), Some(PoolInfo { lp_token: 1_000 }));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::PoolDeposit.into(), &1), 100);
+		System::assert_last_event(
+			Event::PoolCreated {
+				creator: 1,
+				pool_id,
+				pool_account: Dex::pool_account(&pool_id),
+				lp_token: 1_000,
+			}
+			.into(),
+		);
+		assert_noop!(
+			Dex::create_pool(RuntimeOrigin::signed(1), A, B),
+			Error::<Test>::PoolAlreadyExists
+		);
+
+		// Each pool takes a deposit from its creator.
+		assert_noop!(
+			Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A),
+			TokenError::FundsUnavailable
+		);
+		assert_ok!(Balances::set_balance(&1, 250));
+
+		// LP tokens skip ids of existing assets.
+		create_asset(1_001, 1, 1);
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::PoolDeposit.into(), &1), 200);
+		assert_eq!(Pools::<Test>::get((AssetKind::Native, A)), Some(PoolInfo { lp_token: 1_002 }));
+		assert_eq!(NextPoolIndex::<Test>::get(), 3);
+	});
+}
+
+#[test]
+fn add_and_remove_liquidity_works() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+		let pool_id = (A, B);
+
+		// The first deposit mints the geometric mean of the amounts, less the locked liquidity.
+		System::assert_last_event(
+			Event::LiquidityAdded {
+				who: 1,
+				pool_id,
+				amount_a: 10_000,
+				amount_b: 10_000,
+				lp_minted: 9_900,
+			}
+			.into(),
+		);
+		assert_eq!(Assets::balance(1_000, 1), 9_900);
+		assert_eq!(Assets::balance(1_000, Dex::pool_account(&pool_id)), 100);
+
+		// Later deposits are made at the pool's ratio.
+		assert_ok!(Dex::add_liquidity(RuntimeOrigin::signed(2), A, B, 2_000, 3_000, 0, 0));
+		System::assert_last_event(
+			Event::LiquidityAdded {
+				who: 2,
+				pool_id,
+				amount_a: 2_000,
+				amount_b: 2_000,
+				lp_minted: 2_000,
+			}
+			.into(),
+		);
+		assert_eq!(Dex::reserves(A, B), Some((12_000, 12_000)));
+		assert_noop!(
+			Dex::add_liquidity(RuntimeOrigin::signed(2), A, B, 1_000, 3_000, 0, 1_001),
+			Error::<Test>::AmountBelowMinimum
+		);
+
+		assert_noop!(
+			Dex::remove_liquidity(RuntimeOrigin::signed(2), A, B, 1_000, 1_001, 0),
+			Error::<Test>::AmountBelowMinimum
+		);
+		assert_ok!(Dex::remove_liquidity(RuntimeOrigin::signed(2), A, B, 1_000, 1_000, 1_000));
+		System::assert_last_event(
+			Event::LiquidityRemoved {
+				who: 2,
+				pool_id,
+				amount_a: 1_000,
+				amount_b: 1_000,
+				lp_burned: 1_000,
+			}
+			.into(),
+		);
+		assert_eq!(Assets::balance(1_000, 2), 1_000);
+		assert_eq!(Assets::balance(1, 2), 999_000);
+		assert_eq!(Dex::reserves(A, B), Some((11_000, 11_000)));
+	});
+}
+
+#[test]
+fn swap_exact_in_works() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+
+		// 1% of the input is left in the pool as the fee.
+		assert_ok!(Dex::swap_exact_in(RuntimeOrigin::signed(2), A, B, 1_000, 900));
+		System::assert_last_event(
+			Event::SwapExecuted {
+				who: 2,
+				asset_in: A,
+				asset_out: B,
+				amount_in: 1_000,
+				amount_out: 900,
+			}
+			.into(),
+		);
+		assert_eq!(Dex::reserves(A, B), Some((11_000, 9_100)));
+		assert_eq!(Assets::balance(2, 2), 1_000_900);
+
+		assert_noop!(
+			Dex::swap_exact_in(RuntimeOrigin::signed(2), A, B, 1_000, 1_000),
+			Error::<Test>::AmountBelowMinimum
+		);
+		assert_noop!(
+			Dex::swap_exact_in(RuntimeOrigin::signed(2), A, AssetKind::Native, 1_000, 0),
+			Error::<Test>::PoolNotFound
+		);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn swap_exact_out_works() {
 	new_test_ext().execute_with(|| {
-		// Go past genesis block so events get deposited
 		System::set_block_number(1);
-		// Dispatch a signed extrinsic.
-		assert_ok!(Dex::do_something(RuntimeOrigin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(Dex::something(), Some(42));
-		// Assert that the correct event was deposited
-		System::assert_last_event(Event::SomethingStored { something: 42, who: 1 }.into());
+		setup_pool();
+
+		assert_noop!(
+			Dex::swap_exact_out(RuntimeOrigin::signed(2), A, B, 1_000, 1_123),
+			Error::<Test>::AmountAboveMaximum
+		);
+		assert_ok!(Dex::swap_exact_out(RuntimeOrigin::signed(2), A, B, 1_000, 1_124));
+		System::assert_last_event(
+			Event::SwapExecuted {
+				who: 2,
+				asset_in: A,
+				asset_out: B,
+				amount_in: 1_124,
+				amount_out: 1_000,
+			}
+			.into(),
+		);
+		assert_eq!(Dex::reserves(A, B), Some((11_124, 9_000)));
+
+		// A swap can't drain the pool.
+		assert_noop!(
+			Dex::swap_exact_out(RuntimeOrigin::signed(2), A, B, 9_000, u128::MAX),
+			Error::<Test>::InsufficientLiquidity
+		);
+	});
+}
+
+#[test]
+fn swap_exact_in_path_works() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+		create_asset(3, 1, 1_000_000);
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), B, C));
+		assert_ok!(Dex::add_liquidity(RuntimeOrigin::signed(1), B, C, 10_000, 10_000, 0, 0));
+
+		// Both pools take their fee.
+		assert_eq!(Dex::quote_path(&[A, B, C], 1_000), Ok(vec![1_000, 900, 818]));
+		assert_noop!(
+			Dex::swap_exact_in_path(RuntimeOrigin::signed(2), path(&[A, B, C]), 1_000, 819),
+			Error::<Test>::AmountBelowMinimum
+		);
+		assert_ok!(Dex::swap_exact_in_path(RuntimeOrigin::signed(2), path(&[A, B, C]), 1_000, 818));
+		System::assert_last_event(
+			Event::SwapExecuted {
+				who: 2,
+				asset_in: A,
+				asset_out: C,
+				amount_in: 1_000,
+				amount_out: 818,
+			}
+			.into(),
+		);
+		assert_eq!(Dex::reserves(A, B), Some((11_000, 9_100)));
+		assert_eq!(Dex::reserves(B, C), Some((10_900, 9_182)));
+		assert_eq!(Assets::balance(2, 2), 1_000_000);
+		assert_eq!(Assets::balance(3, 2), 818);
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn swap_paths_are_checked() {
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
-		assert_noop!(Dex::cause_error(RuntimeOrigin::signed(1)), Error::<Test>::NoneValue);
+		setup_pool();
+
+		assert_noop!(
+			Dex::swap_exact_in_path(RuntimeOrigin::signed(2), path(&[A]), 1_000, 0),
+			Error::<Test>::InvalidPath
+		);
+		// Visiting a pool twice is not allowed.
+		assert_noop!(
+			Dex::swap_exact_in_path(RuntimeOrigin::signed(2), path(&[A, B, A]), 1_000, 0),
+			Error::<Test>::InvalidPath
+		);
+		assert_noop!(
+			Dex::swap_exact_in_path(RuntimeOrigin::signed(2), path(&[A, C]), 1_000, 0),
+			Error::<Test>::PoolNotFound
+		);
+		assert_eq!(
+			Dex::quote_path(&[A, B, C, AssetKind::Native], 1_000),
+			Err(Error::<Test>::InvalidPath.into())
+		);
+	});
+}
+
+#[test]
+fn native_pools_price_assets() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::set_balance(&1, 1_000_000));
+		assert_ok!(Balances::set_balance(&2, 1_000));
+		create_asset(1, 1, 1_000_000);
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A));
+		assert_ok!(Dex::add_liquidity(
+			RuntimeOrigin::signed(1),
+			AssetKind::Native,
+			A,
+			10_000,
+			20_000,
+			0,
+			0
+		));
+
+		assert_eq!(<Dex as AssetPrice<u32, u128, u128>>::native_to_asset(1, 100), Some(200));
+		assert_eq!(<Dex as AssetPrice<u32, u128, u128>>::native_to_asset(2, 100), None);
+
+		assert_ok!(Dex::swap_exact_in(RuntimeOrigin::signed(2), AssetKind::Native, A, 100, 196));
+		assert_eq!(Balances::free_balance(2), 900);
+		assert_eq!(Assets::balance(1, 2), 196);
+	});
+}
+
+#[test]
+fn twap_averages_prices_over_time() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+
+		System::set_block_number(11);
+		assert_ok!(Dex::swap_exact_in(RuntimeOrigin::signed(2), A, B, 1_000, 0));
+		assert_eq!(Dex::reserves(A, B), Some((11_000, 9_100)));
+
+		// The price after the swap held for the last 10 blocks, and the price before it for the
+		// 10 blocks before that.
+		System::set_block_number(21);
+		let price = FixedU128::checked_from_rational(9_100u128, 11_000u128).unwrap();
+		assert_eq!(Dex::twap(A, B, 10), Some(price));
+		assert_eq!(
+			Dex::twap(A, B, 20),
+			Some(FixedU128::from_inner((FixedU128::one().into_inner() + price.into_inner()) / 2))
+		);
+		assert_eq!(Dex::twap(B, A, 10), FixedU128::checked_from_rational(11_000u128, 9_100u128));
+
+		// The pool's prices weren't observed that long ago.
+		assert_eq!(Dex::twap(A, B, 30), None);
+		assert_eq!(Dex::twap(A, C, 10), None);
+	});
+}
+
+#[test]
+fn on_initialize_records_observations_of_idle_pools() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+
+		for block in [5, 11, 21, 31] {
+			System::set_block_number(block);
+			Dex::on_initialize(block);
+		}
+
+		// An observation is only taken once `ObservationPeriod` has passed, and the oldest is
+		// dropped once `MaxObservations` are kept.
+		let blocks: Vec<_> = Observations::<Test>::get((A, B)).iter().map(|o| o.block).collect();
+		assert_eq!(blocks, vec![11, 21, 31]);
+		assert_eq!(PriceAccumulators::<Test>::get((A, B)).map(|a| a.block), Some(31));
+		assert_eq!(Dex::twap(A, B, 20), Some(FixedU128::one()));
+	});
+}
+
+#[test]
+fn limit_orders_are_placed_and_cancelled() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+
+		assert_noop!(
+			Dex::place_limit_order(RuntimeOrigin::signed(2), A, C, 1_000, 1_000),
+			Error::<Test>::PoolNotFound
+		);
+		assert_noop!(
+			Dex::place_limit_order(RuntimeOrigin::signed(2), A, B, 0, 1_000),
+			Error::<Test>::ZeroAmount
+		);
+
+		// The pool gives 900 of B for 1_000 of A, short of the limit, so the order waits.
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), A, B, 1_000, 950));
+		System::assert_last_event(
+			Event::LimitOrderPlaced {
+				order_id: 0,
+				who: 2,
+				asset_in: A,
+				asset_out: B,
+				amount_in: 1_000,
+				min_amount_out: 950,
+			}
+			.into(),
+		);
+		// Assets are frozen on the owner's account rather than moved.
+		assert_eq!(Assets::balance(1, 2), 1_000_000);
+		assert_eq!(OrderFunds::<Test>::get(1, 2), Some(1_000));
+		assert_eq!(
+			Assets::reducible_balance(1, &2, Preservation::Expendable, Fortitude::Polite),
+			998_999
+		);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &2), 10);
+		Dex::on_idle(1, Weight::MAX);
+		assert!(Orders::<Test>::contains_key(0));
+
+		// Orders in both directions share the pair's book.
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), B, A, 1_000, 1_100));
+		assert_eq!(OrderBooks::<Test>::get((A, B)).into_inner(), vec![0, 1]);
+		assert_noop!(
+			Dex::place_limit_order(RuntimeOrigin::signed(1), A, B, 1_000, 1_200),
+			Error::<Test>::OrderBookFull
+		);
+
+		assert_noop!(Dex::cancel_order(RuntimeOrigin::signed(1), 0), Error::<Test>::NotOrderOwner);
+		assert_ok!(Dex::cancel_order(RuntimeOrigin::signed(2), 0));
+		System::assert_last_event(Event::LimitOrderCancelled { order_id: 0, who: 2 }.into());
+		assert_eq!(OrderFunds::<Test>::get(1, 2), None);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &2), 10);
+		assert_eq!(OrderBooks::<Test>::get((A, B)).into_inner(), vec![1]);
+		assert_noop!(Dex::cancel_order(RuntimeOrigin::signed(2), 0), Error::<Test>::OrderNotFound);
+
+		// Native tokens are held on the owner's account rather than moved.
+		assert_ok!(Balances::set_balance(&1, 1_000_000));
+		assert_ok!(Balances::set_balance(&2, 10_000));
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A));
+		assert_ok!(Dex::add_liquidity(
+			RuntimeOrigin::signed(1),
+			AssetKind::Native,
+			A,
+			10_000,
+			10_000,
+			0,
+			0
+		));
+		assert_ok!(Dex::place_limit_order(
+			RuntimeOrigin::signed(2),
+			AssetKind::Native,
+			A,
+			1_000,
+			2_000
+		));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::LimitOrder.into(), &2), 1_000);
+		assert_ok!(Dex::cancel_order(RuntimeOrigin::signed(2), 2));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::LimitOrder.into(), &2), 0);
+		assert_eq!(Balances::free_balance(2), 10_000);
+	});
+}
+
+#[test]
+fn limit_orders_are_filled_when_the_pool_reaches_their_limit() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), B, A, 1_000, 1_000));
+		Dex::on_idle(1, Weight::MAX);
+		assert!(Orders::<Test>::contains_key(0));
+
+		// A swap into the pool makes B dearer, until 1_000 of it gives 1_272 of A.
+		assert_ok!(Dex::swap_exact_in(RuntimeOrigin::signed(1), A, B, 2_000, 0));
+		assert_eq!(Dex::reserves(A, B), Some((12_000, 8_348)));
+
+		System::set_block_number(2);
+		Dex::on_idle(2, Weight::MAX);
+		System::assert_last_event(
+			Event::LimitOrderFilled { order_id: 0, who: 2, amount_in: 1_000, amount_out: 1_272 }
+				.into(),
+		);
+		assert_eq!(Assets::balance(1, 2), 1_001_272);
+		assert_eq!(Assets::balance(2, 2), 999_000);
+		assert_eq!(OrderFunds::<Test>::get(2, 2), None);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &2), 0);
+		assert_eq!(OrderCounts::<Test>::get(2), 0);
+		assert!(!Orders::<Test>::contains_key(0));
+		assert!(OrderBooks::<Test>::get((A, B)).is_empty());
+	});
+}
+
+#[test]
+fn orders_closer_to_being_filled_evict_the_furthest_from_a_full_book() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(1), 2.into(), 3, 1_000_000));
+		assert_ok!(Balances::set_balance(&3, 1_000));
+
+		// Account 2 fills the book with orders the pool is far from filling.
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), A, B, 1_000, 5_000));
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), B, A, 1_000, 3_000));
+
+		// An order further from being filled than both is turned away.
+		assert_noop!(
+			Dex::place_limit_order(RuntimeOrigin::signed(3), B, A, 1_000, 6_000),
+			Error::<Test>::OrderBookFull
+		);
+
+		// A closer order takes the place of the furthest one, whose funds are returned.
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(3), B, A, 1_000, 1_000));
+		System::assert_has_event(Event::LimitOrderEvicted { order_id: 0, who: 2 }.into());
+		assert!(!Orders::<Test>::contains_key(0));
+		assert_eq!(OrderBooks::<Test>::get((A, B)).into_inner(), vec![1, 2]);
+		assert_eq!(OrderFunds::<Test>::get(1, 2), None);
+		assert_eq!(OrderFunds::<Test>::get(2, 2), Some(1_000));
+		assert_eq!(OrderCounts::<Test>::get(2), 1);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &2), 10);
+	});
+}
+
+#[test]
+fn frozen_order_funds_cannot_be_transferred_or_burned() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), A, B, 1_000, 5_000));
+
+		// Everything but the frozen 1_000 and the minimum balance can be moved.
+		assert_noop!(
+			Assets::transfer(RuntimeOrigin::signed(2), 1.into(), 3, 999_000),
+			pallet_assets::Error::<Test>::BalanceLow
+		);
+		assert_noop!(
+			<Assets as fungibles::Mutate<u64>>::transfer(
+				1,
+				&2,
+				&3,
+				999_000,
+				Preservation::Expendable
+			),
+			TokenError::Frozen
+		);
+
+		// The asset's admin burns at most the funds that aren't frozen.
+		assert_ok!(Assets::burn(RuntimeOrigin::signed(1), 1.into(), 2, 1_000_000));
+		assert_eq!(Assets::balance(1, 2), 1_001);
+		assert_noop!(
+			Assets::transfer(RuntimeOrigin::signed(2), 1.into(), 3, 1),
+			pallet_assets::Error::<Test>::BalanceLow
+		);
+
+		// The order's funds are still there once it is cancelled.
+		assert_ok!(Dex::cancel_order(RuntimeOrigin::signed(2), 0));
+		assert_ok!(Assets::transfer(RuntimeOrigin::signed(2), 1.into(), 3, 1_000));
+	});
+}
+
+#[test]
+fn limit_orders_take_a_deposit_and_are_capped_per_account() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), A, B, 1_000, 1_000));
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), A, B, 1_000, 1_000));
+		assert_eq!(OrderCounts::<Test>::get(2), 2);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &2), 20);
+		assert_eq!(OrderFunds::<Test>::get(1, 2), Some(2_000));
+		assert_noop!(
+			Dex::place_limit_order(RuntimeOrigin::signed(2), B, A, 1_000, 1_000),
+			Error::<Test>::TooManyOrders
+		);
+
+		// Frozen assets can't be transferred.
+		assert_noop!(
+			Assets::transfer(RuntimeOrigin::signed(2), 1.into(), 3, 999_000),
+			pallet_assets::Error::<Test>::BalanceLow
+		);
+
+		// Cancelling returns the deposit and frees a slot.
+		assert_ok!(Dex::cancel_order(RuntimeOrigin::signed(2), 0));
+		assert_eq!(OrderCounts::<Test>::get(2), 1);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &2), 10);
+		assert_noop!(
+			Dex::place_limit_order(RuntimeOrigin::signed(2), A, B, 999_000, 1_000),
+			TokenError::FundsUnavailable
+		);
+		assert_ok!(Dex::place_limit_order(RuntimeOrigin::signed(2), B, A, 1_000, 1_000));
+
+		// Accounts that can't pay the deposit can't place orders.
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(1), 1.into(), 3, 1_000));
+		assert_ok!(Balances::set_balance(&3, 5));
+		assert_noop!(
+			Dex::place_limit_order(RuntimeOrigin::signed(3), A, B, 100, 100),
+			TokenError::FundsUnavailable
+		);
+	});
+}
+
+#[test]
+fn incentives_are_created_by_root_and_funded() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+		create_asset(3, 1, 1_000_000);
+
+		assert_noop!(
+			Dex::create_incentive(RuntimeOrigin::signed(1), A, B, 3, 100),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Dex::create_incentive(RuntimeOrigin::root(), A, C, 3, 100),
+			Error::<Test>::PoolNotFound
+		);
+		assert_noop!(
+			Dex::create_incentive(RuntimeOrigin::root(), A, B, 3, 0),
+			Error::<Test>::ZeroAmount
+		);
+		assert_ok!(Dex::create_incentive(RuntimeOrigin::root(), B, A, 3, 100));
+		System::assert_last_event(
+			Event::IncentiveCreated {
+				incentive_id: 0,
+				pool_id: (A, B),
+				reward_asset: 3,
+				reward_per_block: 100,
+			}
+			.into(),
+		);
+		assert_eq!(Incentives::<Test>::get(0).map(|i| i.lp_token), Some(1_000));
+
+		assert_noop!(
+			Dex::fund_incentive(RuntimeOrigin::signed(1), 1, 1_000),
+			Error::<Test>::IncentiveNotFound
+		);
+		assert_ok!(Dex::fund_incentive(RuntimeOrigin::signed(1), 0, 1_000));
+		System::assert_last_event(
+			Event::IncentiveFunded { incentive_id: 0, who: 1, amount: 1_000 }.into(),
+		);
+		assert_eq!(Incentives::<Test>::get(0).map(|i| i.rewards_left), Some(1_000));
+		assert_eq!(Assets::balance(3, Dex::incentive_account(0)), 1_000);
+	});
+}
+
+#[test]
+fn stakers_share_rewards_in_proportion_to_their_stakes() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+		create_asset(3, 1, 1_000_000);
+		assert_ok!(Dex::add_liquidity(RuntimeOrigin::signed(2), A, B, 2_000, 2_000, 0, 0));
+		assert_ok!(Dex::create_incentive(RuntimeOrigin::root(), A, B, 3, 300));
+		assert_ok!(Dex::fund_incentive(RuntimeOrigin::signed(1), 0, 2_100));
+		assert_ok!(Dex::stake(RuntimeOrigin::signed(1), 0, 1_000));
+		System::assert_last_event(Event::Staked { incentive_id: 0, who: 1, amount: 1_000 }.into());
+
+		// Account 1 earns every block's rewards while it stakes alone.
+		System::set_block_number(3);
+		assert_eq!(Dex::pending_rewards(0, &1), 600);
+		assert_ok!(Dex::stake(RuntimeOrigin::signed(2), 0, 2_000));
+		assert_eq!(Assets::balance(1_000, 2), 0);
+
+		// Then a third of them, for a third of the stake.
+		System::set_block_number(5);
+		assert_ok!(Dex::claim_rewards(RuntimeOrigin::signed(1), 0));
+		System::assert_last_event(
+			Event::RewardsPaid { incentive_id: 0, who: 1, amount: 800 }.into(),
+		);
+		assert_eq!(Assets::balance(3, 1), 1_000_000 - 2_100 + 800);
+		assert_eq!(Dex::pending_rewards(0, &1), 0);
+		assert_eq!(Dex::pending_rewards(0, &2), 400);
+
+		// Rewards stop once the funded 2_100 have been shared out.
+		System::set_block_number(20);
+		assert_eq!(Dex::pending_rewards(0, &1), 300);
+		assert_eq!(Dex::pending_rewards(0, &2), 1_000);
+
+		assert_noop!(
+			Dex::unstake(RuntimeOrigin::signed(1), 0, 2_000),
+			Error::<Test>::InsufficientStake
+		);
+		assert_ok!(Dex::unstake(RuntimeOrigin::signed(2), 0, 2_000));
+		System::assert_has_event(
+			Event::RewardsPaid { incentive_id: 0, who: 2, amount: 1_000 }.into(),
+		);
+		System::assert_last_event(
+			Event::Unstaked { incentive_id: 0, who: 2, amount: 2_000 }.into(),
+		);
+		assert_eq!(Assets::balance(3, 2), 1_000);
+		assert_eq!(Assets::balance(1_000, 2), 2_000);
+		assert_eq!(Stakes::<Test>::get(0, 2), None);
+		assert_eq!(Assets::balance(3, Dex::incentive_account(0)), 300);
+	});
+}
+
+#[test]
+fn admin_origin_sets_the_swap_fee_and_pool_creators() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000));
+		create_asset(1, 1, 1_000_000);
+		create_asset(2, 1, 1_000_000);
+
+		assert_noop!(
+			Dex::set_swap_fee(RuntimeOrigin::signed(1), Permill::from_percent(2)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Dex::set_swap_fee(RuntimeOrigin::root(), Permill::one()),
+			Error::<Test>::InvalidSwapFee
+		);
+		assert_eq!(SwapFee::<Test>::get(), Permill::from_percent(1));
+		assert_ok!(Dex::set_swap_fee(RuntimeOrigin::root(), Permill::from_percent(2)));
+		System::assert_last_event(Event::SwapFeeSet { fee: Permill::from_percent(2) }.into());
+		assert_eq!(Dex::get_amount_out(1_000, 10_000, 10_000), Ok(892));
+
+		// Only accounts on the allowlist can create pools while creation is restricted.
+		assert_noop!(
+			Dex::set_pool_creation_restricted(RuntimeOrigin::signed(1), true),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Dex::set_pool_creation_restricted(RuntimeOrigin::root(), true));
+		System::assert_last_event(Event::PoolCreationRestrictedSet { restricted: true }.into());
+		assert_noop!(
+			Dex::create_pool(RuntimeOrigin::signed(1), A, B),
+			Error::<Test>::NotPoolCreator
+		);
+		assert_ok!(Dex::set_pool_creator(RuntimeOrigin::root(), 1, true));
+		System::assert_last_event(Event::PoolCreatorSet { who: 1, allowed: true }.into());
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), A, B));
+
+		assert_ok!(Dex::set_pool_creator(RuntimeOrigin::root(), 1, false));
+		assert!(!PoolCreators::<Test>::contains_key(1));
+		assert_noop!(
+			Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A),
+			Error::<Test>::NotPoolCreator
+		);
+		assert_ok!(Dex::set_pool_creation_restricted(RuntimeOrigin::root(), false));
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A));
+	});
+}
+
+#[test]
+fn protocol_fee_is_paid_to_the_recipient() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_pool();
+
+		assert_noop!(
+			Dex::set_protocol_fee_recipient(RuntimeOrigin::signed(1), Some(3)),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Dex::set_protocol_fee_recipient(RuntimeOrigin::root(), Some(3)));
+		System::assert_last_event(Event::ProtocolFeeRecipientSet { recipient: Some(3) }.into());
+
+		// Half of the 1% fee is paid to the recipient, and the rest left in the pool.
+		assert_ok!(Dex::swap_exact_in(RuntimeOrigin::signed(2), A, B, 1_000, 900));
+		assert_eq!(Assets::balance(1, 3), 5);
+		assert_eq!(Dex::reserves(A, B), Some((10_995, 9_100)));
+
+		// Without a recipient the whole fee stays in the pool.
+		assert_ok!(Dex::set_protocol_fee_recipient(RuntimeOrigin::root(), None));
+		assert_eq!(ProtocolFeeRecipient::<Test>::get(), None);
+		assert_ok!(Dex::swap_exact_in(RuntimeOrigin::signed(2), B, A, 1_000, 0));
+		assert_eq!(Assets::balance(2, 3), 0);
+		assert_eq!(Dex::reserves(A, B).map(|(_, b)| b), Some(10_100));
+	});
+}
+
+#[test]
+fn fee_assets_are_approved_by_root_and_chosen_by_accounts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		assert_noop!(
+			Dex::set_fee_asset(RuntimeOrigin::signed(2), Some(1)),
+			Error::<Test>::FeeAssetNotApproved
+		);
+		assert_noop!(
+			Dex::approve_fee_asset(RuntimeOrigin::signed(1), 1),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Dex::approve_fee_asset(RuntimeOrigin::root(), 1));
+		System::assert_last_event(Event::FeeAssetApproved { asset: 1 }.into());
+
+		assert_ok!(Dex::set_fee_asset(RuntimeOrigin::signed(2), Some(1)));
+		System::assert_last_event(Event::FeeAssetSet { who: 2, asset: Some(1) }.into());
+		assert_eq!(Dex::fee_asset(&2), Some(1));
+
+		// Removing the approval makes the account pay in the native token again.
+		assert_ok!(Dex::remove_fee_asset(RuntimeOrigin::root(), 1));
+		System::assert_last_event(Event::FeeAssetRemoved { asset: 1 }.into());
+		assert!(!FeeAssets::<Test>::contains_key(1));
+		assert_eq!(AccountFeeAsset::<Test>::get(2), Some(1));
+		assert_eq!(Dex::fee_asset(&2), None);
+		assert_noop!(
+			Dex::remove_fee_asset(RuntimeOrigin::root(), 1),
+			Error::<Test>::FeeAssetNotApproved
+		);
+
+		assert_ok!(Dex::set_fee_asset(RuntimeOrigin::signed(2), None));
+		assert_eq!(AccountFeeAsset::<Test>::get(2), None);
+	});
+}
+
+#[test]
+fn fees_are_charged_and_refunded_in_the_fee_asset() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		create_asset(1, 2, 1_000_000_000);
+		assert_ok!(Dex::approve_fee_asset(RuntimeOrigin::root(), 1));
+		assert_ok!(Dex::set_fee_asset(RuntimeOrigin::signed(2), Some(1)));
+
+		// Asset 1 can't pay fees until it has a pool with the native token to swap them in.
+		let ext = ChargeTransactionPayment::<Test>::from(0);
+		let info = DispatchInfo { weight: Weight::from_parts(1_000, 0), ..Default::default() };
+		assert!(ext.clone().pre_dispatch(&2, &remark_call(), &info, 10).is_err());
+		assert_ok!(Balances::set_balance(&1, 2_000_000_000_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(1), 1.into(), 1, 2_000_000_000_000));
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A));
+		assert_ok!(Dex::add_liquidity(
+			RuntimeOrigin::signed(1),
+			AssetKind::Native,
+			A,
+			1_000_000_000_000,
+			2_000_000_000_000,
+			0,
+			0
+		));
+		let pool_account = Dex::pool_account(&Dex::pool_id(AssetKind::Native, A));
+		let native_reserve = Balances::free_balance(pool_account);
+		let asset_reserve = Assets::balance(1, pool_account);
+
+		// Account 2 has no native tokens, and pays twice the native fee in asset 1 into the pool.
+		let issuance = Assets::total_issuance(1);
+		let pre = ext.pre_dispatch(&2, &remark_call(), &info, 10).unwrap();
+		let withdrawn = 1_000_000_000 - Assets::balance(1, 2);
+		assert_eq!(withdrawn, 2 * TransactionPayment::compute_fee(10, &info, 0));
+		assert_eq!(Balances::free_balance(2), 0);
+		assert_eq!(Assets::balance(1, pool_account), asset_reserve + withdrawn);
+
+		// The fee for unused weight is refunded in the same asset.
+		let post_info = PostDispatchInfo {
+			actual_weight: Some(Weight::from_parts(400, 0)),
+			pays_fee: frame_support::dispatch::Pays::Yes,
+		};
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&post_info,
+			10,
+			&Ok(())
+		));
+		let fee = 1_000_000_000 - Assets::balance(1, 2);
+		assert_eq!(fee, withdrawn - 1_200);
+		System::assert_has_event(Event::AssetFeePaid { who: 2, asset: 1, fee }.into());
+
+		// The fee is swapped through the pool, and the fee account receives the native tokens.
+		assert_eq!(Assets::balance(1, pool_account), asset_reserve + fee);
+		let native = native_reserve - Balances::free_balance(pool_account);
+		assert_eq!(native, Dex::get_amount_out(fee, asset_reserve, native_reserve).unwrap());
+		assert_eq!(Balances::free_balance(FEE_ACCOUNT), native);
+		assert_eq!(Assets::total_issuance(1), issuance);
+	});
+}
+
+#[test]
+fn fees_are_given_back_when_the_pool_cannot_swap_them() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		create_asset(1, 2, 1_000_000_000);
+		assert_ok!(Dex::approve_fee_asset(RuntimeOrigin::root(), 1));
+		assert_ok!(Dex::set_fee_asset(RuntimeOrigin::signed(2), Some(1)));
+		assert_ok!(Balances::set_balance(&1, 2_000_000_000_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(1), 1.into(), 1, 2_000_000_000_000));
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(1), AssetKind::Native, A));
+		assert_ok!(Dex::add_liquidity(
+			RuntimeOrigin::signed(1),
+			AssetKind::Native,
+			A,
+			1_000_000_000_000,
+			2_000_000_000_000,
+			0,
+			0
+		));
+		let pool_account = Dex::pool_account(&Dex::pool_id(AssetKind::Native, A));
+		let asset_reserve = Assets::balance(1, pool_account);
+
+		let ext = ChargeTransactionPayment::<Test>::from(0);
+		let info = DispatchInfo { weight: Weight::from_parts(1_000, 0), ..Default::default() };
+		let pre = ext.pre_dispatch(&2, &remark_call(), &info, 10).unwrap();
+		assert!(Assets::balance(1, 2) < 1_000_000_000);
+
+		// The pool has no native tokens left to buy with the fee, so it is given back in full.
+		assert_ok!(Balances::set_balance(&pool_account, 0));
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&PostDispatchInfo::default(),
+			10,
+			&Ok(())
+		));
+		assert_eq!(Assets::balance(1, 2), 1_000_000_000);
+		assert_eq!(Assets::balance(1, pool_account), asset_reserve);
+		System::assert_has_event(Event::AssetFeePaid { who: 2, asset: 1, fee: 0 }.into());
+		assert_eq!(Balances::free_balance(FEE_ACCOUNT), 0);
+	});
+}
+
+#[test]
+fn fees_fall_back_to_the_native_token() {
+	new_test_ext().execute_with(|| {
+		create_asset(1, 2, 1_000_000_000);
+		create_asset(2, 2, 1_000_000_000);
+		assert_ok!(Balances::set_balance(&2, 1_000_000_000));
+		let ext = ChargeTransactionPayment::<Test>::from(0);
+		let info = DispatchInfo { weight: Weight::from_parts(1_000, 0), ..Default::default() };
+
+		// Without an approved fee asset, the fee is paid in the native token.
+		assert_ok!(ext.clone().pre_dispatch(&2, &remark_call(), &info, 10));
+		assert!(Balances::free_balance(2) < 1_000_000_000);
+		assert_eq!(Assets::balance(1, 2), 1_000_000_000);
+
+		// An asset without a price cannot pay fees.
+		assert_ok!(Dex::approve_fee_asset(RuntimeOrigin::root(), 2));
+		assert_ok!(Dex::set_fee_asset(RuntimeOrigin::signed(2), Some(2)));
+		assert!(ext.clone().pre_dispatch(&2, &remark_call(), &info, 10).is_err());
+		assert_eq!(Assets::balance(2, 2), 1_000_000_000);
+
+		// Neither can an account without enough of the asset.
+		assert_ok!(Dex::approve_fee_asset(RuntimeOrigin::root(), 1));
+		assert_ok!(Dex::set_fee_asset(RuntimeOrigin::signed(3), Some(1)));
+		assert!(ext.pre_dispatch(&3, &remark_call(), &info, 10).is_err());
 	});
 }
@@ -47,6 +47,9 @@ pub mod pallet {
 
 		/// A conversion which takes an authority id, and returns the associated account id.
 		type AuthorityToAccount: Convert<Self::AuthorityId, Self::AccountId>;
+
+		/// The origin allowed to force a change of authorities.
+		type ForceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
 	}
 
 	// This storage only appears in tests, and is used to control the fake
@@ -93,7 +96,7 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			who: T::AuthorityId,
 		) -> DispatchResult {
-			ensure_root(origin)?;
+			T::ForceOrigin::ensure_origin(origin)?;
 			let mut authorities = BoundedVec::<T::AuthorityId, T::MaxAuthorities>::default();
 			authorities.try_push(who).map_err(|_| Error::<T>::TooManyAuthorities)?;
 			pallet_aura::Pallet::<T>::change_authorities(authorities);
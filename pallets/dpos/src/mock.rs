@@ -93,6 +93,7 @@ impl pallet_dpos::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type NativeBalance = Balances;
 	type AuthorityToAccount = AuthorityToAccount;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 // Build genesis storage according to the mock runtime.
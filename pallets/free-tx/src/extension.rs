@@ -0,0 +1,184 @@
+//! Signed extensions for free transactions. `ChargeTransactionPaymentOrFree` waives the
+//! transaction fee of governance calls for eligible accounts, up to a quota per era, and charges
+//! the fee as usual otherwise. `CheckFreeTxQuota` keeps `free_tx` calls over the quota, and
+//! `accept_sponsorship` calls without an offer, out of the transaction pool.
+
+use crate::{Call, Config, Pallet, SponsorshipOffers};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::{DispatchInfo, DispatchResult, PostDispatchInfo},
+	traits::IsSubType,
+};
+use pallet_transaction_payment::{BalanceOf, ChargeTransactionPayment};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+use sp_std::marker::PhantomData;
+
+/// The custom `InvalidTransaction` code of a `free_tx` call from an account without free
+/// transactions left.
+pub const QUOTA_EXCEEDED: u8 = 1;
+
+/// The custom `InvalidTransaction` code of an `accept_sponsorship` call for an offer that doesn't
+/// exist.
+pub const NO_SPONSORSHIP_OFFER: u8 = 2;
+
+/// Wraps `ChargeTransactionPayment`, skipping it for calls allowed by `FreeCalls` when the signer
+/// is one of `FreeTxAccounts` and has free transactions left in the current era. The tip is
+/// waived along with the fee.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeTransactionPaymentOrFree<T: Config>(ChargeTransactionPayment<T>);
+
+impl<T: Config> ChargeTransactionPaymentOrFree<T>
+where
+	BalanceOf<T>: Send + Sync + From<u64>,
+{
+	/// Create the extension with a tip, paid unless the fee is waived.
+	pub fn from(tip: BalanceOf<T>) -> Self {
+		Self(ChargeTransactionPayment::from(tip))
+	}
+}
+
+impl<T: Config> sp_std::fmt::Debug for ChargeTransactionPaymentOrFree<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ChargeTransactionPaymentOrFree<{:?}>", self.0)
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config> SignedExtension for ChargeTransactionPaymentOrFree<T>
+where
+	<T as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	BalanceOf<T>: Send + Sync + From<u64>,
+{
+	const IDENTIFIER: &'static str = "ChargeTransactionPaymentOrFree";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	/// `None` when the fee was waived.
+	type Pre = Option<<ChargeTransactionPayment<T> as SignedExtension>::Pre>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		if Pallet::<T>::is_fee_waived(who, call) {
+			return Ok(ValidTransaction::default())
+		}
+		self.0.validate(who, call, info, len)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		// The quota is checked again, as earlier transactions in the block may have used it up.
+		if Pallet::<T>::is_fee_waived(who, call) {
+			Pallet::<T>::use_free_tx(who);
+			return Ok(None)
+		}
+		self.0.pre_dispatch(who, call, info, len).map(Some)
+	}
+
+	fn post_dispatch(
+		maybe_pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match maybe_pre {
+			// The fee was waived, so there is nothing to refund.
+			Some(None) => Ok(()),
+			maybe_pre => ChargeTransactionPayment::<T>::post_dispatch(
+				maybe_pre.flatten(),
+				info,
+				post_info,
+				len,
+				result,
+			),
+		}
+	}
+}
+
+/// Rejects `free_tx` calls from accounts that have used up their free transactions for the era,
+/// and `accept_sponsorship` calls for offers that don't exist, so these free calls are dropped by
+/// the transaction pool rather than failing in a block.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckFreeTxQuota<T: Config>(PhantomData<T>);
+
+impl<T: Config> CheckFreeTxQuota<T> {
+	/// Create the extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config> sp_std::fmt::Debug for CheckFreeTxQuota<T> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckFreeTxQuota")
+	}
+}
+
+impl<T: Config> SignedExtension for CheckFreeTxQuota<T>
+where
+	<T as frame_system::Config>::RuntimeCall: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckFreeTxQuota";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		match call.is_sub_type() {
+			Some(Call::free_tx { .. }) if Pallet::<T>::free_tx_remaining(who) == 0 =>
+				Err(InvalidTransaction::Custom(QUOTA_EXCEEDED).into()),
+			Some(Call::accept_sponsorship { sponsor })
+				if !SponsorshipOffers::<T>::contains_key(who, sponsor) =>
+				Err(InvalidTransaction::Custom(NO_SPONSORSHIP_OFFER).into()),
+			_ => Ok(ValidTransaction::default()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<(), TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
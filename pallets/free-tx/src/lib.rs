@@ -1,6 +1,9 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{pallet_prelude::*, traits::fungible};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{fungible, tokens::Precision, Contains},
+};
 /// Edit this file to define custom logic or remove it if it is not needed.
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
 /// <https://docs.substrate.io/reference/frame-pallets/>
@@ -15,16 +18,27 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+mod extension;
+pub use extension::{
+	ChargeTransactionPaymentOrFree, CheckFreeTxQuota, NO_SPONSORSHIP_OFFER, QUOTA_EXCEEDED,
+};
+
+mod payment;
+pub use payment::SponsoredCurrencyAdapter;
+
 pub type BalanceOf<T> = <<T as Config>::NativeBalance as fungible::Inspect<
 	<T as frame_system::Config>::AccountId,
 >>::Balance;
 
-use frame_support::dispatch::{Dispatchable, GetDispatchInfo};
+use frame_support::dispatch::{
+	Dispatchable, GetDispatchInfo, PostDispatchInfo, WithPostDispatchInfo,
+};
+use sp_runtime::traits::{CheckedDiv, Saturating, Zero};
 
 #[frame_support::pallet]
 pub mod pallet {
 	use crate::*;
-	use frame_system::{pallet_prelude::*, RawOrigin};
+	use frame_system::pallet_prelude::*;
 	use sp_std::prelude::*;
 
 	#[pallet::pallet]
@@ -32,23 +46,67 @@ pub mod pallet {
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
 		/// Type to access the Balances Pallet.
 		type NativeBalance: fungible::Inspect<Self::AccountId>
 			+ fungible::Mutate<Self::AccountId>
-			+ fungible::hold::Inspect<Self::AccountId>
+			+ fungible::hold::Inspect<Self::AccountId, Reason = Self::RuntimeHoldReason>
 			+ fungible::hold::Mutate<Self::AccountId>
 			+ fungible::freeze::Inspect<Self::AccountId>
 			+ fungible::freeze::Mutate<Self::AccountId>;
 
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
+
 		/// A type representing all calls available in your runtime.
 		#[pallet::no_default_bounds]
 		type RuntimeCall: Parameter
-			+ Dispatchable<RuntimeOrigin = Self::RuntimeOrigin>
+			+ Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
 			+ GetDispatchInfo;
+
+		/// The calls that can be dispatched with `redispatch`.
+		type CallFilter: Contains<<Self as Config>::RuntimeCall>;
+
+		/// The maximum number of `redispatch` calls that can be nested inside each other,
+		/// including the outermost one. The depth is shared with every pallet redispatching calls
+		/// through `redispatch_primitives`, whatever pallet the nested calls belong to.
+		#[pallet::constant]
+		type MaxNestingDepth: Get<u32>;
+
+		/// The calls whose fees `ChargeTransactionPaymentOrFree` can waive.
+		type FreeCalls: Contains<<Self as frame_system::Config>::RuntimeCall>;
+
+		/// The accounts whose fees `ChargeTransactionPaymentOrFree` can waive.
+		type FreeTxAccounts: Contains<Self::AccountId>;
+
+		/// The number of free transactions an account has per era, used both by `free_tx` calls
+		/// and by transactions whose fees are waived.
+		#[pallet::constant]
+		type FreeTxPerEra: Get<u32>;
+
+		/// The number of blocks in an era.
+		#[pallet::constant]
+		type EraLength: Get<BlockNumberFor<Self>>;
+
+		/// The maximum number of calls a sponsorship can be limited to.
+		#[pallet::constant]
+		type MaxSponsoredCalls: Get<u32>;
+	}
+
+	/// A sponsor's offer to pay a beneficiary's transaction fees, up to an allowance and until
+	/// an expiry block. The allowance is held from the sponsor until it is used or the offer or
+	/// sponsorship is removed. `calls` holds the pallet and call index, the first two bytes of the
+	/// encoded call, of each call the sponsorship covers. An empty list covers every call.
+	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Sponsorship<T: Config> {
+		pub sponsor: T::AccountId,
+		pub allowance: BalanceOf<T>,
+		pub expiry: BlockNumberFor<T>,
+		pub calls: BoundedVec<[u8; 2], T::MaxSponsoredCalls>,
 	}
 
 	// The pallet's runtime storage items.
@@ -59,6 +117,35 @@ pub mod pallet {
 	// https://docs.substrate.io/main-docs/build/runtime-storage/#declaring-storage-items
 	pub type Something<T> = StorageValue<_, u32>;
 
+	/// The latest era each account had a fee waived in, and the number of fees waived in it.
+	#[pallet::storage]
+	pub type FreeTxUsage<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (BlockNumberFor<T>, u32)>;
+
+	/// The sponsorship paying each beneficiary's transaction fees.
+	#[pallet::storage]
+	pub type Sponsorships<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Sponsorship<T>>;
+
+	/// Sponsorships offered to each beneficiary by each sponsor, which the beneficiary hasn't
+	/// accepted yet.
+	#[pallet::storage]
+	pub type SponsorshipOffers<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AccountId,
+		Sponsorship<T>,
+	>;
+
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		#[codec(index = 0)]
+		SponsorshipAllowance,
+	}
+
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/main-docs/build/events-errors/
 	#[pallet::event]
@@ -66,6 +153,30 @@ pub mod pallet {
 	pub enum Event<T: Config> {
 		/// Events should be documented.
 		TxSuccess,
+		/// A transaction's fee was waived. [who, remaining]
+		FeeWaived { who: T::AccountId, remaining: u32 },
+		/// A sponsor offered to pay a beneficiary's fees. [sponsor, beneficiary, allowance,
+		/// expiry]
+		SponsorshipOffered {
+			sponsor: T::AccountId,
+			beneficiary: T::AccountId,
+			allowance: BalanceOf<T>,
+			expiry: BlockNumberFor<T>,
+		},
+		/// A sponsorship offer was removed without being accepted. [sponsor, beneficiary]
+		SponsorshipOfferRemoved { sponsor: T::AccountId, beneficiary: T::AccountId },
+		/// A sponsor started paying a beneficiary's fees. [sponsor, beneficiary, allowance,
+		/// expiry]
+		SponsorshipCreated {
+			sponsor: T::AccountId,
+			beneficiary: T::AccountId,
+			allowance: BalanceOf<T>,
+			expiry: BlockNumberFor<T>,
+		},
+		/// A sponsorship was removed. [sponsor, beneficiary]
+		SponsorshipRemoved { sponsor: T::AccountId, beneficiary: T::AccountId },
+		/// A sponsor paid a beneficiary's transaction fee. [sponsor, beneficiary, fee]
+		FeeSponsored { sponsor: T::AccountId, beneficiary: T::AccountId, fee: BalanceOf<T> },
 	}
 
 	// Errors inform users that something went wrong.
@@ -75,6 +186,24 @@ pub mod pallet {
 		TxFailed,
 		/// Errors should have helpful documentation associated with them.
 		StorageOverflow,
+		/// The account has no free transactions left in the current era.
+		QuotaExceeded,
+		/// The call is not allowed by `CallFilter`.
+		CallFiltered,
+		/// `redispatch` calls are nested more than `MaxNestingDepth` deep.
+		TooDeeplyNested,
+		/// A sponsorship can't cover more than `MaxSponsoredCalls` calls.
+		TooManySponsoredCalls,
+		/// A sponsorship must expire after the current block.
+		InvalidExpiry,
+		/// The beneficiary has no sponsorship.
+		SponsorshipNotFound,
+		/// Only the sponsor or the beneficiary can remove a sponsorship.
+		NotSponsorOrBeneficiary,
+		/// The sponsor hasn't offered the beneficiary a sponsorship.
+		OfferNotFound,
+		/// A sponsorship offer must hold at least the existential deposit.
+		AllowanceTooLow,
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -82,14 +211,18 @@ pub mod pallet {
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// An example dispatchable that charges no fee if successful.
+		/// An example dispatchable that charges no fee if successful, using up one of the
+		/// signer's free transactions for the era. `CheckFreeTxQuota` keeps it out of the
+		/// transaction pool once the signer has none left.
 		#[pallet::call_index(0)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
 		pub fn free_tx(origin: OriginFor<T>, success: bool) -> DispatchResultWithPostInfo {
-			let _who = ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
 
-			// If this line fails, the user ends up paying a fee.
+			// If either of these lines fail, the user ends up paying a fee.
 			ensure!(success, Error::<T>::TxFailed);
+			ensure!(Self::free_tx_remaining(&who) > 0, Error::<T>::QuotaExceeded);
+			Self::use_free_tx(&who);
 
 			// Deposit a basic event.
 			Self::deposit_event(Event::<T>::TxSuccess);
@@ -98,20 +231,157 @@ pub mod pallet {
 			Ok(Pays::No.into())
 		}
 
-		/// An example of re-dispatching a call
+		/// Re-dispatch a call from the signer's origin. The call must be allowed by
+		/// `CallFilter`, and `redispatch` calls can be nested at most `MaxNestingDepth` deep.
+		/// The call's post dispatch info is kept, so its unused weight is refunded and a free
+		/// call stays free.
 		#[pallet::call_index(1)]
-		#[pallet::weight(call.get_dispatch_info().weight)]
+		#[pallet::weight(
+			call.get_dispatch_info().weight.saturating_add(Pallet::<T>::redispatch_overhead())
+		)]
 		pub fn redispatch(
 			origin: OriginFor<T>,
 			call: Box<<T as Config>::RuntimeCall>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_redispatch(who, *call)
+		}
+
+		/// Offer to pay a beneficiary's transaction fees, up to an allowance and until an expiry
+		/// block. `calls` limits the sponsorship to calls with the given pallet and call indices;
+		/// an empty list covers every call. The allowance is held from the signer.
+		///
+		/// The offer takes effect once the beneficiary accepts it with `accept_sponsorship`,
+		/// which unfunded accounts can do for free. It must hold at least the existential
+		/// deposit, and keeps the beneficiary's account alive until it is removed, so the
+		/// beneficiary can sign transactions without funds. A sponsor whose sponsorship the
+		/// beneficiary accepted changes it directly.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(4, 4))]
+		pub fn sponsor(
+			origin: OriginFor<T>,
+			beneficiary: T::AccountId,
+			allowance: BalanceOf<T>,
+			expiry: BlockNumberFor<T>,
+			calls: Vec<[u8; 2]>,
+		) -> DispatchResult {
+			let sponsor = ensure_signed(origin)?;
+			ensure!(expiry > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidExpiry);
+			let calls =
+				BoundedVec::try_from(calls).map_err(|_| Error::<T>::TooManySponsoredCalls)?;
+			let sponsorship = Sponsorship { sponsor: sponsor.clone(), allowance, expiry, calls };
+
+			match Sponsorships::<T>::get(&beneficiary) {
+				Some(existing) if existing.sponsor == sponsor => {
+					Self::release_allowance(&existing)?;
+					Self::hold_allowance(&sponsorship)?;
+					Sponsorships::<T>::insert(&beneficiary, sponsorship);
+
+					Self::deposit_event(Event::SponsorshipCreated {
+						sponsor,
+						beneficiary,
+						allowance,
+						expiry,
+					});
+				},
+				_ => {
+					ensure!(
+						allowance >= T::NativeBalance::minimum_balance(),
+						Error::<T>::AllowanceTooLow
+					);
+					let existing = SponsorshipOffers::<T>::get(&beneficiary, &sponsor);
+					if let Some(existing) = &existing {
+						Self::release_allowance(existing)?;
+					}
+					Self::hold_allowance(&sponsorship)?;
+					if existing.is_none() {
+						frame_system::Pallet::<T>::inc_sufficients(&beneficiary);
+					}
+					SponsorshipOffers::<T>::insert(&beneficiary, &sponsor, sponsorship);
+
+					Self::deposit_event(Event::SponsorshipOffered {
+						sponsor,
+						beneficiary,
+						allowance,
+						expiry,
+					});
+				},
+			}
+			Ok(())
+		}
+
+		/// Remove a beneficiary's sponsorship, releasing what is left of its allowance. Either
+		/// the sponsor or the beneficiary can remove it, including once it has expired.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn remove_sponsorship(
+			origin: OriginFor<T>,
+			beneficiary: T::AccountId,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			let sponsorship =
+				Sponsorships::<T>::get(&beneficiary).ok_or(Error::<T>::SponsorshipNotFound)?;
+			ensure!(
+				who == sponsorship.sponsor || who == beneficiary,
+				Error::<T>::NotSponsorOrBeneficiary
+			);
 
-			// Re-dispatch some call on behalf of the caller.
-			let res = call.dispatch(RawOrigin::Signed(who).into());
+			Self::release_allowance(&sponsorship)?;
+			Sponsorships::<T>::remove(&beneficiary);
+			frame_system::Pallet::<T>::dec_sufficients(&beneficiary);
 
-			// Turn the result from the `dispatch` into our expected `DispatchResult` type.
-			res.map(|_| ()).map_err(|e| e.error)
+			Self::deposit_event(Event::SponsorshipRemoved {
+				sponsor: sponsorship.sponsor,
+				beneficiary,
+			});
+			Ok(())
+		}
+
+		/// Accept a sponsor's offer to pay the signer's fees, replacing the signer's current
+		/// sponsorship, if any. The call is free, and `CheckFreeTxQuota` keeps it out of the
+		/// transaction pool unless the offer exists, so accounts without funds can accept offers.
+		#[pallet::call_index(4)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(4, 4), DispatchClass::Normal, Pays::No))]
+		pub fn accept_sponsorship(origin: OriginFor<T>, sponsor: T::AccountId) -> DispatchResult {
+			let beneficiary = ensure_signed(origin)?;
+			let offer = SponsorshipOffers::<T>::take(&beneficiary, &sponsor)
+				.ok_or(Error::<T>::OfferNotFound)?;
+
+			// The offer's reference keeps the account alive in place of the replaced one's.
+			if let Some(existing) = Sponsorships::<T>::get(&beneficiary) {
+				Self::release_allowance(&existing)?;
+				frame_system::Pallet::<T>::dec_sufficients(&beneficiary);
+			}
+			Sponsorships::<T>::insert(&beneficiary, &offer);
+
+			Self::deposit_event(Event::SponsorshipCreated {
+				sponsor,
+				beneficiary,
+				allowance: offer.allowance,
+				expiry: offer.expiry,
+			});
+			Ok(())
+		}
+
+		/// Remove a sponsorship offer, releasing its allowance. Either the sponsor or the
+		/// beneficiary can remove it.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 3))]
+		pub fn remove_offer(
+			origin: OriginFor<T>,
+			beneficiary: T::AccountId,
+			sponsor: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(who == sponsor || who == beneficiary, Error::<T>::NotSponsorOrBeneficiary);
+			let offer = SponsorshipOffers::<T>::take(&beneficiary, &sponsor)
+				.ok_or(Error::<T>::OfferNotFound)?;
+
+			Self::release_allowance(&offer)?;
+			frame_system::Pallet::<T>::dec_sufficients(&beneficiary);
+
+			Self::deposit_event(Event::SponsorshipOfferRemoved { sponsor, beneficiary });
+			Ok(())
 		}
 	}
 }
@@ -121,4 +391,166 @@ impl<T: Config> Pallet<T> {
 	pub fn call_weight(call: <T as Config>::RuntimeCall) -> Weight {
 		call.get_dispatch_info().weight
 	}
+
+	/// The era of the current block. Free transaction quotas are reset in each era.
+	pub fn current_era() -> BlockNumberFor<T> {
+		let now = frame_system::Pallet::<T>::block_number();
+		now.checked_div(&T::EraLength::get()).unwrap_or_else(Zero::zero)
+	}
+
+	/// Return the number of free transactions an account has left in the current era. Usage
+	/// from earlier eras is ignored, so quotas reset without any storage being cleared.
+	pub fn free_tx_remaining(who: &T::AccountId) -> u32 {
+		let used = match FreeTxUsage::<T>::get(who) {
+			Some((era, used)) if era == Self::current_era() => used,
+			_ => 0,
+		};
+		T::FreeTxPerEra::get().saturating_sub(used)
+	}
+
+	/// Return whether the fee of a call signed by an account is waived.
+	pub fn is_fee_waived(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+	) -> bool {
+		T::FreeCalls::contains(call) &&
+			T::FreeTxAccounts::contains(who) &&
+			Self::free_tx_remaining(who) > 0
+	}
+
+	/// Use up one of an account's free transactions in the current era.
+	pub fn use_free_tx(who: &T::AccountId) {
+		let remaining = Self::free_tx_remaining(who).saturating_sub(1);
+		let used = T::FreeTxPerEra::get().saturating_sub(remaining);
+		FreeTxUsage::<T>::insert(who, (Self::current_era(), used));
+
+		Self::deposit_event(Event::FeeWaived { who: who.clone(), remaining });
+	}
+
+	/// Return the pallet and call index of a call, the first two bytes of its encoding.
+	pub fn call_index(call: &<T as frame_system::Config>::RuntimeCall) -> [u8; 2] {
+		call.using_encoded(|mut bytes| <[u8; 2]>::decode(&mut bytes).unwrap_or_default())
+	}
+
+	/// Return the account that pays the fee of a call signed by an account: its sponsor, if its
+	/// sponsorship covers the call and has enough allowance left for the fee, or else itself.
+	pub fn fee_payer(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+		fee: BalanceOf<T>,
+	) -> T::AccountId {
+		match Sponsorships::<T>::get(who) {
+			Some(sponsorship)
+				if frame_system::Pallet::<T>::block_number() < sponsorship.expiry &&
+					fee <= sponsorship.allowance &&
+					(sponsorship.calls.is_empty() ||
+						sponsorship.calls.contains(&Self::call_index(call))) =>
+				sponsorship.sponsor,
+			_ => who.clone(),
+		}
+	}
+
+	/// Hold a sponsorship's allowance from its sponsor.
+	fn hold_allowance(sponsorship: &Sponsorship<T>) -> DispatchResult {
+		if !sponsorship.allowance.is_zero() {
+			T::NativeBalance::hold(
+				&HoldReason::SponsorshipAllowance.into(),
+				&sponsorship.sponsor,
+				sponsorship.allowance,
+			)?;
+		}
+		Ok(())
+	}
+
+	/// Release what is left of a sponsorship's allowance to its sponsor.
+	fn release_allowance(sponsorship: &Sponsorship<T>) -> DispatchResult {
+		if !sponsorship.allowance.is_zero() {
+			T::NativeBalance::release(
+				&HoldReason::SponsorshipAllowance.into(),
+				&sponsorship.sponsor,
+				sponsorship.allowance,
+				Precision::BestEffort,
+			)?;
+		}
+		Ok(())
+	}
+
+	/// Take a fee out of a beneficiary's sponsorship allowance, releasing it from the hold on
+	/// the sponsor's funds so the fee can be withdrawn from them. Fails if the sponsor no
+	/// longer has the fee on hold.
+	pub fn use_allowance(beneficiary: &T::AccountId, fee: BalanceOf<T>) -> DispatchResult {
+		Sponsorships::<T>::try_mutate(beneficiary, |maybe_sponsorship| -> DispatchResult {
+			let sponsorship = maybe_sponsorship.as_mut().ok_or(Error::<T>::SponsorshipNotFound)?;
+			T::NativeBalance::release(
+				&HoldReason::SponsorshipAllowance.into(),
+				&sponsorship.sponsor,
+				fee,
+				Precision::Exact,
+			)?;
+			sponsorship.allowance = sponsorship.allowance.saturating_sub(fee);
+			Ok(())
+		})
+	}
+
+	/// Put an amount back into a beneficiary's sponsorship allowance, holding it from the
+	/// sponsor again. Nothing is put back if the sponsorship now belongs to another sponsor, or
+	/// if the amount can't be held.
+	pub fn restore_allowance(
+		sponsor: &T::AccountId,
+		beneficiary: &T::AccountId,
+		amount: BalanceOf<T>,
+	) {
+		if amount.is_zero() {
+			return
+		}
+		Sponsorships::<T>::mutate(beneficiary, |maybe_sponsorship| {
+			if let Some(sponsorship) = maybe_sponsorship {
+				if sponsorship.sponsor == *sponsor &&
+					T::NativeBalance::hold(
+						&HoldReason::SponsorshipAllowance.into(),
+						sponsor,
+						amount,
+					)
+					.is_ok()
+				{
+					sponsorship.allowance = sponsorship.allowance.saturating_add(amount);
+				}
+			}
+		});
+	}
+
+	/// Return the part of a sponsored fee that wasn't used, which was refunded to the sponsor,
+	/// to a beneficiary's allowance, and record the fee the sponsor paid.
+	pub fn refund_allowance(
+		sponsor: &T::AccountId,
+		beneficiary: &T::AccountId,
+		withdrawn: BalanceOf<T>,
+		fee: BalanceOf<T>,
+	) {
+		Self::restore_allowance(sponsor, beneficiary, withdrawn.saturating_sub(fee));
+
+		Self::deposit_event(Event::FeeSponsored {
+			sponsor: sponsor.clone(),
+			beneficiary: beneficiary.clone(),
+			fee,
+		});
+	}
+
+	/// The weight `redispatch` adds to the weight of the call it dispatches.
+	pub fn redispatch_overhead() -> Weight {
+		T::DbWeight::get().reads_writes(1, 2)
+	}
+
+	/// Dispatch a call from an account's signed origin. The nesting depth is shared with the
+	/// `redispatch` calls of other pallets through `redispatch_primitives`. The actual weight
+	/// returned includes `redispatch_overhead`.
+	pub fn do_redispatch(
+		who: T::AccountId,
+		call: <T as Config>::RuntimeCall,
+	) -> DispatchResultWithPostInfo {
+		let overhead = Self::redispatch_overhead();
+		ensure!(T::CallFilter::contains(&call), Error::<T>::CallFiltered.with_weight(overhead));
+		redispatch_primitives::dispatch_as_signed(who, call, T::MaxNestingDepth::get(), overhead)
+			.map_err(|_| Error::<T>::TooDeeplyNested.with_weight(overhead))?
+	}
 }
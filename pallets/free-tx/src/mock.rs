@@ -1,5 +1,8 @@
 use crate as pallet_voting;
-use frame_support::traits::{ConstU128, ConstU16, ConstU32, ConstU64};
+use frame_support::{
+	traits::{ConstU128, ConstU16, ConstU32, ConstU64, ConstU8, Contains},
+	weights::IdentityFee,
+};
 use sp_core::H256;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
@@ -15,6 +18,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system,
 		Balances: pallet_balances,
+		TransactionPayment: pallet_transaction_payment,
 		Voting: pallet_voting,
 	}
 );
@@ -55,16 +59,57 @@ impl pallet_balances::Config for Test {
 	type MaxLocks = ConstU32<10>;
 	type MaxReserves = ();
 	type ReserveIdentifier = [u8; 8];
-	type RuntimeHoldReason = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type FreezeIdentifier = ();
 	type MaxHolds = ConstU32<10>;
 	type MaxFreezes = ConstU32<10>;
 }
 
+impl pallet_transaction_payment::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type OnChargeTransaction = pallet_voting::SponsoredCurrencyAdapter<Balances, ()>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = IdentityFee<Balance>;
+	type LengthToFee = IdentityFee<Balance>;
+	type FeeMultiplierUpdate = ();
+}
+
 impl pallet_voting::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type NativeBalance = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type RuntimeCall = RuntimeCall;
+	type CallFilter = NoBalancesCalls;
+	type MaxNestingDepth = ConstU32<2>;
+	type FreeCalls = RemarkCalls;
+	type FreeTxAccounts = Voters;
+	type FreeTxPerEra = ConstU32<2>;
+	type EraLength = ConstU64<10>;
+	type MaxSponsoredCalls = ConstU32<2>;
+}
+
+// Forbid redispatching calls to the balances pallet.
+pub struct NoBalancesCalls;
+impl Contains<RuntimeCall> for NoBalancesCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		!matches!(call, RuntimeCall::Balances(_))
+	}
+}
+
+// Waive the fees of remarks.
+pub struct RemarkCalls;
+impl Contains<RuntimeCall> for RemarkCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(call, RuntimeCall::System(frame_system::Call::remark { .. }))
+	}
+}
+
+// Accounts 1 and 2 can have their fees waived.
+pub struct Voters;
+impl Contains<u64> for Voters {
+	fn contains(who: &u64) -> bool {
+		matches!(who, 1 | 2)
+	}
 }
 
 // Build genesis storage according to the mock runtime.
@@ -0,0 +1,99 @@
+//! A transaction payment adapter that charges a beneficiary's fees to its sponsor.
+
+use crate::{BalanceOf, Config, Pallet};
+use frame_support::traits::{Currency, Imbalance, OnUnbalanced};
+use pallet_transaction_payment::{CurrencyAdapter, NegativeImbalanceOf, OnChargeTransaction};
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, UniqueSaturatedFrom, UniqueSaturatedInto},
+	transaction_validity::TransactionValidityError,
+};
+use sp_std::marker::PhantomData;
+
+type BalanceOfCurrency<C, T> = <C as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type CallOf<T> = <T as frame_system::Config>::RuntimeCall;
+
+// Convert a fee to the pallet's balance type, which is the currency's balance type in practice.
+fn to_balance<T: Config, C: Currency<T::AccountId>>(
+	amount: BalanceOfCurrency<C, T>,
+) -> BalanceOf<T> {
+	let amount: u128 = amount.unique_saturated_into();
+	BalanceOf::<T>::unique_saturated_from(amount)
+}
+
+/// Works like `CurrencyAdapter`, but withdraws the fee from the signer's sponsor when it has a
+/// sponsorship covering the call with enough allowance left. The fee is released from the
+/// allowance held from the sponsor, and the signer pays it instead if the sponsor can't. The
+/// part of the fee refunded after dispatch goes back to the sponsor and to the sponsorship's
+/// allowance.
+pub struct SponsoredCurrencyAdapter<C, OU>(PhantomData<(C, OU)>);
+
+impl<T, C, OU> OnChargeTransaction<T> for SponsoredCurrencyAdapter<C, OU>
+where
+	T: Config,
+	C: Currency<T::AccountId>,
+	C::PositiveImbalance: Imbalance<BalanceOfCurrency<C, T>, Opposite = C::NegativeImbalance>,
+	C::NegativeImbalance: Imbalance<BalanceOfCurrency<C, T>, Opposite = C::PositiveImbalance>,
+	OU: OnUnbalanced<NegativeImbalanceOf<C, T>>,
+{
+	type Balance = BalanceOfCurrency<C, T>;
+	/// The account the fee was withdrawn from, and the withdrawn fee.
+	type LiquidityInfo = Option<(T::AccountId, NegativeImbalanceOf<C, T>)>;
+
+	fn withdraw_fee(
+		who: &T::AccountId,
+		call: &CallOf<T>,
+		info: &DispatchInfoOf<CallOf<T>>,
+		fee: Self::Balance,
+		tip: Self::Balance,
+	) -> Result<Self::LiquidityInfo, TransactionValidityError> {
+		let payer = Pallet::<T>::fee_payer(who, call, to_balance::<T, C>(fee));
+		if payer != *who && Pallet::<T>::use_allowance(who, to_balance::<T, C>(fee)).is_ok() {
+			match <CurrencyAdapter<C, OU> as OnChargeTransaction<T>>::withdraw_fee(
+				&payer, call, info, fee, tip,
+			) {
+				Ok(withdrawn) => return Ok(withdrawn.map(|imbalance| (payer, imbalance))),
+				Err(_) => Pallet::<T>::restore_allowance(&payer, who, to_balance::<T, C>(fee)),
+			}
+		}
+
+		let withdrawn = <CurrencyAdapter<C, OU> as OnChargeTransaction<T>>::withdraw_fee(
+			who, call, info, fee, tip,
+		)?;
+		Ok(withdrawn.map(|imbalance| (who.clone(), imbalance)))
+	}
+
+	fn correct_and_deposit_fee(
+		who: &T::AccountId,
+		dispatch_info: &DispatchInfoOf<CallOf<T>>,
+		post_info: &PostDispatchInfoOf<CallOf<T>>,
+		corrected_fee: Self::Balance,
+		tip: Self::Balance,
+		already_withdrawn: Self::LiquidityInfo,
+	) -> Result<(), TransactionValidityError> {
+		let (payer, withdrawn) = match already_withdrawn {
+			Some((payer, imbalance)) => (payer, Some(imbalance)),
+			None => (who.clone(), None),
+		};
+
+		let amount = withdrawn.as_ref().map_or_else(Default::default, |i| i.peek());
+		<CurrencyAdapter<C, OU> as OnChargeTransaction<T>>::correct_and_deposit_fee(
+			&payer,
+			dispatch_info,
+			post_info,
+			corrected_fee,
+			tip,
+			withdrawn,
+		)?;
+
+		// The refund is back in the sponsor's free balance, so it can be held again.
+		if payer != *who {
+			Pallet::<T>::refund_allowance(
+				&payer,
+				who,
+				to_balance::<T, C>(amount),
+				to_balance::<T, C>(corrected_fee),
+			);
+		}
+		Ok(())
+	}
+}
@@ -1,5 +1,32 @@
-use crate::{mock::*, Error, Event};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*, ChargeTransactionPaymentOrFree, CheckFreeTxQuota, Error, Event, HoldReason,
+	SponsorshipOffers, Sponsorships, NO_SPONSORSHIP_OFFER, QUOTA_EXCEEDED,
+};
+use frame_support::{
+	assert_err_ignore_postinfo, assert_noop, assert_ok,
+	dispatch::{DispatchInfo, GetDispatchInfo, Pays, PostDispatchInfo},
+	traits::{
+		fungible::{InspectHold, Mutate, MutateHold},
+		tokens::{Fortitude, Precision},
+	},
+	weights::Weight,
+};
+use frame_system::CheckNonce;
+use sp_runtime::{traits::SignedExtension, transaction_validity::InvalidTransaction, TokenError};
+
+fn remark_call() -> RuntimeCall {
+	RuntimeCall::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn dispatch_info() -> DispatchInfo {
+	DispatchInfo { weight: Weight::from_parts(100, 0), ..Default::default() }
+}
+
+// Offer `beneficiary` a sponsorship from `sponsor` covering every call, and accept it.
+fn sponsor_and_accept(sponsor: u64, beneficiary: u64, allowance: u128) {
+	assert_ok!(Voting::sponsor(RuntimeOrigin::signed(sponsor), beneficiary, allowance, 10, vec![]));
+	assert_ok!(Voting::accept_sponsorship(RuntimeOrigin::signed(beneficiary), sponsor));
+}
 
 #[test]
 fn it_works() {
@@ -14,3 +41,433 @@ fn it_works() {
 		assert_noop!(Voting::free_tx(RuntimeOrigin::signed(1), false), Error::<Test>::TxFailed);
 	});
 }
+
+#[test]
+fn redispatch_keeps_post_dispatch_info() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// A redispatched free transaction stays free.
+		let call = Box::new(RuntimeCall::Voting(crate::Call::free_tx { success: true }));
+		let post_info = Voting::redispatch(RuntimeOrigin::signed(1), call).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		System::assert_last_event(Event::TxSuccess.into());
+		assert_eq!(redispatch_primitives::depth(), 0);
+	});
+}
+
+#[test]
+fn redispatch_checks_call_filter() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::set_balance(&1, 100));
+		let transfer =
+			Box::new(RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive {
+				dest: 2,
+				value: 10,
+			}));
+
+		// The mock forbids balances calls, and only the overhead is charged for trying.
+		let err = Voting::redispatch(RuntimeOrigin::signed(1), transfer.clone()).unwrap_err();
+		assert_eq!(err.error, Error::<Test>::CallFiltered.into());
+		assert_eq!(err.post_info.actual_weight, Some(Voting::redispatch_overhead()));
+
+		// Nesting the call doesn't get around the filter.
+		let nested = Box::new(RuntimeCall::Voting(crate::Call::redispatch { call: transfer }));
+		assert_err_ignore_postinfo!(
+			Voting::redispatch(RuntimeOrigin::signed(1), nested),
+			Error::<Test>::CallFiltered
+		);
+		assert_eq!(Balances::free_balance(2), 0);
+	});
+}
+
+#[test]
+fn redispatch_limits_nesting_depth() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let nest = |call: Box<RuntimeCall>| {
+			Box::new(RuntimeCall::Voting(crate::Call::redispatch { call }))
+		};
+		let call = Box::new(RuntimeCall::Voting(crate::Call::free_tx { success: true }));
+
+		// `MaxNestingDepth` is two in the mock.
+		assert_ok!(Voting::redispatch(RuntimeOrigin::signed(1), nest(call.clone())));
+		assert_err_ignore_postinfo!(
+			Voting::redispatch(RuntimeOrigin::signed(1), nest(nest(call))),
+			Error::<Test>::TooDeeplyNested
+		);
+		assert_eq!(redispatch_primitives::depth(), 0);
+	});
+}
+
+#[test]
+fn fees_are_waived_for_free_calls_of_free_accounts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let ext = ChargeTransactionPaymentOrFree::<Test>::from(0);
+		let (remark, info) = (remark_call(), dispatch_info());
+
+		// Account 1 can remark without any funds.
+		assert_ok!(ext.validate(&1, &remark, &info, 10));
+		let pre = ext.clone().pre_dispatch(&1, &remark, &info, 10).unwrap();
+		assert!(pre.is_none());
+		assert_ok!(ChargeTransactionPaymentOrFree::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&PostDispatchInfo::default(),
+			10,
+			&Ok(())
+		));
+		System::assert_last_event(Event::FeeWaived { who: 1, remaining: 1 }.into());
+
+		// Account 3 can't have its fees waived, so it needs funds to pay for a remark.
+		assert!(ext.validate(&3, &remark, &info, 10).is_err());
+		assert_ok!(Balances::set_balance(&3, 1_000));
+		assert!(ext.clone().pre_dispatch(&3, &remark, &info, 10).unwrap().is_some());
+		assert!(Balances::free_balance(3) < 1_000);
+
+		// Account 2 pays for calls that aren't free.
+		assert_ok!(Balances::set_balance(&2, 1_000));
+		let call = RuntimeCall::Voting(crate::Call::free_tx { success: true });
+		assert!(ext.pre_dispatch(&2, &call, &info, 10).unwrap().is_some());
+		assert!(Balances::free_balance(2) < 1_000);
+	});
+}
+
+#[test]
+fn free_transactions_are_limited_per_era() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000));
+		let ext = ChargeTransactionPaymentOrFree::<Test>::from(0);
+		let (remark, info) = (remark_call(), dispatch_info());
+
+		// An account has two free transactions per era in the mock.
+		for remaining in [1, 0] {
+			assert!(ext.clone().pre_dispatch(&1, &remark, &info, 10).unwrap().is_none());
+			System::assert_last_event(Event::FeeWaived { who: 1, remaining }.into());
+		}
+		assert_eq!(Voting::free_tx_remaining(&1), 0);
+		assert_eq!(Balances::free_balance(1), 1_000);
+
+		// Further transactions pay their fees as usual.
+		assert!(ext.clone().pre_dispatch(&1, &remark, &info, 10).unwrap().is_some());
+		assert!(Balances::free_balance(1) < 1_000);
+
+		// The quota is reset in the next era.
+		System::set_block_number(10);
+		assert_eq!(Voting::free_tx_remaining(&1), 2);
+		assert!(ext.pre_dispatch(&1, &remark, &info, 10).unwrap().is_none());
+	});
+}
+
+#[test]
+fn free_tx_is_limited_per_era() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		// An account has two free transactions per era in the mock.
+		assert_ok!(Voting::free_tx(RuntimeOrigin::signed(1), true));
+		assert_ok!(Voting::free_tx(RuntimeOrigin::signed(1), true));
+		assert_noop!(Voting::free_tx(RuntimeOrigin::signed(1), true), Error::<Test>::QuotaExceeded);
+		assert_ok!(Voting::free_tx(RuntimeOrigin::signed(2), true));
+
+		// Failed calls don't use up the quota.
+		assert_noop!(Voting::free_tx(RuntimeOrigin::signed(2), false), Error::<Test>::TxFailed);
+		assert_eq!(Voting::free_tx_remaining(&2), 1);
+
+		// The quota is reset in the next era.
+		System::set_block_number(10);
+		assert_ok!(Voting::free_tx(RuntimeOrigin::signed(1), true));
+	});
+}
+
+#[test]
+fn over_quota_free_tx_is_rejected_by_the_pool() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let ext = CheckFreeTxQuota::<Test>::new();
+		let call = RuntimeCall::Voting(crate::Call::free_tx { success: true });
+		let info = dispatch_info();
+		assert_ok!(ext.validate(&1, &call, &info, 10));
+
+		// Waived fees use up the same quota.
+		Voting::use_free_tx(&1);
+		Voting::use_free_tx(&1);
+		let exceeded = InvalidTransaction::Custom(QUOTA_EXCEEDED).into();
+		assert_eq!(ext.validate(&1, &call, &info, 10), Err(exceeded));
+		assert_eq!(ext.clone().pre_dispatch(&1, &call, &info, 10), Err(exceeded));
+
+		// Other calls and accounts are unaffected.
+		assert_ok!(ext.validate(&1, &remark_call(), &info, 10));
+		assert_ok!(ext.validate(&2, &call, &info, 10));
+	});
+}
+
+#[test]
+fn sponsor_creates_and_removes_sponsorships() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000));
+		let remark = Voting::call_index(&remark_call());
+
+		assert_noop!(
+			Voting::sponsor(RuntimeOrigin::signed(1), 3, 100, 1, vec![]),
+			Error::<Test>::InvalidExpiry
+		);
+		assert_noop!(
+			Voting::sponsor(RuntimeOrigin::signed(1), 3, 100, 10, vec![remark; 3]),
+			Error::<Test>::TooManySponsoredCalls
+		);
+		assert_noop!(
+			Voting::sponsor(RuntimeOrigin::signed(1), 3, 0, 10, vec![]),
+			Error::<Test>::AllowanceTooLow
+		);
+
+		// The offer holds the allowance, but only covers fees once account 3 accepts it.
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(1), 3, 100, 10, vec![remark]));
+		System::assert_last_event(
+			Event::SponsorshipOffered { sponsor: 1, beneficiary: 3, allowance: 100, expiry: 10 }
+				.into(),
+		);
+		assert!(Sponsorships::<Test>::get(3).is_none());
+		assert_eq!(Balances::balance_on_hold(&HoldReason::SponsorshipAllowance.into(), &1), 100);
+		assert_noop!(
+			Voting::accept_sponsorship(RuntimeOrigin::signed(3), 2),
+			Error::<Test>::OfferNotFound
+		);
+		assert_ok!(Voting::accept_sponsorship(RuntimeOrigin::signed(3), 1));
+		System::assert_last_event(
+			Event::SponsorshipCreated { sponsor: 1, beneficiary: 3, allowance: 100, expiry: 10 }
+				.into(),
+		);
+		assert_eq!(Sponsorships::<Test>::get(3).unwrap().calls.into_inner(), vec![remark]);
+		assert!(SponsorshipOffers::<Test>::get(3, 1).is_none());
+		assert_eq!(Balances::balance_on_hold(&HoldReason::SponsorshipAllowance.into(), &1), 100);
+
+		// The sponsor can update its sponsorship without another acceptance, holding the new
+		// allowance instead.
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(1), 3, 200, 10, vec![remark]));
+		System::assert_last_event(
+			Event::SponsorshipCreated { sponsor: 1, beneficiary: 3, allowance: 200, expiry: 10 }
+				.into(),
+		);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::SponsorshipAllowance.into(), &1), 200);
+
+		// Only the sponsor or the beneficiary can remove a sponsorship.
+		assert_noop!(
+			Voting::remove_sponsorship(RuntimeOrigin::signed(2), 3),
+			Error::<Test>::NotSponsorOrBeneficiary
+		);
+		assert_ok!(Voting::remove_sponsorship(RuntimeOrigin::signed(3), 3));
+		System::assert_last_event(Event::SponsorshipRemoved { sponsor: 1, beneficiary: 3 }.into());
+		assert_eq!(Balances::balance_on_hold(&HoldReason::SponsorshipAllowance.into(), &1), 0);
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_noop!(
+			Voting::remove_sponsorship(RuntimeOrigin::signed(1), 3),
+			Error::<Test>::SponsorshipNotFound
+		);
+	});
+}
+
+#[test]
+fn sponsorships_cannot_be_replaced_by_other_accounts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000));
+		assert_ok!(Balances::set_balance(&2, 1_000));
+		sponsor_and_accept(1, 3, 100);
+		let held = |who| Balances::balance_on_hold(&HoldReason::SponsorshipAllowance.into(), &who);
+
+		// Account 2 can only offer account 3 a sponsorship, which doesn't replace account 1's.
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(2), 3, 1, 10, vec![]));
+		assert_eq!(Sponsorships::<Test>::get(3).unwrap().sponsor, 1);
+		assert_eq!(Sponsorships::<Test>::get(3).unwrap().allowance, 100);
+		assert_eq!(held(2), 1);
+
+		// Accepting the offer replaces the sponsorship, releasing its allowance.
+		assert_ok!(Voting::accept_sponsorship(RuntimeOrigin::signed(3), 2));
+		assert_eq!(Sponsorships::<Test>::get(3).unwrap().sponsor, 2);
+		assert_eq!(held(1), 0);
+		assert_eq!(held(2), 1);
+
+		// Only the sponsor or the beneficiary can remove an offer, which releases its allowance.
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(1), 3, 100, 10, vec![]));
+		assert_eq!(held(1), 100);
+		assert_noop!(
+			Voting::remove_offer(RuntimeOrigin::signed(2), 3, 1),
+			Error::<Test>::NotSponsorOrBeneficiary
+		);
+		assert_ok!(Voting::remove_offer(RuntimeOrigin::signed(3), 3, 1));
+		System::assert_last_event(
+			Event::SponsorshipOfferRemoved { sponsor: 1, beneficiary: 3 }.into(),
+		);
+		assert_eq!(held(1), 0);
+		assert_noop!(
+			Voting::remove_offer(RuntimeOrigin::signed(1), 3, 1),
+			Error::<Test>::OfferNotFound
+		);
+	});
+}
+
+#[test]
+fn sponsors_must_hold_the_allowance() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 50));
+
+		assert_noop!(
+			Voting::sponsor(RuntimeOrigin::signed(1), 3, 100, 10, vec![]),
+			TokenError::FundsUnavailable
+		);
+		assert!(SponsorshipOffers::<Test>::get(3, 1).is_none());
+	});
+}
+
+#[test]
+fn sponsors_pay_beneficiaries_fees() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000_000));
+		sponsor_and_accept(1, 3, 100_000);
+		let held = || Balances::balance_on_hold(&HoldReason::SponsorshipAllowance.into(), &1);
+
+		// Account 3 has no funds, but its sponsor pays the fee out of the held allowance.
+		let ext = ChargeTransactionPaymentOrFree::<Test>::from(0);
+		let call = RuntimeCall::Voting(crate::Call::free_tx { success: true });
+		let info = DispatchInfo { weight: Weight::from_parts(1_000, 0), ..Default::default() };
+		let pre = ext.pre_dispatch(&3, &call, &info, 10).unwrap();
+		let withdrawn = 100_000 - held();
+		assert!(withdrawn > 0);
+		assert_eq!(Balances::free_balance(1), 900_000);
+		assert_eq!(Sponsorships::<Test>::get(3).unwrap().allowance, 100_000 - withdrawn);
+
+		// The fee for unused weight goes back to the sponsor and to the allowance.
+		let post_info = PostDispatchInfo {
+			actual_weight: Some(Weight::from_parts(400, 0)),
+			pays_fee: Pays::Yes,
+		};
+		assert_ok!(ChargeTransactionPaymentOrFree::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&post_info,
+			10,
+			&Ok(())
+		));
+		let fee = 100_000 - held();
+		assert_eq!(fee, withdrawn - 600);
+		assert_eq!(Balances::free_balance(1), 900_000);
+		assert_eq!(Sponsorships::<Test>::get(3).unwrap().allowance, 100_000 - fee);
+		System::assert_has_event(Event::FeeSponsored { sponsor: 1, beneficiary: 3, fee }.into());
+		assert_eq!(Balances::free_balance(3), 0);
+	});
+}
+
+#[test]
+fn signers_pay_fees_their_sponsor_cannot_pay() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000_000));
+		assert_ok!(Balances::set_balance(&3, 1_000_000));
+		sponsor_and_accept(1, 3, 100_000);
+
+		// The allowance held from the sponsor is slashed, so it no longer covers the fee.
+		assert_ok!(Balances::burn_held(
+			&HoldReason::SponsorshipAllowance.into(),
+			&1,
+			100_000,
+			Precision::Exact,
+			Fortitude::Force
+		));
+
+		// Account 3 pays its own fee instead of the transaction being rejected.
+		let ext = ChargeTransactionPaymentOrFree::<Test>::from(0);
+		assert_ok!(ext.pre_dispatch(&3, &remark_call(), &dispatch_info(), 10));
+		assert!(Balances::free_balance(3) < 1_000_000);
+		assert_eq!(Balances::free_balance(1), 900_000);
+		assert_eq!(Sponsorships::<Test>::get(3).unwrap().allowance, 100_000);
+	});
+}
+
+#[test]
+fn sponsorships_only_cover_their_calls_allowance_and_period() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000_000));
+		assert_ok!(Balances::set_balance(&3, 1_000_000));
+		let remark = Voting::call_index(&remark_call());
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(1), 3, 100_000, 10, vec![remark]));
+		assert_ok!(Voting::accept_sponsorship(RuntimeOrigin::signed(3), 1));
+		let held = || Balances::balance_on_hold(&HoldReason::SponsorshipAllowance.into(), &1);
+		let ext = ChargeTransactionPaymentOrFree::<Test>::from(0);
+		let info = dispatch_info();
+
+		// Calls other than remarks aren't covered, so account 3 pays for them.
+		let call = RuntimeCall::Voting(crate::Call::free_tx { success: true });
+		assert_ok!(ext.clone().pre_dispatch(&3, &call, &info, 10));
+		assert_eq!(held(), 100_000);
+		let balance = Balances::free_balance(3);
+		assert!(balance < 1_000_000);
+
+		// Remarks are paid for by the sponsor.
+		assert_ok!(ext.clone().pre_dispatch(&3, &remark_call(), &info, 10));
+		assert_eq!(Balances::free_balance(3), balance);
+		assert!(held() < 100_000);
+
+		// An allowance smaller than the fee doesn't cover it.
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(1), 3, 1, 10, vec![]));
+		assert_ok!(ext.clone().pre_dispatch(&3, &remark_call(), &info, 10));
+		assert_eq!(held(), 1);
+		let balance = Balances::free_balance(3);
+
+		// Nor does an expired sponsorship.
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(1), 3, 100_000, 10, vec![]));
+		System::set_block_number(10);
+		assert_ok!(ext.pre_dispatch(&3, &remark_call(), &info, 10));
+		assert_eq!(held(), 100_000);
+		assert!(Balances::free_balance(3) < balance);
+	});
+}
+
+#[test]
+fn unfunded_accounts_accept_sponsorships_for_free() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(Balances::set_balance(&1, 1_000_000));
+		let accept = RuntimeCall::Voting(crate::Call::accept_sponsorship { sponsor: 1 });
+		let info = accept.get_dispatch_info();
+		assert_eq!(info.pays_fee, Pays::No);
+		let quota = CheckFreeTxQuota::<Test>::new();
+		let payment = ChargeTransactionPaymentOrFree::<Test>::from(0);
+
+		// Account 3 has never held funds, so it can't sign transactions, and it has no offer to
+		// accept.
+		assert!(!System::account_exists(&3));
+		assert_eq!(
+			CheckNonce::<Test>::from(0).pre_dispatch(&3, &accept, &info, 10),
+			Err(InvalidTransaction::Payment.into())
+		);
+		assert_eq!(
+			quota.validate(&3, &accept, &info, 10),
+			Err(InvalidTransaction::Custom(NO_SPONSORSHIP_OFFER).into())
+		);
+
+		// An offer keeps account 3's account alive, so it can accept the offer without paying.
+		assert_ok!(Voting::sponsor(RuntimeOrigin::signed(1), 3, 100_000, 10, vec![]));
+		assert_eq!(System::sufficients(&3), 1);
+		assert_ok!(CheckNonce::<Test>::from(0).pre_dispatch(&3, &accept, &info, 10));
+		assert_ok!(quota.validate(&3, &accept, &info, 10));
+		assert_ok!(payment.clone().pre_dispatch(&3, &accept, &info, 10));
+		assert_ok!(Voting::accept_sponsorship(RuntimeOrigin::signed(3), 1));
+		assert_eq!(Balances::free_balance(3), 0);
+
+		// Its sponsor then pays its fees.
+		assert_ok!(payment.pre_dispatch(&3, &remark_call(), &dispatch_info(), 10));
+		assert!(Sponsorships::<Test>::get(3).unwrap().allowance < 100_000);
+		assert_eq!(Balances::free_balance(3), 0);
+
+		// Removing the sponsorship drops the reference that kept account 3 alive.
+		assert_ok!(Voting::remove_sponsorship(RuntimeOrigin::signed(3), 3));
+		assert!(!System::account_exists(&3));
+	});
+}
@@ -1,35 +1,209 @@
-//! Benchmarking setup for pallet-voting
+//! Benchmarking setup for pallet-multisig
 #![cfg(feature = "runtime-benchmarks")]
 use super::*;
 
 #[allow(unused)]
-use crate::Pallet as Voting;
+use crate::Pallet as Multisig;
 use frame_benchmarking::v2::*;
 use frame_system::RawOrigin;
+use sp_runtime::traits::Saturating;
+use sp_std::prelude::*;
 
-#[benchmarks]
+/// Fund `s` signatories and return them sorted with a weight of one each, with the whitelisted
+/// caller among them.
+fn setup_signatories<T: Config>(s: u32) -> Vec<(T::AccountId, u32)> {
+	let caller: T::AccountId = whitelisted_caller();
+	let mut signatories: Vec<T::AccountId> =
+		(1..s).map(|i| account("signatory", i, 0)).chain(Some(caller)).collect();
+	signatories.sort();
+	for who in signatories.iter() {
+		let balance = T::OperationDeposit::get().saturating_mul(10u32.into());
+		let _ = <T::NativeBalance as fungible::Mutate<T::AccountId>>::set_balance(who, balance);
+	}
+	signatories.into_iter().map(|who| (who, 1)).collect()
+}
+
+/// Create a voting power multisig, whose approvals freeze the approvers' balances. Its
+/// threshold is zero, so an operation can be executed whatever the runtime's voting power is.
+fn setup_multisig<T: Config>(signatories: &[(T::AccountId, u32)]) -> T::AccountId {
+	let signatories: Vec<_> = signatories.iter().map(|(who, _)| (who.clone(), 0)).collect();
+	let threshold = 0;
+	let weight_source = WeightSource::VotingPower;
+	let multisig = Multisig::<T>::multi_account_id(&signatories, threshold, weight_source);
+	let signatories = BoundedVec::truncate_from(signatories);
+	Multisigs::<T>::insert(&multisig, MultisigInfo { signatories, threshold, weight_source });
+	multisig
+}
+
+/// Have the first `s` signatories other than `depositor` approve an operation.
+fn approve_by<T: Config>(
+	signatories: &[(T::AccountId, u32)],
+	depositor: &T::AccountId,
+	multisig: &T::AccountId,
+	call_hash: CallHash,
+	s: u32,
+) -> Result<(), BenchmarkError> {
+	let when = Operations::<T>::get(multisig, call_hash)
+		.ok_or(BenchmarkError::Stop("operation not found"))?
+		.when;
+	for (who, _) in signatories.iter().filter(|(who, _)| who != depositor).take(s as usize) {
+		Multisig::<T>::approve(
+			RawOrigin::Signed(who.clone()).into(),
+			multisig.clone(),
+			call_hash,
+			when,
+		)?;
+	}
+	Ok(())
+}
+
+#[benchmarks(where <T as Config>::RuntimeCall: From<frame_system::Call<T>>)]
 mod benchmarks {
 	use super::*;
 
 	#[benchmark]
-	fn do_something() {
-		let value = 100u32.into();
+	fn create_multisig(s: Linear<2, { T::MaxSignatories::get() }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let signatories = setup_signatories::<T>(s);
+		let multisig = Multisig::<T>::multi_account_id(&signatories, s, WeightSource::Fixed);
+		#[extrinsic_call]
+		create_multisig(RawOrigin::Signed(caller), signatories, s, WeightSource::Fixed);
+
+		assert!(Multisigs::<T>::contains_key(&multisig));
+	}
+
+	#[benchmark]
+	fn propose() {
+		let caller: T::AccountId = whitelisted_caller();
+		let signatories = setup_signatories::<T>(T::MaxSignatories::get());
+		let multisig = setup_multisig::<T>(&signatories);
+		let call_hash = [1; 32];
+		#[extrinsic_call]
+		propose(RawOrigin::Signed(caller), multisig.clone(), call_hash);
+
+		assert!(Operations::<T>::contains_key(&multisig, call_hash));
+	}
+
+	#[benchmark]
+	fn approve() -> Result<(), BenchmarkError> {
 		let caller: T::AccountId = whitelisted_caller();
+		let signatories = setup_signatories::<T>(T::MaxSignatories::get());
+		let multisig = setup_multisig::<T>(&signatories);
+		let call_hash = [1; 32];
+
+		// Every other signatory has already approved.
+		let others: Vec<_> = signatories
+			.iter()
+			.map(|(who, _)| who.clone())
+			.filter(|who| *who != caller)
+			.collect();
+		Multisig::<T>::propose(
+			RawOrigin::Signed(others[0].clone()).into(),
+			multisig.clone(),
+			call_hash,
+		)?;
+		let when = Multisig::<T>::timepoint();
+		for who in others.iter().skip(1) {
+			Multisig::<T>::approve(
+				RawOrigin::Signed(who.clone()).into(),
+				multisig.clone(),
+				call_hash,
+				when,
+			)?;
+		}
+
+		#[extrinsic_call]
+		approve(RawOrigin::Signed(caller), multisig.clone(), call_hash, when);
+
+		let operation = Operations::<T>::get(&multisig, call_hash)
+			.ok_or(BenchmarkError::Stop("operation not found"))?;
+		assert_eq!(operation.approvals.len(), signatories.len());
+		Ok(())
+	}
+
+	#[benchmark]
+	fn cancel(s: Linear<1, { T::MaxSignatories::get() }>) -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let signatories = setup_signatories::<T>(T::MaxSignatories::get());
+		let multisig = setup_multisig::<T>(&signatories);
+		let call_hash = [1; 32];
+
+		// The operation has `s` approvals, including the depositor's.
+		Multisig::<T>::propose(
+			RawOrigin::Signed(caller.clone()).into(),
+			multisig.clone(),
+			call_hash,
+		)?;
+		approve_by::<T>(&signatories, &caller, &multisig, call_hash, s - 1)?;
+
+		let when = Multisig::<T>::timepoint();
+
 		#[extrinsic_call]
-		do_something(RawOrigin::Signed(caller), value);
+		cancel(RawOrigin::Signed(caller), multisig.clone(), call_hash, when);
 
-		assert_eq!(Something::<T>::get(), Some(value));
+		assert!(!Operations::<T>::contains_key(&multisig, call_hash));
+		Ok(())
 	}
 
 	#[benchmark]
-	fn cause_error() {
-		Something::<T>::put(100u32);
+	fn execute(s: Linear<1, { T::MaxSignatories::get() }>) -> Result<(), BenchmarkError> {
 		let caller: T::AccountId = whitelisted_caller();
+		let signatories = setup_signatories::<T>(T::MaxSignatories::get());
+		let multisig = setup_multisig::<T>(&signatories);
+		let call: <T as Config>::RuntimeCall =
+			frame_system::Call::<T>::remark { remark: Vec::new() }.into();
+		let call_hash = call.using_encoded(blake2_256);
+
+		// The operation has `s` approvals, including the depositor's.
+		Multisig::<T>::propose(
+			RawOrigin::Signed(caller.clone()).into(),
+			multisig.clone(),
+			call_hash,
+		)?;
+		let when = Multisig::<T>::timepoint();
+		approve_by::<T>(&signatories, &caller, &multisig, call_hash, s - 1)?;
+
 		#[extrinsic_call]
-		cause_error(RawOrigin::Signed(caller));
+		execute(RawOrigin::Signed(caller), multisig.clone(), Box::new(call), when);
+
+		assert!(!Operations::<T>::contains_key(&multisig, call_hash));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn sweep_expired(
+		e: Linear<0, { T::MaxExpiriesPerBlock::get() }>,
+		s: Linear<1, { T::MaxSignatories::get() }>,
+	) -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		let signatories = setup_signatories::<T>(T::MaxSignatories::get());
+		let multisig = setup_multisig::<T>(&signatories);
+		let balance = T::OperationDeposit::get().saturating_mul((e + 10).into());
+		let _ = <T::NativeBalance as fungible::Mutate<T::AccountId>>::set_balance(&caller, balance);
+
+		// `e` operations with `s` approvals each expire in the same block.
+		for i in 0..e {
+			let call_hash = (i, multisig.clone()).using_encoded(blake2_256);
+			Multisig::<T>::propose(
+				RawOrigin::Signed(caller.clone()).into(),
+				multisig.clone(),
+				call_hash,
+			)?;
+			approve_by::<T>(&signatories, &caller, &multisig, call_hash, s - 1)?;
+		}
+		let now = Multisig::<T>::timepoint().height.saturating_add(T::MaxPendingBlocks::get());
+		frame_system::Pallet::<T>::set_block_number(now);
+		NextExpiryBlock::<T>::put(now);
+
+		#[block]
+		{
+			Multisig::<T>::sweep_expired(now, Weight::MAX);
+		}
 
-		assert_eq!(Something::<T>::get(), Some(101u32));
+		assert!(!Expiries::<T>::contains_key(now));
+		assert_eq!(Operations::<T>::iter_prefix(&multisig).count(), 0);
+		Ok(())
 	}
 
-	impl_benchmark_test_suite!(Voting, crate::mock::new_test_ext(), crate::mock::Test);
+	impl_benchmark_test_suite!(Multisig, crate::mock::new_test_ext(), crate::mock::Test);
 }
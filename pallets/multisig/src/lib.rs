@@ -1,9 +1,13 @@
+//! A pallet for M-of-N multisig accounts. An account is derived from a sorted set of signatories
+//! and a threshold. Signatories propose calls by hash and approve them, and once enough of them
+//! have approved, the call is dispatched from the multisig account's signed origin.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{pallet_prelude::*, traits::fungible};
-/// Edit this file to define custom logic or remove it if it is not needed.
-/// Learn more about FRAME and the core library of Substrate FRAME pallets:
-/// <https://docs.substrate.io/reference/frame-pallets/>
+use frame_support::{
+	dispatch::{Dispatchable, PostDispatchInfo, WithPostDispatchInfo},
+	pallet_prelude::*,
+	traits::{fungible, tokens::Precision, Contains},
+};
 pub use pallet::*;
 
 #[cfg(test)]
@@ -15,17 +19,23 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod weights;
+pub use weights::*;
+
 pub type BalanceOf<T> = <<T as Config>::NativeBalance as fungible::Inspect<
 	<T as frame_system::Config>::AccountId,
 >>::Balance;
 
+/// The hash of an encoded call, identifying an operation of a multisig.
+pub type CallHash = [u8; 32];
+
 use sp_io::hashing::blake2_256;
-use sp_runtime::traits::TrailingZeroInput;
+use sp_runtime::traits::{CheckedAdd, Convert, One, Saturating, TrailingZeroInput, Zero};
 
 #[frame_support::pallet]
 pub mod pallet {
 	use crate::*;
-	use frame_support::dispatch::{Dispatchable, GetDispatchInfo, RawOrigin};
+	use frame_support::dispatch::{GetDispatchInfo, RawOrigin};
 	use frame_system::pallet_prelude::*;
 	use sp_std::prelude::*;
 
@@ -41,43 +51,239 @@ pub mod pallet {
 		/// Type to access the Balances Pallet.
 		type NativeBalance: fungible::Inspect<Self::AccountId>
 			+ fungible::Mutate<Self::AccountId>
-			+ fungible::hold::Inspect<Self::AccountId>
+			+ fungible::hold::Inspect<Self::AccountId, Reason = Self::RuntimeHoldReason>
 			+ fungible::hold::Mutate<Self::AccountId>
-			+ fungible::freeze::Inspect<Self::AccountId>
+			+ fungible::freeze::Inspect<Self::AccountId, Id = Self::RuntimeFreezeReason>
 			+ fungible::freeze::Mutate<Self::AccountId>;
 
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
+
+		/// The overarching freeze reason.
+		type RuntimeFreezeReason: From<FreezeReason>;
+
 		/// A type representing all calls available in your runtime.
 		#[pallet::no_default_bounds]
 		type RuntimeCall: Parameter
-			+ Dispatchable<RuntimeOrigin = Self::RuntimeOrigin>
+			+ Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
 			+ GetDispatchInfo;
+
+		/// The maximum number of signatories of a multisig.
+		#[pallet::constant]
+		type MaxSignatories: Get<u32>;
+
+		/// The deposit held from the signatory that proposes an operation, until the operation
+		/// is executed, cancelled or expires.
+		#[pallet::constant]
+		type OperationDeposit: Get<BalanceOf<Self>>;
+
+		/// The number of blocks an operation stays pending before it expires.
+		#[pallet::constant]
+		type MaxPendingBlocks: Get<BlockNumberFor<Self>>;
+
+		/// The maximum number of operations that can expire in a single block.
+		#[pallet::constant]
+		type MaxExpiriesPerBlock: Get<u32>;
+
+		/// The number of blocks searched for space in their expiry schedule when an operation is
+		/// proposed, starting from the block it expires in. Proposing fails if they are all full.
+		#[pallet::constant]
+		type MaxExpiryLookahead: Get<u32>;
+
+		/// The voting power of an account, used as the weight of signatories of multisigs
+		/// created with `WeightSource::VotingPower`. It should only depend on the account's
+		/// native balance, which is frozen while the account's approvals are pending.
+		type VotingPower: Convert<Self::AccountId, u32>;
+
+		/// The calls that can be dispatched with `redispatch`.
+		type CallFilter: Contains<<Self as Config>::RuntimeCall>;
+
+		/// The maximum number of `redispatch` calls that can be nested inside each other,
+		/// including the outermost one. The depth is shared with every pallet redispatching calls
+		/// through `redispatch_primitives`, whatever pallet the nested calls belong to.
+		#[pallet::constant]
+		type MaxNestingDepth: Get<u32>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// Where the weights of a multisig's signatories come from.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub enum WeightSource {
+		/// Each signatory has the fixed weight given when the multisig was created.
+		Fixed,
+		/// Each signatory's weight is its `VotingPower` when it approves an operation, and its
+		/// balance is frozen until the operation is executed, cancelled or expires, so the same
+		/// tokens can't back the approvals of two signatories. The weights given when the
+		/// multisig is created must be zero.
+		VotingPower,
 	}
 
-	// The pallet's runtime storage items.
-	// https://docs.substrate.io/main-docs/build/runtime-storage/
+	/// The block number and extrinsic index an operation was proposed at.
+	#[derive(
+		Copy, Clone, Eq, PartialEq, Default, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen,
+	)]
+	pub struct Timepoint<BlockNumber> {
+		/// The block the operation was proposed in.
+		pub height: BlockNumber,
+		/// The index of the proposing extrinsic in that block.
+		pub index: u32,
+	}
+
+	/// A multisig account's weighted signatories, sorted by account and without duplicates, and
+	/// the approval weight required to execute an operation.
+	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct MultisigInfo<T: Config> {
+		pub signatories: BoundedVec<(T::AccountId, u32), T::MaxSignatories>,
+		pub threshold: u32,
+		pub weight_source: WeightSource,
+	}
+
+	/// A call proposed for dispatch from a multisig account.
+	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Operation<T: Config> {
+		/// The signatory that proposed the operation and paid its deposit.
+		pub depositor: T::AccountId,
+		/// Deposit held from the depositor until the operation is executed, cancelled or expires.
+		pub deposit: BalanceOf<T>,
+		/// When the operation was proposed. Approvals must refer to it, so they can't be
+		/// replayed onto a later operation with the same call hash.
+		pub when: Timepoint<BlockNumberFor<T>>,
+		/// The signatories that approved the operation, including the depositor, with their
+		/// weights when they approved.
+		pub approvals: BoundedVec<(T::AccountId, u32), T::MaxSignatories>,
+	}
+
+	/// Multisig accounts that have been created, keyed by their derived account.
+	#[pallet::storage]
+	pub type Multisigs<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, MultisigInfo<T>>;
+
+	/// Pending operations of each multisig account, keyed by the hash of the proposed call.
 	#[pallet::storage]
-	#[pallet::getter(fn something)]
-	// Learn more about declaring storage items:
-	// https://docs.substrate.io/main-docs/build/runtime-storage/#declaring-storage-items
-	pub type Something<T> = StorageValue<_, u32>;
+	pub type Operations<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Identity, CallHash, Operation<T>>;
+
+	/// Operations that expire at a block, swept in `on_idle`.
+	#[pallet::storage]
+	pub type Expiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<(T::AccountId, CallHash), T::MaxExpiriesPerBlock>,
+		ValueQuery,
+	>;
+
+	/// The next block whose expiries have not been swept yet. Set when the first operation is
+	/// proposed.
+	#[pallet::storage]
+	pub type NextExpiryBlock<T: Config> = StorageValue<_, BlockNumberFor<T>>;
+
+	/// The number of pending operations of `WeightSource::VotingPower` multisigs each account
+	/// has approved. The account's balance is frozen while it is non-zero.
+	#[pallet::storage]
+	pub type ApprovalFreezes<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32>;
+
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		#[codec(index = 0)]
+		OperationDeposit,
+	}
+
+	/// A reason for the pallet freezing funds.
+	#[pallet::composite_enum]
+	pub enum FreezeReason {
+		/// The balance backing the approvals of pending operations of voting power multisigs.
+		#[codec(index = 0)]
+		Approval,
+	}
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/main-docs/build/events-errors/
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Event documentation should end with an array that provides descriptive names for event
-		/// parameters. [something, who]
-		SomethingStored { something: u32, who: T::AccountId },
+		/// A multisig account was created. [who, multisig, threshold, weight_source]
+		MultisigCreated {
+			who: T::AccountId,
+			multisig: T::AccountId,
+			threshold: u32,
+			weight_source: WeightSource,
+		},
+		/// A signatory proposed a call for a multisig. [who, multisig, call_hash, timepoint]
+		OperationProposed {
+			who: T::AccountId,
+			multisig: T::AccountId,
+			call_hash: CallHash,
+			timepoint: Timepoint<BlockNumberFor<T>>,
+		},
+		/// A signatory approved an operation. [who, multisig, call_hash, approval_weight]
+		OperationApproved {
+			who: T::AccountId,
+			multisig: T::AccountId,
+			call_hash: CallHash,
+			approval_weight: u32,
+		},
+		/// An operation was cancelled by its depositor. [who, multisig, call_hash]
+		OperationCancelled { who: T::AccountId, multisig: T::AccountId, call_hash: CallHash },
+		/// An operation's call was dispatched from the multisig account. [multisig, call_hash,
+		/// result]
+		OperationExecuted { multisig: T::AccountId, call_hash: CallHash, result: DispatchResult },
+		/// A pending operation expired and its deposit was returned. [multisig, call_hash,
+		/// depositor]
+		OperationExpired { multisig: T::AccountId, call_hash: CallHash, depositor: T::AccountId },
 	}
 
 	// Errors inform users that something went wrong.
 	#[pallet::error]
 	pub enum Error<T> {
-		/// Error names should be descriptive.
-		NoneValue,
-		/// Errors should have helpful documentation associated with them.
-		StorageOverflow,
+		/// Signatories must be sorted and without duplicates.
+		SignatoriesOutOfOrder,
+		/// More signatories than `MaxSignatories`.
+		TooManySignatories,
+		/// The threshold must be at least one and, for fixed weights, at most the total weight of
+		/// the signatories.
+		InvalidThreshold,
+		/// Fixed weights must be at least one, and weights must be zero when they come from
+		/// voting power.
+		InvalidWeight,
+		/// The multisig account has already been created.
+		MultisigAlreadyExists,
+		/// No multisig account exists with the given account.
+		MultisigNotFound,
+		/// The signer is not a signatory of the multisig.
+		NotSignatory,
+		/// The call has already been proposed for the multisig.
+		OperationAlreadyExists,
+		/// No operation exists for the call hash.
+		OperationNotFound,
+		/// The signatory has already approved the operation.
+		AlreadyApproved,
+		/// Only the depositor can cancel an operation.
+		NotDepositor,
+		/// The operation's approval weight has not reached the threshold.
+		ThresholdNotReached,
+		/// The timepoint does not match the operation's.
+		WrongTimepoint,
+		/// The operation has been pending for more than `MaxPendingBlocks`.
+		OperationExpired,
+		/// The expiry schedules of the `MaxExpiryLookahead` blocks from the one the operation
+		/// expires in are all full.
+		TooManyExpiries,
+		/// The call is not allowed by `CallFilter`.
+		CallFiltered,
+		/// `redispatch` calls are nested more than `MaxNestingDepth` deep.
+		TooDeeplyNested,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::sweep_expired(now, remaining_weight)
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -85,68 +291,448 @@ pub mod pallet {
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// An example dispatchable that takes a singles value as a parameter, writes the value to
-		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
+		/// Create a multisig account from a set of weighted signatories sorted by account, a
+		/// threshold of approval weight and the source of the signatories' weights. The signer
+		/// must be one of the signatories.
 		#[pallet::call_index(0)]
-		#[pallet::weight(Weight::default())]
-		pub fn do_something(origin: OriginFor<T>, something: u32) -> DispatchResult {
-			// Check that the extrinsic was signed and get the signer.
-			// This function will return an error if the extrinsic is not signed.
-			// https://docs.substrate.io/main-docs/build/origins/
+		#[pallet::weight(T::WeightInfo::create_multisig(signatories.len() as u32))]
+		pub fn create_multisig(
+			origin: OriginFor<T>,
+			signatories: Vec<(T::AccountId, u32)>,
+			threshold: u32,
+			weight_source: WeightSource,
+		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 
-			// Update storage.
-			<Something<T>>::put(something);
+			// Signatories must be strictly increasing, which also rules out duplicates.
+			ensure!(
+				signatories.windows(2).all(|pair| pair[0].0 < pair[1].0),
+				Error::<T>::SignatoriesOutOfOrder
+			);
+			match weight_source {
+				WeightSource::Fixed => {
+					ensure!(
+						signatories.iter().all(|(_, weight)| *weight > 0),
+						Error::<T>::InvalidWeight
+					);
+					let total = signatories
+						.iter()
+						.fold(0u32, |total, (_, weight)| total.saturating_add(*weight));
+					ensure!(threshold > 0 && threshold <= total, Error::<T>::InvalidThreshold);
+				},
+				WeightSource::VotingPower => {
+					ensure!(
+						signatories.iter().all(|(_, weight)| weight.is_zero()),
+						Error::<T>::InvalidWeight
+					);
+					ensure!(threshold > 0, Error::<T>::InvalidThreshold);
+				},
+			}
+			ensure!(
+				signatories.binary_search_by(|(account, _)| account.cmp(&who)).is_ok(),
+				Error::<T>::NotSignatory
+			);
+
+			let multisig = Self::multi_account_id(&signatories, threshold, weight_source);
+			ensure!(!Multisigs::<T>::contains_key(&multisig), Error::<T>::MultisigAlreadyExists);
+
+			let signatories =
+				BoundedVec::try_from(signatories).map_err(|_| Error::<T>::TooManySignatories)?;
+			Multisigs::<T>::insert(
+				&multisig,
+				MultisigInfo { signatories, threshold, weight_source },
+			);
 
-			// Emit an event.
-			Self::deposit_event(Event::SomethingStored { something, who });
-			// Return a successful DispatchResultWithPostInfo
+			Self::deposit_event(Event::MultisigCreated { who, multisig, threshold, weight_source });
 			Ok(())
 		}
 
-		/// An example dispatchable that may throw a custom error.
+		/// Propose a call for a multisig by its hash. The signer's approval is recorded and
+		/// `OperationDeposit` is held from them until the operation is executed, cancelled or
+		/// expires. The operation is tied to the current block and extrinsic index, which the
+		/// other signatories pass as the timepoint when approving it.
 		#[pallet::call_index(1)]
-		#[pallet::weight(Weight::default())]
-		pub fn cause_error(origin: OriginFor<T>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
-
-			// Read a value from storage.
-			match <Something<T>>::get() {
-				// Return an error if the value has not been set.
-				None => Err(Error::<T>::NoneValue.into()),
-				Some(old) => {
-					// Increment the value read from storage; will error in the event of overflow.
-					let new = old.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
-					// Update the value in storage with the incremented result.
-					<Something<T>>::put(new);
-					Ok(())
-				},
+		#[pallet::weight(
+			T::WeightInfo::propose().saturating_add(Pallet::<T>::expiry_schedule_weight())
+		)]
+		pub fn propose(
+			origin: OriginFor<T>,
+			multisig: T::AccountId,
+			call_hash: CallHash,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let info = Self::ensure_signatory(&multisig, &who)?;
+			ensure!(
+				!Operations::<T>::contains_key(&multisig, call_hash),
+				Error::<T>::OperationAlreadyExists
+			);
+
+			// Schedule the operation's expiry, in a later block if its own is full. The operation
+			// still expires `MaxPendingBlocks` after it was proposed, it is only swept later.
+			let timepoint = Self::timepoint();
+			let expiry = Self::schedule_expiry(
+				timepoint.height.saturating_add(T::MaxPendingBlocks::get()),
+				&multisig,
+				call_hash,
+			)?;
+			if !NextExpiryBlock::<T>::exists() {
+				NextExpiryBlock::<T>::put(expiry);
+			}
+
+			let deposit = T::OperationDeposit::get();
+			if !deposit.is_zero() {
+				T::NativeBalance::hold(&HoldReason::OperationDeposit.into(), &who, deposit)?;
 			}
+
+			let mut approvals = BoundedVec::default();
+			approvals
+				.try_push(Self::record_approval(&info, &who)?)
+				.map_err(|_| Error::<T>::TooManySignatories)?;
+			Operations::<T>::insert(
+				&multisig,
+				call_hash,
+				Operation { depositor: who.clone(), deposit, when: timepoint, approvals },
+			);
+
+			Self::deposit_event(Event::OperationProposed { who, multisig, call_hash, timepoint });
+			Ok(())
 		}
 
-		/// An example of re-dispatching a call
+		/// Dispatch a call from the signer's origin. The call must be allowed by `CallFilter`,
+		/// and `redispatch` calls can be nested at most `MaxNestingDepth` deep. Weight the call
+		/// reports it didn't use is refunded.
 		#[pallet::call_index(2)]
-		#[pallet::weight(call.get_dispatch_info().weight)]
+		#[pallet::weight(
+			call.get_dispatch_info().weight.saturating_add(Pallet::<T>::redispatch_overhead())
+		)]
 		pub fn redispatch(
 			origin: OriginFor<T>,
 			call: Box<<T as Config>::RuntimeCall>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_redispatch(who, *call)
+		}
+
+		/// Approve a pending operation of a multisig, identified by its call hash and the
+		/// timepoint it was proposed at.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::approve())]
+		pub fn approve(
+			origin: OriginFor<T>,
+			multisig: T::AccountId,
+			call_hash: CallHash,
+			timepoint: Timepoint<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			let info = Self::ensure_signatory(&multisig, &who)?;
 
-			// Re-dispatch some call on behalf of the caller.
-			let res = call.dispatch(RawOrigin::Signed(who).into());
+			let mut operation = Self::pending_operation(&multisig, call_hash, timepoint)?;
+			ensure!(
+				!operation.approvals.iter().any(|(approver, _)| *approver == who),
+				Error::<T>::AlreadyApproved
+			);
+			operation
+				.approvals
+				.try_push(Self::record_approval(&info, &who)?)
+				.map_err(|_| Error::<T>::TooManySignatories)?;
+			let approval_weight = Self::approval_weight(&operation.approvals);
+			Operations::<T>::insert(&multisig, call_hash, operation);
 
-			// Turn the result from the `dispatch` into our expected `DispatchResult` type.
-			res.map(|_| ()).map_err(|e| e.error)
+			Self::deposit_event(Event::OperationApproved {
+				who,
+				multisig,
+				call_hash,
+				approval_weight,
+			});
+			Ok(())
+		}
+
+		/// Cancel a pending operation and return its deposit. Only the depositor can cancel an
+		/// operation, including one that has expired but not yet been swept.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::cancel(T::MaxSignatories::get()))]
+		pub fn cancel(
+			origin: OriginFor<T>,
+			multisig: T::AccountId,
+			call_hash: CallHash,
+			timepoint: Timepoint<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let operation =
+				Operations::<T>::get(&multisig, call_hash).ok_or(Error::<T>::OperationNotFound)?;
+			ensure!(operation.when == timepoint, Error::<T>::WrongTimepoint);
+			ensure!(operation.depositor == who, Error::<T>::NotDepositor);
+			Self::remove_operation(&multisig, call_hash, &operation)?;
+
+			Self::deposit_event(Event::OperationCancelled { who, multisig, call_hash });
+			Ok(())
+		}
+
+		/// Dispatch an operation's call from the multisig account once the weight of its
+		/// approvals has reached the multisig's threshold. Any signatory can provide the call,
+		/// which must match the proposed hash, along with the operation's timepoint. The
+		/// operation is removed and its deposit returned, whether or not the call succeeds.
+		#[pallet::call_index(5)]
+		#[pallet::weight(
+			T::WeightInfo::execute(T::MaxSignatories::get())
+				.saturating_add(call.get_dispatch_info().weight)
+		)]
+		pub fn execute(
+			origin: OriginFor<T>,
+			multisig: T::AccountId,
+			call: Box<<T as Config>::RuntimeCall>,
+			timepoint: Timepoint<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let info = Self::ensure_signatory(&multisig, &who)?;
+
+			let call_hash = call.using_encoded(blake2_256);
+			let operation = Self::pending_operation(&multisig, call_hash, timepoint)?;
+			ensure!(
+				Self::approval_weight(&operation.approvals) >= info.threshold,
+				Error::<T>::ThresholdNotReached
+			);
+			Self::remove_operation(&multisig, call_hash, &operation)?;
+
+			let result = call
+				.dispatch(RawOrigin::Signed(multisig.clone()).into())
+				.map(|_| ())
+				.map_err(|e| e.error);
+
+			Self::deposit_event(Event::OperationExecuted { multisig, call_hash, result });
+			Ok(())
 		}
 	}
 }
 
 impl<T: Config> Pallet<T> {
-	/// Derive a unique account id using a seed value.
-	pub fn multi_account_id(seed: u32) -> T::AccountId {
-		let entropy = (b"pba/multisig", seed).using_encoded(blake2_256);
+	/// The weight `redispatch` adds to the weight of the call it dispatches.
+	pub fn redispatch_overhead() -> Weight {
+		T::DbWeight::get().reads_writes(1, 2)
+	}
+
+	/// Dispatch a call from an account's signed origin. The nesting depth is shared with the
+	/// `redispatch` calls of other pallets through `redispatch_primitives`. The actual weight
+	/// returned includes `redispatch_overhead`.
+	pub fn do_redispatch(
+		who: T::AccountId,
+		call: <T as Config>::RuntimeCall,
+	) -> DispatchResultWithPostInfo {
+		let overhead = Self::redispatch_overhead();
+		ensure!(T::CallFilter::contains(&call), Error::<T>::CallFiltered.with_weight(overhead));
+		redispatch_primitives::dispatch_as_signed(who, call, T::MaxNestingDepth::get(), overhead)
+			.map_err(|_| Error::<T>::TooDeeplyNested.with_weight(overhead))?
+	}
+
+	/// Derive the account of a multisig from its sorted signatories, threshold and weight
+	/// source.
+	pub fn multi_account_id(
+		signatories: &[(T::AccountId, u32)],
+		threshold: u32,
+		weight_source: WeightSource,
+	) -> T::AccountId {
+		let entropy =
+			(b"pba/multisig", signatories, threshold, weight_source).using_encoded(blake2_256);
 		Decode::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
 			.expect("infinite length input; no invalid inputs for type; qed")
 	}
+
+	/// Return the multisig's info if `who` is one of its signatories.
+	fn ensure_signatory(
+		multisig: &T::AccountId,
+		who: &T::AccountId,
+	) -> Result<MultisigInfo<T>, DispatchError> {
+		let info = Multisigs::<T>::get(multisig).ok_or(Error::<T>::MultisigNotFound)?;
+		ensure!(
+			info.signatories.binary_search_by(|(account, _)| account.cmp(who)).is_ok(),
+			Error::<T>::NotSignatory
+		);
+		Ok(info)
+	}
+
+	/// Return the weight of a signatory of a multisig, or zero if it is not a signatory.
+	pub fn signatory_weight(info: &MultisigInfo<T>, who: &T::AccountId) -> u32 {
+		match info.weight_source {
+			WeightSource::Fixed => info
+				.signatories
+				.binary_search_by(|(account, _)| account.cmp(who))
+				.map_or(0, |index| info.signatories[index].1),
+			WeightSource::VotingPower => T::VotingPower::convert(who.clone()),
+		}
+	}
+
+	/// Return the cumulative weight of an operation's approvals, as recorded when they were
+	/// given.
+	pub fn approval_weight(approvals: &[(T::AccountId, u32)]) -> u32 {
+		approvals.iter().fold(0u32, |total, (_, weight)| total.saturating_add(*weight))
+	}
+
+	/// Return a signatory's approval with its current weight. The balance of a signatory of a
+	/// `WeightSource::VotingPower` multisig is frozen, so the weight can't be moved to another
+	/// signatory while the approval is pending.
+	fn record_approval(
+		info: &MultisigInfo<T>,
+		who: &T::AccountId,
+	) -> Result<(T::AccountId, u32), DispatchError> {
+		if info.weight_source == WeightSource::VotingPower {
+			// Freezes overlap holds, so freeze the held balance too to cover all of the free one.
+			let balance = T::NativeBalance::total_balance(who);
+			T::NativeBalance::extend_freeze(&FreezeReason::Approval.into(), who, balance)?;
+			ApprovalFreezes::<T>::mutate(who, |count| {
+				*count = Some(count.unwrap_or_default().saturating_add(1))
+			});
+		}
+		Ok((who.clone(), Self::signatory_weight(info, who)))
+	}
+
+	/// Stop an approval keeping a signatory's balance frozen, thawing the balance once none of
+	/// its approvals are pending.
+	fn release_approval(who: &T::AccountId) -> DispatchResult {
+		let remaining = ApprovalFreezes::<T>::get(who).unwrap_or_default().saturating_sub(1);
+		if remaining.is_zero() {
+			ApprovalFreezes::<T>::remove(who);
+			T::NativeBalance::thaw(&FreezeReason::Approval.into(), who)?;
+		} else {
+			ApprovalFreezes::<T>::insert(who, remaining);
+		}
+		Ok(())
+	}
+
+	/// The current block number and extrinsic index.
+	pub fn timepoint() -> Timepoint<BlockNumberFor<T>> {
+		Timepoint {
+			height: frame_system::Pallet::<T>::block_number(),
+			index: frame_system::Pallet::<T>::extrinsic_index().unwrap_or_default(),
+		}
+	}
+
+	/// Whether an operation has been pending for `MaxPendingBlocks` as of `now`.
+	fn is_expired(operation: &Operation<T>, now: BlockNumberFor<T>) -> bool {
+		now >= operation.when.height.saturating_add(T::MaxPendingBlocks::get())
+	}
+
+	/// Return an operation that was proposed at `timepoint` and has not expired.
+	fn pending_operation(
+		multisig: &T::AccountId,
+		call_hash: CallHash,
+		timepoint: Timepoint<BlockNumberFor<T>>,
+	) -> Result<Operation<T>, DispatchError> {
+		let operation =
+			Operations::<T>::get(multisig, call_hash).ok_or(Error::<T>::OperationNotFound)?;
+		ensure!(operation.when == timepoint, Error::<T>::WrongTimepoint);
+		ensure!(
+			!Self::is_expired(&operation, frame_system::Pallet::<T>::block_number()),
+			Error::<T>::OperationExpired
+		);
+		Ok(operation)
+	}
+
+	/// Add an operation to the expiry schedule of the first of the `MaxExpiryLookahead` blocks
+	/// from `block` with space left, and return that block.
+	fn schedule_expiry(
+		block: BlockNumberFor<T>,
+		multisig: &T::AccountId,
+		call_hash: CallHash,
+	) -> Result<BlockNumberFor<T>, DispatchError> {
+		let mut block = block;
+		for _ in 0..T::MaxExpiryLookahead::get() {
+			if Expiries::<T>::try_append(block, (multisig.clone(), call_hash)).is_ok() {
+				return Ok(block)
+			}
+			block = match block.checked_add(&One::one()) {
+				Some(next) => next,
+				None => break,
+			};
+		}
+		Err(Error::<T>::TooManyExpiries.into())
+	}
+
+	/// The weight of searching the expiry schedules for space in `schedule_expiry`, on top of
+	/// the single schedule read and write counted in the weight of `propose`.
+	pub fn expiry_schedule_weight() -> Weight {
+		T::DbWeight::get().reads(T::MaxExpiryLookahead::get().saturating_sub(1).into())
+	}
+
+	/// Remove expired operations and return their deposits, walking the expiry schedule from
+	/// the last swept block up to `now` while there is weight left. Operations that were
+	/// executed or cancelled, or replaced by a later operation with the same call hash, are
+	/// skipped.
+	///
+	/// Each block is charged for the expiries it actually holds, so blocks without expiries
+	/// only cost the read and removal of their empty schedule.
+	pub(crate) fn sweep_expired(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+		let db = T::DbWeight::get();
+
+		// Reading and updating the sweep cursor.
+		let mut used = db.reads_writes(1, 1);
+		if used.any_gt(remaining_weight) {
+			return Weight::zero()
+		}
+
+		// Nothing has been scheduled yet.
+		let start = match NextExpiryBlock::<T>::get() {
+			Some(block) => block,
+			None => return db.reads(1),
+		};
+		let mut block = start;
+		while block <= now {
+			// The schedule is read to count its expiries, so make sure that read is paid for.
+			let empty = T::WeightInfo::sweep_expired(0, T::MaxSignatories::get());
+			if used.saturating_add(empty).any_gt(remaining_weight) {
+				break
+			}
+			let expiries = Expiries::<T>::decode_len(block).unwrap_or(0) as u32;
+			let block_weight = T::WeightInfo::sweep_expired(expiries, T::MaxSignatories::get());
+			if used.saturating_add(block_weight).any_gt(remaining_weight) {
+				used = used.saturating_add(db.reads(1));
+				break
+			}
+			used = used.saturating_add(block_weight);
+
+			for (multisig, call_hash) in Expiries::<T>::take(block) {
+				let operation = match Operations::<T>::get(&multisig, call_hash) {
+					Some(operation) if Self::is_expired(&operation, now) => operation,
+					_ => continue,
+				};
+				if Self::remove_operation(&multisig, call_hash, &operation).is_ok() {
+					Self::deposit_event(Event::OperationExpired {
+						multisig,
+						call_hash,
+						depositor: operation.depositor,
+					});
+				}
+			}
+			block = block.saturating_add(One::one());
+		}
+		if block != start {
+			NextExpiryBlock::<T>::put(block);
+		}
+
+		used
+	}
+
+	/// Remove an operation, return its deposit to the depositor and release its approvals.
+	fn remove_operation(
+		multisig: &T::AccountId,
+		call_hash: CallHash,
+		operation: &Operation<T>,
+	) -> DispatchResult {
+		let weight_source = Multisigs::<T>::get(multisig).map(|info| info.weight_source);
+		if weight_source == Some(WeightSource::VotingPower) {
+			for (who, _) in operation.approvals.iter() {
+				Self::release_approval(who)?;
+			}
+		}
+		if !operation.deposit.is_zero() {
+			T::NativeBalance::release(
+				&HoldReason::OperationDeposit.into(),
+				&operation.depositor,
+				operation.deposit,
+				Precision::BestEffort,
+			)?;
+		}
+		Operations::<T>::remove(multisig, call_hash);
+		Ok(())
+	}
 }
@@ -1,8 +1,8 @@
-use crate as pallet_voting;
-use frame_support::traits::{ConstU128, ConstU16, ConstU32, ConstU64};
+use crate as pallet_multisig;
+use frame_support::traits::{fungible::Inspect, ConstU128, ConstU16, ConstU32, ConstU64, Contains};
 use sp_core::H256;
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup},
+	traits::{BlakeTwo256, Convert, IdentityLookup, IntegerSquareRoot},
 	BuildStorage,
 };
 
@@ -15,7 +15,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system,
 		Balances: pallet_balances,
-		Voting: pallet_voting,
+		Multisig: pallet_multisig,
 	}
 );
 
@@ -55,16 +55,43 @@ impl pallet_balances::Config for Test {
 	type MaxLocks = ConstU32<10>;
 	type MaxReserves = ();
 	type ReserveIdentifier = [u8; 8];
-	type RuntimeHoldReason = ();
-	type FreezeIdentifier = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type FreezeIdentifier = RuntimeFreezeReason;
 	type MaxHolds = ConstU32<10>;
 	type MaxFreezes = ConstU32<10>;
 }
 
-impl pallet_voting::Config for Test {
+impl pallet_multisig::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type NativeBalance = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RuntimeFreezeReason = RuntimeFreezeReason;
 	type RuntimeCall = RuntimeCall;
+	type MaxSignatories = ConstU32<5>;
+	type OperationDeposit = ConstU128<10>;
+	type MaxPendingBlocks = ConstU64<10>;
+	type MaxExpiriesPerBlock = ConstU32<2>;
+	type MaxExpiryLookahead = ConstU32<2>;
+	type VotingPower = BalanceVotingPower;
+	type CallFilter = NoBalancesCalls;
+	type MaxNestingDepth = ConstU32<2>;
+	type WeightInfo = ();
+}
+
+// Forbid redispatching calls to the balances pallet.
+pub struct NoBalancesCalls;
+impl Contains<RuntimeCall> for NoBalancesCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		!matches!(call, RuntimeCall::Balances(_))
+	}
+}
+
+// Quadratic voting power: the square root of an account's spendable balance.
+pub struct BalanceVotingPower;
+impl Convert<u64, u32> for BalanceVotingPower {
+	fn convert(who: u64) -> u32 {
+		Balances::balance(&who).integer_sqrt().try_into().unwrap_or(u32::MAX)
+	}
 }
 
 // Build genesis storage according to the mock runtime.
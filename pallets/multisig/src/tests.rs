@@ -1,24 +1,669 @@
-use crate::{mock::*, Error, Event};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*, ApprovalFreezes, CallHash, Error, Event, Expiries, FreezeReason, HoldReason,
+	Multisigs, Operations, Timepoint, WeightInfo, WeightSource,
+};
+use codec::Encode;
+use frame_support::{
+	assert_err_ignore_postinfo, assert_noop, assert_ok,
+	traits::{
+		fungible::{InspectFreeze, InspectHold, Mutate},
+		Hooks,
+	},
+	weights::Weight,
+};
+use sp_io::hashing::blake2_256;
+use sp_runtime::{
+	traits::{BlakeTwo256, Hash},
+	TokenError,
+};
+
+type AccountId = u64;
+
+// Fund the signatories and create a 2-of-3 multisig from accounts 1, 2 and 3.
+fn setup_multisig() -> AccountId {
+	System::set_block_number(1);
+	for who in 1..=3 {
+		assert_ok!(Balances::set_balance(&who, 100));
+	}
+	assert_ok!(Multisig::create_multisig(
+		RuntimeOrigin::signed(1),
+		equal_weights(&[1, 2, 3]),
+		2,
+		WeightSource::Fixed
+	));
+	Multisig::multi_account_id(&equal_weights(&[1, 2, 3]), 2, WeightSource::Fixed)
+}
+
+// Give every signatory a weight of one, for a plain M-of-N multisig.
+fn equal_weights(accounts: &[AccountId]) -> Vec<(AccountId, u32)> {
+	accounts.iter().map(|who| (*who, 1)).collect()
+}
+
+fn remark_call() -> (Box<RuntimeCall>, CallHash) {
+	let call =
+		RuntimeCall::System(frame_system::Call::remark_with_event { remark: b"multisig".to_vec() });
+	let call_hash = call.using_encoded(blake2_256);
+	(Box::new(call), call_hash)
+}
+
+#[test]
+fn create_multisig_derives_account_from_signatories() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		System::assert_last_event(
+			Event::MultisigCreated {
+				who: 1,
+				multisig,
+				threshold: 2,
+				weight_source: WeightSource::Fixed,
+			}
+			.into(),
+		);
+
+		let info = Multisigs::<Test>::get(multisig).unwrap();
+		assert_eq!(info.signatories.into_inner(), equal_weights(&[1, 2, 3]));
+		assert_eq!(info.threshold, 2);
+
+		// A different threshold, weight or weight source derives a different account.
+		let weights = equal_weights(&[1, 2, 3]);
+		assert_ne!(Multisig::multi_account_id(&weights, 3, WeightSource::Fixed), multisig);
+		assert_ne!(
+			Multisig::multi_account_id(&[(1, 2), (2, 1), (3, 1)], 2, WeightSource::Fixed),
+			multisig
+		);
+		assert_ne!(Multisig::multi_account_id(&weights, 2, WeightSource::VotingPower), multisig);
+		assert_noop!(
+			Multisig::create_multisig(RuntimeOrigin::signed(2), weights, 2, WeightSource::Fixed),
+			Error::<Test>::MultisigAlreadyExists
+		);
+	});
+}
+
+#[test]
+fn create_multisig_checks_signatories_and_threshold() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				equal_weights(&[2, 1, 3]),
+				2,
+				WeightSource::Fixed
+			),
+			Error::<Test>::SignatoriesOutOfOrder
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				equal_weights(&[1, 1, 3]),
+				2,
+				WeightSource::Fixed
+			),
+			Error::<Test>::SignatoriesOutOfOrder
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				equal_weights(&[1, 2, 3]),
+				0,
+				WeightSource::Fixed
+			),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				equal_weights(&[1, 2, 3]),
+				4,
+				WeightSource::Fixed
+			),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(4),
+				equal_weights(&[1, 2, 3]),
+				2,
+				WeightSource::Fixed
+			),
+			Error::<Test>::NotSignatory
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				equal_weights(&[1, 2, 3, 4, 5, 6]),
+				2,
+				WeightSource::Fixed
+			),
+			Error::<Test>::TooManySignatories
+		);
+	});
+}
+
+#[test]
+fn operation_is_executed_from_multisig_origin_at_threshold() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let when = Multisig::timepoint();
+		let (call, call_hash) = remark_call();
+
+		// Account 1 proposes the call and its deposit is held.
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		System::assert_last_event(
+			Event::OperationProposed { who: 1, multisig, call_hash, timepoint: when }.into(),
+		);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &1), 10);
+
+		// One approval is not enough to execute a 2-of-3 multisig.
+		assert_noop!(
+			Multisig::execute(RuntimeOrigin::signed(1), multisig, call.clone(), when),
+			Error::<Test>::ThresholdNotReached
+		);
+		assert_noop!(
+			Multisig::approve(RuntimeOrigin::signed(1), multisig, call_hash, when),
+			Error::<Test>::AlreadyApproved
+		);
+		assert_noop!(
+			Multisig::approve(RuntimeOrigin::signed(4), multisig, call_hash, when),
+			Error::<Test>::NotSignatory
+		);
+
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(3), multisig, call_hash, when));
+		System::assert_last_event(
+			Event::OperationApproved { who: 3, multisig, call_hash, approval_weight: 2 }.into(),
+		);
+
+		// Any signatory can provide the call once the threshold is reached.
+		assert_ok!(Multisig::execute(RuntimeOrigin::signed(2), multisig, call, when));
+		System::assert_has_event(
+			frame_system::Event::Remarked {
+				sender: multisig,
+				hash: BlakeTwo256::hash(b"multisig"),
+			}
+			.into(),
+		);
+		System::assert_last_event(
+			Event::OperationExecuted { multisig, call_hash, result: Ok(()) }.into(),
+		);
+		assert!(!Operations::<Test>::contains_key(multisig, call_hash));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &1), 0);
+	});
+}
+
+#[test]
+fn execute_checks_call_hash() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let when = Multisig::timepoint();
+		let (_, call_hash) = remark_call();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(2), multisig, call_hash, when));
+
+		let other_call =
+			Box::new(RuntimeCall::System(frame_system::Call::remark { remark: vec![] }));
+		assert_noop!(
+			Multisig::execute(RuntimeOrigin::signed(1), multisig, other_call, when),
+			Error::<Test>::OperationNotFound
+		);
+	});
+}
+
+#[test]
+fn failed_call_still_removes_operation() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let when = Multisig::timepoint();
+
+		// The multisig account has no funds to transfer.
+		let call = RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive {
+			dest: 4,
+			value: 50,
+		});
+		let call_hash = call.using_encoded(blake2_256);
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(2), multisig, call_hash, when));
+
+		assert_ok!(Multisig::execute(RuntimeOrigin::signed(3), multisig, Box::new(call), when));
+		assert!(matches!(
+			System::events().last().map(|record| record.event.clone()),
+			Some(RuntimeEvent::Multisig(Event::OperationExecuted { result: Err(_), .. }))
+		));
+		assert!(!Operations::<Test>::contains_key(multisig, call_hash));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &1), 0);
+	});
+}
+
+#[test]
+fn propose_checks_signatory_and_duplicates() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let (_, call_hash) = remark_call();
+
+		assert_noop!(
+			Multisig::propose(RuntimeOrigin::signed(1), 42, call_hash),
+			Error::<Test>::MultisigNotFound
+		);
+		assert_noop!(
+			Multisig::propose(RuntimeOrigin::signed(4), multisig, call_hash),
+			Error::<Test>::NotSignatory
+		);
+
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		assert_noop!(
+			Multisig::propose(RuntimeOrigin::signed(2), multisig, call_hash),
+			Error::<Test>::OperationAlreadyExists
+		);
+	});
+}
+
+#[test]
+fn only_depositor_can_cancel() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let when = Multisig::timepoint();
+		let (_, call_hash) = remark_call();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+
+		assert_noop!(
+			Multisig::cancel(RuntimeOrigin::signed(2), multisig, call_hash, when),
+			Error::<Test>::NotDepositor
+		);
+		assert_ok!(Multisig::cancel(RuntimeOrigin::signed(1), multisig, call_hash, when));
+		System::assert_last_event(Event::OperationCancelled { who: 1, multisig, call_hash }.into());
+		assert!(!Operations::<Test>::contains_key(multisig, call_hash));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &1), 0);
+
+		assert_noop!(
+			Multisig::cancel(RuntimeOrigin::signed(1), multisig, call_hash, when),
+			Error::<Test>::OperationNotFound
+		);
+	});
+}
+
+#[test]
+fn approvals_cannot_be_replayed_onto_a_new_operation() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let (call, call_hash) = remark_call();
+		let first = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(2), multisig, call_hash, first));
+		assert_ok!(Multisig::execute(RuntimeOrigin::signed(1), multisig, call.clone(), first));
+
+		// The same call is proposed again in a later block.
+		System::set_block_number(2);
+		let second = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		assert_eq!(Operations::<Test>::get(multisig, call_hash).unwrap().when, second);
+
+		// Approvals and execution referring to the first operation are rejected.
+		assert_noop!(
+			Multisig::approve(RuntimeOrigin::signed(2), multisig, call_hash, first),
+			Error::<Test>::WrongTimepoint
+		);
+		assert_noop!(
+			Multisig::execute(RuntimeOrigin::signed(2), multisig, call, first),
+			Error::<Test>::WrongTimepoint
+		);
+		assert_noop!(
+			Multisig::cancel(RuntimeOrigin::signed(1), multisig, call_hash, first),
+			Error::<Test>::WrongTimepoint
+		);
+	});
+}
+
+#[test]
+fn expired_operations_are_swept_on_idle() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let (call, call_hash) = remark_call();
+		let when = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		assert_eq!(Expiries::<Test>::get(11).into_inner(), vec![(multisig, call_hash)]);
+
+		// The operation can't be approved or executed once it has expired.
+		System::set_block_number(11);
+		assert_noop!(
+			Multisig::approve(RuntimeOrigin::signed(2), multisig, call_hash, when),
+			Error::<Test>::OperationExpired
+		);
+		assert_noop!(
+			Multisig::execute(RuntimeOrigin::signed(2), multisig, call, when),
+			Error::<Test>::OperationExpired
+		);
+
+		// Nothing is swept without enough weight.
+		assert_eq!(Multisig::on_idle(11, Weight::zero()), Weight::zero());
+		assert!(Operations::<Test>::contains_key(multisig, call_hash));
+
+		// The sweep removes the operation and returns the deposit.
+		Multisig::on_idle(11, Weight::MAX);
+		System::assert_last_event(
+			Event::OperationExpired { multisig, call_hash, depositor: 1 }.into(),
+		);
+		assert!(!Operations::<Test>::contains_key(multisig, call_hash));
+		assert!(!Expiries::<Test>::contains_key(11));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &1), 0);
+	});
+}
+
+#[test]
+fn sweep_is_charged_for_the_expiries_it_processes() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let (_, call_hash) = remark_call();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+
+		// Before the first expiry only the sweep cursor is read, which is free in the mock.
+		assert_eq!(Multisig::on_idle(10, Weight::MAX), Weight::zero());
+
+		// The block with one expiry is charged for it, and empty blocks only for their schedule.
+		assert_eq!(Multisig::on_idle(11, Weight::MAX), <() as WeightInfo>::sweep_expired(1, 5));
+		assert_eq!(
+			Multisig::on_idle(13, Weight::MAX),
+			<() as WeightInfo>::sweep_expired(0, 5).saturating_mul(2)
+		);
+	});
+}
+
+#[test]
+fn sweep_skips_operations_that_were_proposed_again() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		let (_, call_hash) = remark_call();
+		let first = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		Multisig::on_idle(1, Weight::MAX);
+
+		// The operation is cancelled and proposed again, expiring later.
+		System::set_block_number(5);
+		assert_ok!(Multisig::cancel(RuntimeOrigin::signed(1), multisig, call_hash, first));
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(2), multisig, call_hash));
+		let second = Timepoint { height: 5, index: 0 };
+
+		// The first operation's expiry leaves the second one in place.
+		System::set_block_number(11);
+		Multisig::on_idle(11, Weight::MAX);
+		assert_eq!(Operations::<Test>::get(multisig, call_hash).unwrap().when, second);
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &2), 10);
+
+		System::set_block_number(15);
+		Multisig::on_idle(15, Weight::MAX);
+		assert!(!Operations::<Test>::contains_key(multisig, call_hash));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &2), 0);
+	});
+}
+
+#[test]
+fn full_expiry_blocks_overflow_into_the_next() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_multisig();
+		for i in 0..4u8 {
+			assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, [i; 32]));
+		}
+		assert_eq!(Expiries::<Test>::get(11).len(), 2);
+		assert_eq!(
+			Expiries::<Test>::get(12).into_inner(),
+			vec![(multisig, [2; 32]), (multisig, [3; 32])]
+		);
+
+		// Only `MaxExpiryLookahead` blocks are searched for space.
+		assert_noop!(
+			Multisig::propose(RuntimeOrigin::signed(1), multisig, [4; 32]),
+			Error::<Test>::TooManyExpiries
+		);
+
+		// The overflowing operations still expire with the others, and are swept a block later.
+		System::set_block_number(11);
+		let when = Timepoint { height: 1, index: 0 };
+		assert_noop!(
+			Multisig::approve(RuntimeOrigin::signed(2), multisig, [3; 32], when),
+			Error::<Test>::OperationExpired
+		);
+		Multisig::on_idle(11, Weight::MAX);
+		assert!(!Operations::<Test>::contains_key(multisig, [0; 32]));
+		assert!(Operations::<Test>::contains_key(multisig, [3; 32]));
+		Multisig::on_idle(12, Weight::MAX);
+		assert!(!Operations::<Test>::contains_key(multisig, [3; 32]));
+		assert_eq!(Balances::balance_on_hold(&HoldReason::OperationDeposit.into(), &1), 0);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn create_multisig_checks_weights() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				vec![(1, 1), (2, 0)],
+				1,
+				WeightSource::Fixed
+			),
+			Error::<Test>::InvalidWeight
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				vec![(1, 2), (2, 3)],
+				6,
+				WeightSource::Fixed
+			),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				vec![(1, 1), (2, 1)],
+				1,
+				WeightSource::VotingPower
+			),
+			Error::<Test>::InvalidWeight
+		);
+		assert_noop!(
+			Multisig::create_multisig(
+				RuntimeOrigin::signed(1),
+				vec![(1, 0), (2, 0)],
+				0,
+				WeightSource::VotingPower
+			),
+			Error::<Test>::InvalidThreshold
+		);
+	});
+}
+
+#[test]
+fn execution_requires_cumulative_approval_weight() {
 	new_test_ext().execute_with(|| {
-		// Go past genesis block so events get deposited
 		System::set_block_number(1);
-		// Dispatch a signed extrinsic.
-		assert_ok!(Voting::do_something(RuntimeOrigin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(Voting::something(), Some(42));
-		// Assert that the correct event was deposited
-		System::assert_last_event(Event::SomethingStored { something: 42, who: 1 }.into());
+		for who in 1..=3 {
+			assert_ok!(Balances::set_balance(&who, 100));
+		}
+
+		// Account 1 holds half of the weight; a threshold of 4 needs it and one other.
+		let signatories = vec![(1, 3), (2, 2), (3, 1)];
+		assert_ok!(Multisig::create_multisig(
+			RuntimeOrigin::signed(2),
+			signatories.clone(),
+			4,
+			WeightSource::Fixed
+		));
+		let multisig = Multisig::multi_account_id(&signatories, 4, WeightSource::Fixed);
+
+		let (call, call_hash) = remark_call();
+		let when = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(2), multisig, call_hash));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(3), multisig, call_hash, when));
+		System::assert_last_event(
+			Event::OperationApproved { who: 3, multisig, call_hash, approval_weight: 3 }.into(),
+		);
+		assert_noop!(
+			Multisig::execute(RuntimeOrigin::signed(2), multisig, call.clone(), when),
+			Error::<Test>::ThresholdNotReached
+		);
+
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(1), multisig, call_hash, when));
+		System::assert_last_event(
+			Event::OperationApproved { who: 1, multisig, call_hash, approval_weight: 6 }.into(),
+		);
+		assert_ok!(Multisig::execute(RuntimeOrigin::signed(2), multisig, call, when));
 	});
 }
 
+// Create a voting power multisig of accounts 1 and 2 with a threshold of 12.
+fn setup_voting_power_multisig() -> AccountId {
+	System::set_block_number(1);
+
+	// Voting power is the square root of the free balance in the mock.
+	assert_ok!(Balances::set_balance(&1, 100));
+	assert_ok!(Balances::set_balance(&2, 25));
+	let signatories = vec![(1, 0), (2, 0)];
+	assert_ok!(Multisig::create_multisig(
+		RuntimeOrigin::signed(1),
+		signatories.clone(),
+		12,
+		WeightSource::VotingPower
+	));
+	Multisig::multi_account_id(&signatories, 12, WeightSource::VotingPower)
+}
+
 #[test]
-fn correct_error_for_none_value() {
+fn voting_power_weights_are_recorded_at_approval() {
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
-		assert_noop!(Voting::cause_error(RuntimeOrigin::signed(1)), Error::<Test>::NoneValue);
+		let multisig = setup_voting_power_multisig();
+
+		let (call, call_hash) = remark_call();
+		let when = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(2), multisig, call_hash));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(1), multisig, call_hash, when));
+
+		// Account 2's balance of 15 after its deposit gives 3 votes, for 13 in total.
+		System::assert_last_event(
+			Event::OperationApproved { who: 1, multisig, call_hash, approval_weight: 13 }.into(),
+		);
+		assert_eq!(
+			Operations::<Test>::get(multisig, call_hash).unwrap().approvals.into_inner(),
+			vec![(2, 3), (1, 10)]
+		);
+		assert_eq!(Balances::balance_frozen(&FreezeReason::Approval.into(), &1), 100);
+		assert_eq!(Balances::balance_frozen(&FreezeReason::Approval.into(), &2), 25);
+		assert_eq!(ApprovalFreezes::<Test>::get(1), Some(1));
+
+		assert_ok!(Multisig::execute(RuntimeOrigin::signed(1), multisig, call, when));
+		assert_eq!(ApprovalFreezes::<Test>::get(1), None);
+		assert_eq!(ApprovalFreezes::<Test>::get(2), None);
+		assert_eq!(Balances::balance_frozen(&FreezeReason::Approval.into(), &1), 0);
+		assert_eq!(Balances::balance_frozen(&FreezeReason::Approval.into(), &2), 0);
+	});
+}
+
+#[test]
+fn approved_voting_power_cannot_be_moved_to_another_signatory() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_voting_power_multisig();
+
+		let (call, call_hash) = remark_call();
+		let when = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, call_hash));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(2), multisig, call_hash, when));
+
+		// Once both have approved, neither can move its tokens to the other to count them twice.
+		assert_noop!(
+			Balances::transfer_allow_death(RuntimeOrigin::signed(1), 2, 50),
+			TokenError::Frozen
+		);
+		assert_noop!(
+			Balances::transfer_allow_death(RuntimeOrigin::signed(2), 1, 10),
+			TokenError::Frozen
+		);
+
+		// The balances are thawed once the operation is executed.
+		assert_ok!(Multisig::execute(RuntimeOrigin::signed(1), multisig, call, when));
+		assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(1), 2, 50));
+	});
+}
+
+#[test]
+fn cancelling_thaws_only_balances_without_pending_approvals() {
+	new_test_ext().execute_with(|| {
+		let multisig = setup_voting_power_multisig();
+
+		let first = remark_call().1;
+		let second = [1u8; 32];
+		let when = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(1), multisig, first));
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(2), multisig, second));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(1), multisig, second, when));
+		assert_eq!(ApprovalFreezes::<Test>::get(1), Some(2));
+
+		// Account 1 still approves the second operation, so it stays frozen.
+		assert_ok!(Multisig::cancel(RuntimeOrigin::signed(1), multisig, first, when));
+		assert_eq!(ApprovalFreezes::<Test>::get(1), Some(1));
+		assert_noop!(
+			Balances::transfer_allow_death(RuntimeOrigin::signed(1), 2, 10),
+			TokenError::Frozen
+		);
+
+		assert_ok!(Multisig::cancel(RuntimeOrigin::signed(2), multisig, second, when));
+		assert_eq!(ApprovalFreezes::<Test>::get(1), None);
+		assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(1), 2, 10));
+	});
+}
+
+#[test]
+fn redispatch_dispatches_from_signer() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let (call, _) = remark_call();
+		assert_ok!(Multisig::redispatch(RuntimeOrigin::signed(1), call));
+		System::assert_last_event(
+			frame_system::Event::Remarked { sender: 1, hash: BlakeTwo256::hash(b"multisig") }
+				.into(),
+		);
+		assert_eq!(redispatch_primitives::depth(), 0);
+	});
+}
+
+#[test]
+fn redispatch_checks_call_filter() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::set_balance(&1, 100));
+		let transfer =
+			Box::new(RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive {
+				dest: 2,
+				value: 10,
+			}));
+
+		// The mock forbids balances calls, and only the overhead is charged for trying.
+		let err = Multisig::redispatch(RuntimeOrigin::signed(1), transfer.clone()).unwrap_err();
+		assert_eq!(err.error, Error::<Test>::CallFiltered.into());
+		assert_eq!(err.post_info.actual_weight, Some(Multisig::redispatch_overhead()));
+
+		// Nesting the call doesn't get around the filter, and each level adds its overhead.
+		let nested = Box::new(RuntimeCall::Multisig(crate::Call::redispatch { call: transfer }));
+		let err = Multisig::redispatch(RuntimeOrigin::signed(1), nested).unwrap_err();
+		assert_eq!(err.error, Error::<Test>::CallFiltered.into());
+		assert_eq!(
+			err.post_info.actual_weight,
+			Some(Multisig::redispatch_overhead().saturating_mul(2))
+		);
+		assert_eq!(Balances::free_balance(2), 0);
+	});
+}
+
+#[test]
+fn redispatch_limits_nesting_depth() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let nest = |call: Box<RuntimeCall>| {
+			Box::new(RuntimeCall::Multisig(crate::Call::redispatch { call }))
+		};
+		let (call, _) = remark_call();
+
+		// `MaxNestingDepth` is two in the mock.
+		assert_ok!(Multisig::redispatch(RuntimeOrigin::signed(1), nest(call.clone())));
+		assert_err_ignore_postinfo!(
+			Multisig::redispatch(RuntimeOrigin::signed(1), nest(nest(call))),
+			Error::<Test>::TooDeeplyNested
+		);
+		assert_eq!(redispatch_primitives::depth(), 0);
 	});
 }
@@ -0,0 +1,208 @@
+//! Weights for pallet_multisig.
+//!
+//! THESE ARE HAND-ESTIMATED PLACEHOLDERS, NOT BENCHMARK OUTPUT. Each function counts the storage
+//! reads and writes of its benchmark, listed above it, and adds a guessed execution time and
+//! proof size. They must be replaced with the output of the pallet's benchmarks before the
+//! runtime is used on a live chain:
+//!
+//! ```text
+//! cargo build --release --features runtime-benchmarks
+//! ./target/release/node-template benchmark pallet --chain dev --pallet pallet_multisig \
+//!     --extrinsic '*' --steps 50 --repeat 20 --output pallets/multisig/src/weights.rs
+//! ```
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_multisig.
+pub trait WeightInfo {
+	fn create_multisig(s: u32, ) -> Weight;
+	fn propose() -> Weight;
+	fn approve() -> Weight;
+	fn cancel(s: u32, ) -> Weight;
+	fn execute(s: u32, ) -> Weight;
+	fn sweep_expired(e: u32, s: u32, ) -> Weight;
+}
+
+/// Estimated weights for pallet_multisig, using the runtime's database weights.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: Multisig Multisigs (r:1 w:1)
+	/// The range of component `s` is `[2, 32]`.
+	fn create_multisig(s: u32, ) -> Weight {
+		Weight::from_parts(18_000_000, 3_600)
+			.saturating_add(Weight::from_parts(150_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig Expiries (r:1 w:1)
+	/// Storage: Multisig NextExpiryBlock (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:1 w:1)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn propose() -> Weight {
+		Weight::from_parts(52_000_000, 11_900)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:1 w:1)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn approve() -> Weight {
+		Weight::from_parts(34_000_000, 8_500)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:32 w:32)
+	/// Storage: Balances Freezes (r:32 w:32)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Storage: System Account (r:32 w:32)
+	/// The range of component `s` is `[1, 32]`.
+	fn cancel(s: u32, ) -> Weight {
+		Weight::from_parts(32_000_000, 6_100)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 2_700).saturating_mul(s.into()))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:32 w:32)
+	/// Storage: Balances Freezes (r:32 w:32)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Storage: System Account (r:32 w:32)
+	/// The range of component `s` is `[1, 32]`.
+	fn execute(s: u32, ) -> Weight {
+		Weight::from_parts(38_000_000, 8_700)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 2_700).saturating_mul(s.into()))
+	}
+	/// Storage: Multisig Expiries (r:1 w:1)
+	/// Storage: Multisig Operations (r:50 w:50)
+	/// Storage: Multisig Multisigs (r:50 w:0)
+	/// Storage: Balances Holds (r:50 w:50)
+	/// Storage: Multisig ApprovalFreezes (r:1600 w:1600)
+	/// Storage: Balances Freezes (r:1600 w:1600)
+	/// Storage: System Account (r:1600 w:1600)
+	/// The range of component `e` is `[0, 50]`.
+	/// The range of component `s` is `[1, 32]`.
+	fn sweep_expired(e: u32, s: u32, ) -> Weight {
+		Weight::from_parts(6_000_000, 3_500)
+			.saturating_add(Weight::from_parts(30_000_000, 0).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(e.into()).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(e.into())))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(e.into()).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(e.into())))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(e.into()).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 6_100).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(0, 2_700).saturating_mul(e.into()).saturating_mul(s.into()))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	/// Storage: Multisig Multisigs (r:1 w:1)
+	/// The range of component `s` is `[2, 32]`.
+	fn create_multisig(s: u32, ) -> Weight {
+		Weight::from_parts(18_000_000, 3_600)
+			.saturating_add(Weight::from_parts(150_000, 0).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig Expiries (r:1 w:1)
+	/// Storage: Multisig NextExpiryBlock (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:1 w:1)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn propose() -> Weight {
+		Weight::from_parts(52_000_000, 11_900)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:1 w:1)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Storage: System Account (r:1 w:1)
+	fn approve() -> Weight {
+		Weight::from_parts(34_000_000, 8_500)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:32 w:32)
+	/// Storage: Balances Freezes (r:32 w:32)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Storage: System Account (r:32 w:32)
+	/// The range of component `s` is `[1, 32]`.
+	fn cancel(s: u32, ) -> Weight {
+		Weight::from_parts(32_000_000, 6_100)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(s.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 2_700).saturating_mul(s.into()))
+	}
+	/// Storage: Multisig Multisigs (r:1 w:0)
+	/// Storage: Multisig Operations (r:1 w:1)
+	/// Storage: Multisig ApprovalFreezes (r:32 w:32)
+	/// Storage: Balances Freezes (r:32 w:32)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Storage: System Account (r:32 w:32)
+	/// The range of component `s` is `[1, 32]`.
+	fn execute(s: u32, ) -> Weight {
+		Weight::from_parts(38_000_000, 8_700)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(s.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 2_700).saturating_mul(s.into()))
+	}
+	/// Storage: Multisig Expiries (r:1 w:1)
+	/// Storage: Multisig Operations (r:50 w:50)
+	/// Storage: Multisig Multisigs (r:50 w:0)
+	/// Storage: Balances Holds (r:50 w:50)
+	/// Storage: Multisig ApprovalFreezes (r:1600 w:1600)
+	/// Storage: Balances Freezes (r:1600 w:1600)
+	/// Storage: System Account (r:1600 w:1600)
+	/// The range of component `e` is `[0, 50]`.
+	/// The range of component `s` is `[1, 32]`.
+	fn sweep_expired(e: u32, s: u32, ) -> Weight {
+		Weight::from_parts(6_000_000, 3_500)
+			.saturating_add(Weight::from_parts(30_000_000, 0).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(e.into()).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(e.into())))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(e.into()).saturating_mul(s.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(e.into())))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(e.into()).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 6_100).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(0, 2_700).saturating_mul(e.into()).saturating_mul(s.into()))
+	}
+}
@@ -5,30 +5,294 @@ use super::*;
 #[allow(unused)]
 use crate::Pallet as Voting;
 use frame_benchmarking::v2::*;
+use frame_support::{
+	sp_runtime::traits::Hash,
+	sp_std::{boxed::Box, vec, vec::Vec},
+};
 use frame_system::RawOrigin;
 
+/// Balance given to benchmark accounts. Large enough to cover the existential deposit and the
+/// frozen balance of any vote cast during setup.
+const FUNDS: u32 = 1_000_000_000;
+
+/// Votes cast on the benchmarked proposal. Proposals voted on during setup use fewer votes, so
+/// the benchmarked call always has to extend the account's freeze.
+const VOTES: u32 = 10;
+
+/// Fund and register an account so it can vote. Registering records a balance checkpoint, so
+/// the account can vote on proposals submitted from the current block onwards.
+fn setup_voter<T: Config>(who: &T::AccountId) {
+	let _ = <T::NativeBalance as fungible::Mutate<T::AccountId>>::set_balance(who, FUNDS.into());
+	Voting::<T>::do_register_account(who);
+}
+
+/// Derive a unique proposal hash from an index.
+fn proposal_hash<T: Config>(index: u32) -> T::Hash {
+	T::Hashing::hash_of(&(b"proposal", index))
+}
+
+/// The track benchmarked proposals are submitted on. Submitters are funded and registered, so
+/// they can submit proposals on any track.
+fn track<T: Config>() -> TrackId {
+	T::Tracks::get().first().map_or(0, |(id, _)| *id)
+}
+
+/// A call to attach to proposals, with the longest remark that fits in `MaxCallLength`.
+fn remark_call<T: Config>() -> Box<<T as Config>::RuntimeCall> {
+	let call: <T as Config>::RuntimeCall =
+		frame_system::Call::<T>::remark { remark: vec![] }.into();
+	let overhead = call.encoded_size() as u32;
+	let length = T::MaxCallLength::get().saturating_sub(overhead).saturating_sub(4);
+	Box::new(frame_system::Call::<T>::remark { remark: vec![0; length as usize] }.into())
+}
+
+/// Submit a proposal and open it for voting, skipping the discussion period.
+fn submit_active_proposal<T: Config>(
+	who: &T::AccountId,
+	hash: T::Hash,
+	call: Option<Box<<T as Config>::RuntimeCall>>,
+) -> Result<(), BenchmarkError> {
+	Voting::<T>::submit_proposal(
+		RawOrigin::Signed(who.clone()).into(),
+		hash,
+		track::<T>(),
+		Duration::Tier1,
+		call,
+	)?;
+	Voting::<T>::do_activate_proposal(&hash)?;
+	Ok(())
+}
+
+/// Submit `count` proposals and have `who` vote on each of them.
+fn vote_on_proposals<T: Config>(who: &T::AccountId, count: u32) -> Result<(), BenchmarkError> {
+	for i in 0..count {
+		let hash = proposal_hash::<T>(i);
+		submit_active_proposal::<T>(who, hash, None)?;
+		Voting::<T>::cast_vote(
+			RawOrigin::Signed(who.clone()).into(),
+			hash,
+			Vote::Aye(VOTES - 1),
+			None,
+		)?;
+	}
+	Ok(())
+}
+
 #[benchmarks]
 mod benchmarks {
 	use super::*;
 
 	#[benchmark]
-	fn do_something() {
-		let value = 100u32.into();
+	fn register_account() -> Result<(), BenchmarkError> {
+		let origin =
+			T::RegisterOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let who: T::AccountId = account("who", 0, 0);
+		#[extrinsic_call]
+		register_account(origin as T::RuntimeOrigin, who.clone());
+
+		assert!(RegisteredAccounts::<T>::contains_key(&who));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn circle_of_trust_register_account() {
 		let caller: T::AccountId = whitelisted_caller();
+		RegisteredAccounts::<T>::insert(&caller, true);
+		let who: T::AccountId = account("who", 0, 0);
 		#[extrinsic_call]
-		do_something(RawOrigin::Signed(caller), value);
+		circle_of_trust_register_account(RawOrigin::Signed(caller), who.clone());
 
-		assert_eq!(Something::<T>::get(), Some(value));
+		assert!(RegisteredAccounts::<T>::contains_key(&who));
 	}
 
 	#[benchmark]
-	fn cause_error() {
-		Something::<T>::put(100u32);
+	fn submit_proposal() {
 		let caller: T::AccountId = whitelisted_caller();
+		setup_voter::<T>(&caller);
+		let hash = proposal_hash::<T>(0);
 		#[extrinsic_call]
-		cause_error(RawOrigin::Signed(caller));
+		submit_proposal(
+			RawOrigin::Signed(caller),
+			hash,
+			track::<T>(),
+			Duration::Tier3,
+			Some(remark_call::<T>()),
+		);
+
+		assert!(SubmittedProposals::<T>::contains_key(&hash));
+		assert!(ProposalCalls::<T>::contains_key(&hash));
+	}
+
+	#[benchmark]
+	fn cast_vote(
+		p: Linear<0, { T::MaxProposalsAccountCanVote::get() - 1 }>,
+	) -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		setup_voter::<T>(&caller);
+		vote_on_proposals::<T>(&caller, p)?;
+
+		let hash = proposal_hash::<T>(p);
+		submit_active_proposal::<T>(&caller, hash, None)?;
+
+		#[extrinsic_call]
+		cast_vote(
+			RawOrigin::Signed(caller.clone()),
+			hash,
+			Vote::Aye(VOTES),
+			Some(Conviction::Locked6x),
+		);
+
+		assert_eq!(AccountVotes::<T>::get(&caller, &hash), Some(VOTES));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn close_proposal(
+		v: Linear<0, { T::MaxVotersPerProposal::get() }>,
+	) -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		setup_voter::<T>(&caller);
+		let hash = proposal_hash::<T>(0);
+		submit_active_proposal::<T>(&caller, hash, Some(remark_call::<T>()))?;
+
+		// Every voter has their tokens unfrozen when the proposal is closed, and the owner's
+		// deposit is returned.
+		let voters: Vec<T::AccountId> = (0..v).map(|i| account("voter", i, 0)).collect();
+		for voter in voters.iter() {
+			setup_voter::<T>(voter);
+			Voting::<T>::cast_vote(
+				RawOrigin::Signed(voter.clone()).into(),
+				hash,
+				Vote::Aye(VOTES),
+				None,
+			)?;
+		}
+
+		let deadline = Voting::<T>::get_proposal(hash)?.deadline;
+		frame_system::Pallet::<T>::set_block_number(deadline);
+
+		#[extrinsic_call]
+		close_proposal(RawOrigin::Signed(caller), hash);
+
+		assert!(Voting::<T>::get_proposal(hash)?.status != Status::Active);
+		for voter in voters.iter() {
+			assert!(!AccountVotes::<T>::contains_key(voter, &hash));
+		}
+		Ok(())
+	}
+
+	#[benchmark]
+	fn claim_back_tokens(
+		p: Linear<0, { T::MaxProposalsAccountCanVote::get() - 1 }>,
+	) -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		setup_voter::<T>(&caller);
+		vote_on_proposals::<T>(&caller, p)?;
+
+		// Vote with conviction so the tokens are still frozen after the proposal is closed.
+		let hash = proposal_hash::<T>(p);
+		submit_active_proposal::<T>(&caller, hash, None)?;
+		Voting::<T>::cast_vote(
+			RawOrigin::Signed(caller.clone()).into(),
+			hash,
+			Vote::Aye(VOTES),
+			Some(Conviction::Locked2x),
+		)?;
+
+		// Close the proposal; the others stay active and keep tokens frozen.
+		let deadline = Voting::<T>::get_proposal(hash)?.deadline;
+		frame_system::Pallet::<T>::set_block_number(deadline);
+		Voting::<T>::close_proposal(RawOrigin::Signed(caller.clone()).into(), hash)?;
+
+		let unlock_block = AccountVoteLocks::<T>::get(&caller, &hash)
+			.ok_or(BenchmarkError::Stop("vote lock not found"))?
+			.unlock_block;
+		frame_system::Pallet::<T>::set_block_number(unlock_block);
+
+		#[extrinsic_call]
+		claim_back_tokens(RawOrigin::Signed(caller.clone()), hash);
+
+		assert!(!AccountVotes::<T>::contains_key(&caller, &hash));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn checkpoint_balance() {
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = <T::NativeBalance as fungible::Mutate<T::AccountId>>::set_balance(
+			&caller,
+			FUNDS.into(),
+		);
+
+		// Fill the checkpoints so the oldest one has to be dropped.
+		let max = T::MaxBalanceCheckpoints::get();
+		for i in 0..max {
+			frame_system::Pallet::<T>::set_block_number(i.into());
+			Voting::<T>::checkpoint_balance_of(&caller);
+		}
+		frame_system::Pallet::<T>::set_block_number(max.into());
+
+		#[extrinsic_call]
+		checkpoint_balance(RawOrigin::Signed(caller.clone()));
+
+		let checkpoints = BalanceCheckpoints::<T>::get(&caller);
+		assert_eq!(checkpoints.len() as u32, max);
+		assert_eq!(checkpoints.last().map(|(block, _)| *block), Some(max.into()));
+	}
+
+	#[benchmark]
+	fn activate_proposal() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		setup_voter::<T>(&caller);
+		let hash = proposal_hash::<T>(0);
+		Voting::<T>::submit_proposal(
+			RawOrigin::Signed(caller).into(),
+			hash,
+			track::<T>(),
+			Duration::Tier1,
+			None,
+		)?;
+		SubmittedProposals::<T>::mutate(&hash, |maybe_proposal| {
+			if let Some(proposal) = maybe_proposal {
+				proposal.status = Status::Pending;
+			}
+		});
+
+		#[block]
+		{
+			Voting::<T>::do_activate_proposal(&hash)?;
+		}
+
+		assert!(Voting::<T>::get_proposal(hash)?.status == Status::Active);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn enact_proposal() -> Result<(), BenchmarkError> {
+		let caller: T::AccountId = whitelisted_caller();
+		setup_voter::<T>(&caller);
+		let hash = proposal_hash::<T>(0);
+		Voting::<T>::submit_proposal(
+			RawOrigin::Signed(caller).into(),
+			hash,
+			track::<T>(),
+			Duration::Tier1,
+			Some(remark_call::<T>()),
+		)?;
+		SubmittedProposals::<T>::mutate(&hash, |maybe_proposal| {
+			if let Some(proposal) = maybe_proposal {
+				proposal.status = Status::Passed;
+			}
+		});
+
+		#[block]
+		{
+			Voting::<T>::do_enact_proposal(&hash)?;
+		}
 
-		assert_eq!(Something::<T>::get(), Some(101u32));
+		assert!(Voting::<T>::get_proposal(hash)?.status == Status::Enacted);
+		assert!(!ProposalCalls::<T>::contains_key(&hash));
+		Ok(())
 	}
 
 	impl_benchmark_test_suite!(Voting, crate::mock::new_test_ext(), crate::mock::Test);
@@ -15,22 +15,41 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migrations;
+pub mod weights;
+pub use weights::*;
+
 pub type BalanceOf<T> = <<T as Config>::NativeBalance as fungible::Inspect<
 	<T as frame_system::Config>::AccountId,
 >>::Balance;
 
-use frame_support::sp_runtime::traits::{Convert, Saturating};
+use frame_support::dispatch::GetDispatchInfo;
+use frame_support::sp_runtime::traits::{
+	CheckedAdd, Convert, Dispatchable, IntegerSquareRoot, One, Saturating, Zero,
+};
+use frame_support::sp_std::boxed::Box;
 use frame_support::traits::fungible::MutateFreeze;
+use frame_support::traits::StoredMap;
+use frame_support::traits::tokens::Precision;
 use frame_support::traits::tokens::currency::Currency;
+use frame_support::sp_runtime::Perbill;
+use frame_support::sp_std::vec::Vec;
 use frame_support::BoundedVec;
 use frame_system::pallet_prelude::BlockNumberFor;
 
+#[cfg(any(feature = "try-runtime", test))]
+use frame_support::sp_runtime::TryRuntimeError;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use crate::*;
 	use frame_system::pallet_prelude::{OriginFor, *};
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(5);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
@@ -42,7 +61,7 @@ pub mod pallet {
 		/// Type to access the Balances Pallet.
 		type NativeBalance: fungible::Inspect<Self::AccountId>
 			+ fungible::Mutate<Self::AccountId>
-			+ fungible::hold::Inspect<Self::AccountId>
+			+ fungible::hold::Inspect<Self::AccountId, Reason = Self::RuntimeHoldReason>
 			+ fungible::hold::Mutate<Self::AccountId>
 			+ fungible::freeze::Inspect<Self::AccountId>
 			+ fungible::freeze::Mutate<Self::AccountId, Id = Self::RuntimeFreezeReason>
@@ -55,20 +74,77 @@ pub mod pallet {
 		/// The overarching freeze reason.
 		type RuntimeFreezeReason: From<FreezeReason>;
 
-		/// Tiers representing the possible duration of a proposal. A u8 representing how many
-		/// weeks the proposal will be active.
-		#[pallet::constant]
-		type ProposalDurationTier1: Get<u8>;
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
 
-		#[pallet::constant]
-		type ProposalDurationTier2: Get<u8>;
+		/// The overarching call type. Calls attached to proposals are dispatched once the
+		/// proposal has passed, with the origin set by the proposal's track.
+		type RuntimeCall: Parameter
+			+ Dispatchable<RuntimeOrigin = Self::RuntimeOrigin>
+			+ GetDispatchInfo
+			+ From<frame_system::Call<Self>>;
 
+		/// The runtime's pallet origins, which include the pallet's `Origin`.
+		type PalletsOrigin: From<Origin> + Into<<Self as frame_system::Config>::RuntimeOrigin>;
+
+		/// The origin allowed to register accounts to vote on proposals.
+		type RegisterOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The tracks proposals can be submitted on, with the duration options, pass rules,
+		/// deposit and allowed submitters of each track.
 		#[pallet::constant]
-		type ProposalDurationTier3: Get<u8>;
+		type Tracks: Get<Vec<(TrackId, TrackInfo<BalanceOf<Self>>)>>;
 
 		/// The maximum number of proposals an account can vote on.
 		#[pallet::constant]
 		type MaxProposalsAccountCanVote: Get<u32>;
+
+		/// The number of blocks a single conviction lock period lasts. Conviction votes keep
+		/// their tokens frozen for a multiple of this period after the proposal's deadline.
+		#[pallet::constant]
+		type ConvictionBasePeriod: Get<BlockNumberFor<Self>>;
+
+		/// The maximum number of accounts that can vote on a single proposal.
+		#[pallet::constant]
+		type MaxVotersPerProposal: Get<u32>;
+
+		/// The maximum number of balance checkpoints kept per account. Once it is reached, the two
+		/// oldest checkpoints are merged into one at the older block with the lower balance, so
+		/// old balances are understated rather than lost.
+		#[pallet::constant]
+		type MaxBalanceCheckpoints: Get<u32>;
+
+		/// The number of blocks a proposal is discussed for before voting opens.
+		#[pallet::constant]
+		type DiscussionPeriod: Get<BlockNumberFor<Self>>;
+
+		/// The number of blocks between a proposal passing and its attached call being
+		/// dispatched.
+		#[pallet::constant]
+		type EnactmentDelay: Get<BlockNumberFor<Self>>;
+
+		/// The maximum length of the encoded call attached to a proposal.
+		#[pallet::constant]
+		type MaxCallLength: Get<u32>;
+
+		/// The maximum number of proposal status transitions scheduled in a single block. Further
+		/// transitions are scheduled in the next block with space in its agenda.
+		#[pallet::constant]
+		type MaxScheduledPerBlock: Get<u32>;
+
+		/// The number of blocks searched for space in their agenda when a status transition is
+		/// scheduled, starting from the block it is due in. Scheduling fails if they are all
+		/// full.
+		#[pallet::constant]
+		type MaxScheduleLookahead: Get<u32>;
+
+		/// The maximum weight `on_initialize` spends applying scheduled status transitions.
+		/// Transitions that don't fit are carried over to the following blocks.
+		#[pallet::constant]
+		type MaxServiceWeight: Get<Weight>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	/// Voting options to be sent with extrinsic.
@@ -78,15 +154,60 @@ pub mod pallet {
 		Nay(u32),
 	}
 
+	/// Conviction that can be attached to a vote. A voter multiplies the weight of their votes
+	/// in exchange for keeping their tokens frozen for longer after the proposal's deadline.
+	#[derive(Clone, Copy, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub enum Conviction {
+		/// 2x votes, locked for 1 period after the deadline.
+		Locked2x,
+		/// 3x votes, locked for 2 periods after the deadline.
+		Locked3x,
+		/// 4x votes, locked for 4 periods after the deadline.
+		Locked4x,
+		/// 5x votes, locked for 8 periods after the deadline.
+		Locked5x,
+		/// 6x votes, locked for 16 periods after the deadline.
+		Locked6x,
+	}
+
+	impl Conviction {
+		/// The multiplier applied to the votes cast with this conviction.
+		pub fn multiplier(&self) -> u32 {
+			match self {
+				Conviction::Locked2x => 2,
+				Conviction::Locked3x => 3,
+				Conviction::Locked4x => 4,
+				Conviction::Locked5x => 5,
+				Conviction::Locked6x => 6,
+			}
+		}
+
+		/// The number of `ConvictionBasePeriod`s tokens stay frozen after the deadline.
+		pub fn lock_periods(&self) -> u32 {
+			match self {
+				Conviction::Locked2x => 1,
+				Conviction::Locked3x => 2,
+				Conviction::Locked4x => 4,
+				Conviction::Locked5x => 8,
+				Conviction::Locked6x => 16,
+			}
+		}
+	}
+
 	/// Status of a proposal.
 	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
 	pub enum Status {
 		Active,
 		Failed,
 		Passed,
+		/// The proposal is being discussed and does not accept votes yet.
+		Pending,
+		/// The proposal passed and its attached call has been dispatched.
+		Enacted,
 	}
 
-	/// Duration tiers for a proposal.
+	/// Duration tiers for a proposal. The number of weeks of each tier is set by the track
+	/// the proposal is submitted on.
 	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
 	pub enum Duration {
 		Tier1,
@@ -94,6 +215,47 @@ pub mod pallet {
 		Tier3,
 	}
 
+	/// Identifier of a proposal track.
+	pub type TrackId = u8;
+
+	/// Accounts allowed to submit proposals on a track.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub enum SubmitterOrigin {
+		/// Any signed account.
+		Signed,
+		/// Registered accounts only.
+		Registered,
+	}
+
+	/// The origin the calls of a track's passed proposals are dispatched with.
+	#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub enum EnactmentOrigin {
+		/// The root origin.
+		Root,
+		/// The pallet's `Origin::Track` for the track, which the runtime can allow narrower
+		/// powers than root with `EnsureTrack`.
+		Track,
+	}
+
+	/// Parameters of a proposal track.
+	#[derive(Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode)]
+	pub struct TrackInfo<Balance> {
+		/// Name of the track.
+		pub name: &'static str,
+		/// The number of weeks a proposal is active for, for each duration tier.
+		pub durations: [u8; 3],
+		/// The proportion of registered accounts that must vote for a proposal to pass.
+		pub quorum: Perbill,
+		/// A proposal passes with more than this proportion of aye votes.
+		pub approval: Perbill,
+		/// Deposit held from the submitter until the proposal is closed.
+		pub deposit: Balance,
+		/// Accounts allowed to submit proposals on the track.
+		pub submitter: SubmitterOrigin,
+		/// The origin the calls of the track's passed proposals are dispatched with.
+		pub enactment: EnactmentOrigin,
+	}
+
 	/// Proposal data.
 	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
 	#[scale_info(skip_type_params(T))]
@@ -103,6 +265,24 @@ pub mod pallet {
 		pub owner: T::AccountId,
 		pub aye_votes: u32,
 		pub nay_votes: u32,
+		/// The number of accounts that voted on the proposal.
+		pub voter_count: u32,
+		/// The block at which voters' balances are measured to bound their votes.
+		pub snapshot_block: BlockNumberFor<T>,
+		/// The track the proposal was submitted on.
+		pub track: TrackId,
+		/// Deposit held from the owner until the proposal is closed.
+		pub deposit: BalanceOf<T>,
+	}
+
+	/// Lock schedule entry for an account's votes on a proposal.
+	#[derive(Clone, PartialEq, DebugNoBound, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct VoteLock<T: Config> {
+		/// Votes added to the proposal's tally after applying conviction multipliers.
+		pub weighted_votes: u32,
+		/// The block from which the frozen tokens can be claimed back.
+		pub unlock_block: BlockNumberFor<T>,
 	}
 
 	/// Map of registered users.
@@ -110,6 +290,10 @@ pub mod pallet {
 	pub type RegisteredAccounts<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
 
+	/// The number of registered accounts.
+	#[pallet::storage]
+	pub type RegisteredAccountsCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
 	/// Map of proposals, keyed by the proposal hash.
 	#[pallet::storage]
 	pub type SubmittedProposals<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, Proposal<T>>;
@@ -134,22 +318,169 @@ pub mod pallet {
 		BoundedVec<T::Hash, T::MaxProposalsAccountCanVote>,
 	>;
 
+	/// Free balance checkpoints of an account, as (block number, balance) pairs in ascending
+	/// block order. The first checkpoint is recorded when the account registers or calls
+	/// `checkpoint_balance`, and later ones whenever its balance changes, through
+	/// `CheckpointedAccountStore`.
+	#[pallet::storage]
+	pub type BalanceCheckpoints<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<(BlockNumberFor<T>, BalanceOf<T>), T::MaxBalanceCheckpoints>,
+		ValueQuery,
+	>;
+
+	/// Index of the accounts that voted on a proposal, keyed by the proposal hash first so the
+	/// voters of a proposal can be listed without scanning every account.
+	#[pallet::storage]
+	pub type ProposalVoters<T: Config> = StorageDoubleMap<
+		Hasher1 = Twox64Concat,
+		Key1 = T::Hash,
+		Hasher2 = Blake2_128Concat,
+		Key2 = T::AccountId,
+		Value = (),
+		QueryKind = OptionQuery,
+	>;
+
+	/// Per-account lock schedule for voted on proposals. Tokens cannot be claimed back for a
+	/// proposal until its unlock block has been reached.
+	#[pallet::storage]
+	pub type AccountVoteLocks<T: Config> = StorageDoubleMap<
+		Hasher1 = Blake2_128Concat,
+		Key1 = T::AccountId,
+		Hasher2 = Twox64Concat,
+		Key2 = T::Hash,
+		Value = VoteLock<T>,
+		QueryKind = OptionQuery,
+	>;
+
+	/// SCALE encoded calls attached to proposals, keyed by the proposal hash.
+	#[pallet::storage]
+	pub type ProposalCalls<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::Hash, BoundedVec<u8, T::MaxCallLength>>;
+
+	/// Proposals due for a status transition, keyed by the block the transition happens in.
+	/// Proposals are activated, closed and enacted in `on_initialize`.
+	#[pallet::storage]
+	pub type Agenda<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<T::Hash, T::MaxScheduledPerBlock>,
+		ValueQuery,
+	>;
+
+	/// The earliest block whose agenda has transitions left, because `on_initialize` ran out of
+	/// weight before applying them.
+	#[pallet::storage]
+	pub type IncompleteSince<T: Config> = StorageValue<_, BlockNumberFor<T>>;
+
+	/// Genesis configuration.
+	#[pallet::genesis_config]
+	#[derive(frame_support::DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		/// Accounts registered to vote from genesis.
+		pub registered_accounts: Vec<T::AccountId>,
+		/// Active proposals submitted at genesis, as (owner, proposal hash, track, deadline
+		/// block). No deposit is held for them.
+		pub initial_proposals: Vec<(T::AccountId, T::Hash, TrackId, BlockNumberFor<T>)>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			for who in &self.registered_accounts {
+				Pallet::<T>::do_register_account(who);
+			}
+
+			for (owner, hash, track, deadline) in &self.initial_proposals {
+				assert!(
+					!SubmittedProposals::<T>::contains_key(hash),
+					"Duplicate proposal in genesis config"
+				);
+				assert!(Pallet::<T>::track_info(*track).is_ok(), "Unknown track in genesis config");
+
+				let proposal: Proposal<T> = Proposal {
+					status: Status::Active,
+					owner: owner.clone(),
+					deadline: *deadline,
+					aye_votes: 0,
+					nay_votes: 0,
+					voter_count: 0,
+					snapshot_block: Zero::zero(),
+					track: *track,
+					deposit: Zero::zero(),
+				};
+				SubmittedProposals::<T>::insert(hash, proposal);
+				Pallet::<T>::schedule(*deadline, hash);
+			}
+		}
+	}
+
 	/// Events
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// Account has been regiseterd.
 		AccountRegistered { who: T::AccountId },
-		/// Proposal has been created.
-		ProposalCreated { who: T::AccountId, proposal: T::Hash },
-		/// Proposal has been closed.
-		ProposalClosed { who: T::AccountId, hash: T::Hash },
-		/// Account has voted aye.
-		VotedAye { who: T::AccountId, proposal: T::Hash, votes: u32 },
-		/// Account has voted nay.
-		VotedNay { who: T::AccountId, proposal: T::Hash, votes: u32 },
-		/// Tokens have been unfrozen.
-		TokensClaimed { who: T::AccountId },
+		/// Proposal has been created, holding `deposit` from its owner.
+		ProposalCreated {
+			who: T::AccountId,
+			proposal: T::Hash,
+			track: TrackId,
+			deadline: BlockNumberFor<T>,
+			deposit: BalanceOf<T>,
+		},
+		/// Proposal has been closed with its final status and vote totals.
+		ProposalClosed {
+			who: T::AccountId,
+			hash: T::Hash,
+			status: Status,
+			aye_votes: u32,
+			nay_votes: u32,
+		},
+		/// Account has voted aye. `votes` are the weighted votes added to the proposal,
+		/// `account_votes` the account's votes on the proposal before conviction, and `frozen`
+		/// the account's frozen balance after the vote.
+		VotedAye {
+			who: T::AccountId,
+			proposal: T::Hash,
+			votes: u32,
+			account_votes: u32,
+			frozen: BalanceOf<T>,
+			unlock_block: BlockNumberFor<T>,
+		},
+		/// Account has voted nay. Fields are as in `VotedAye`.
+		VotedNay {
+			who: T::AccountId,
+			proposal: T::Hash,
+			votes: u32,
+			account_votes: u32,
+			frozen: BalanceOf<T>,
+			unlock_block: BlockNumberFor<T>,
+		},
+		/// Tokens frozen for votes on a proposal have been unfrozen. `frozen` is the account's
+		/// remaining frozen balance.
+		TokensClaimed {
+			who: T::AccountId,
+			proposal: T::Hash,
+			amount: BalanceOf<T>,
+			frozen: BalanceOf<T>,
+		},
+		/// Account has recorded a balance checkpoint.
+		BalanceCheckpointed { who: T::AccountId },
+		/// Proposal's discussion period has ended and voting has opened.
+		ProposalActivated { proposal: T::Hash },
+		/// Proposal has been closed and passed.
+		ProposalPassed { proposal: T::Hash, aye_votes: u32, nay_votes: u32, turnout: Perbill },
+		/// Proposal has been closed and failed.
+		ProposalFailed { proposal: T::Hash, aye_votes: u32, nay_votes: u32, turnout: Perbill },
+		/// Proposal's attached call has been dispatched.
+		ProposalEnacted { proposal: T::Hash, result: DispatchResult },
+		/// Proposal's scheduled status transition needs more than `MaxServiceWeight`, so it was
+		/// skipped. A proposal left active can still be closed with `close_proposal`.
+		ProposalOverweight { proposal: T::Hash },
 	}
 
 	/// A reason for freezing funds.
@@ -159,6 +490,21 @@ pub mod pallet {
 		ProposalVote,
 	}
 
+	/// A reason for holding funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		#[codec(index = 0)]
+		ProposalDeposit,
+	}
+
+	/// The origin of calls dispatched by the pallet.
+	#[pallet::origin]
+	#[derive(Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, Encode, Decode, MaxEncodedLen)]
+	pub enum Origin {
+		/// A passed proposal on the track.
+		Track(TrackId),
+	}
+
 	/// Errors to inform users that something went wrong.
 	#[pallet::error]
 	pub enum Error<T> {
@@ -184,6 +530,47 @@ pub mod pallet {
 		VoteProposalsExceeded,
 		/// Cannot vote on a closed proposal.
 		VotingOnClosedProposalNotAllowed,
+		/// Tokens are still locked by a conviction vote.
+		VoteStillLocked,
+		/// The proposal has reached the maximum number of voters.
+		TooManyVoters,
+		/// Cannot vote on a proposal during its discussion period.
+		ProposalPending,
+		/// The encoded call attached to a proposal is too long.
+		CallTooLong,
+		/// The agendas of the `MaxScheduleLookahead` blocks from the one a status transition is
+		/// due in are all full.
+		AgendaFull,
+		/// The proposal has no attached call.
+		CallNotFound,
+		/// The call attached to a proposal could not be decoded.
+		UndecodableCall,
+		/// The track does not exist.
+		UnknownTrack,
+		/// The call attached to a proposal is too heavy to be enacted within `MaxServiceWeight`.
+		CallTooHeavy,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			Self::service_agenda(n)
+		}
+
+		fn integrity_test() {
+			// Otherwise proposals with many voters could only be closed manually.
+			let close_weight = T::WeightInfo::close_proposal(T::MaxVotersPerProposal::get())
+				.saturating_add(Self::schedule_weight());
+			assert!(
+				!close_weight.any_gt(T::MaxServiceWeight::get()),
+				"`MaxServiceWeight` must fit closing a proposal with `MaxVotersPerProposal` voters"
+			);
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), TryRuntimeError> {
+			Self::do_try_state()
+		}
 	}
 
 	/// Calls
@@ -191,12 +578,12 @@ pub mod pallet {
 	impl<T: Config> Pallet<T> {
 		/// Register account.
 		///
-		/// Allows the root origin to register an account to vote on proposals.
+		/// Allows the `RegisterOrigin` to register an account to vote on proposals.
 		#[pallet::call_index(1)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::register_account())]
 		pub fn register_account(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
-			ensure_root(origin)?;
-			RegisteredAccounts::<T>::insert(who.clone(), true);
+			T::RegisterOrigin::ensure_origin(origin)?;
+			Self::do_register_account(&who);
 			Self::deposit_event(Event::AccountRegistered { who });
 			Ok(())
 		}
@@ -206,7 +593,7 @@ pub mod pallet {
 		/// Allows an already-registered account to register another account to vote 
 		/// on proposals.
 		#[pallet::call_index(2)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::circle_of_trust_register_account())]
 		pub fn circle_of_trust_register_account(
 			origin: OriginFor<T>,
 			who: T::AccountId,
@@ -214,7 +601,7 @@ pub mod pallet {
 			let signer = ensure_signed(origin)?;
 
 			if RegisteredAccounts::<T>::contains_key(signer) {
-				RegisteredAccounts::<T>::insert(who.clone(), true);
+				Self::do_register_account(&who);
 				Self::deposit_event(Event::AccountRegistered { who });
 				Ok(())
 			} else {
@@ -226,12 +613,22 @@ pub mod pallet {
 		///
 		/// The proposal metadata includes the owner's account ID, the total number of votes 
 		/// for the proposal (Ayes and Nays), and the deadline represented by a block number.
+		/// The proposal's track sets its duration options and pass rules, and the deposit held
+		/// from the submitter until the proposal is closed. The proposal is discussed for
+		/// `DiscussionPeriod` blocks before voting opens, and an optional call is dispatched with
+		/// the origin the track enacts with, root or the track's `Origin::Track`,
+		/// `EnactmentDelay` blocks after the proposal passes.
 		#[pallet::call_index(3)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(
+			T::WeightInfo::submit_proposal()
+				.saturating_add(Pallet::<T>::schedule_weight().saturating_mul(2))
+		)]
 		pub fn submit_proposal(
 			origin: OriginFor<T>,
 			proposal: T::Hash,
+			track: TrackId,
 			duration: Duration,
+			call: Option<Box<<T as Config>::RuntimeCall>>,
 		) -> DispatchResult {
 			// Check that the function was signed and get the signer.
 			let who = ensure_signed(origin)?;
@@ -242,38 +639,87 @@ pub mod pallet {
 				Error::<T>::AlreadySubmitted
 			);
 
-			// Fetch tier from config, representing the duration in weeks.
+			// Check that the signer can submit proposals on the track.
+			let info = Self::track_info(track)?;
+			if info.submitter == SubmitterOrigin::Registered {
+				ensure!(RegisteredAccounts::<T>::get(&who), Error::<T>::NotRegistered);
+			}
+
+			// Make sure the call can be enacted in `on_initialize` if the proposal passes.
+			if let Some(call) = call.as_ref() {
+				let weight =
+					T::WeightInfo::enact_proposal().saturating_add(call.get_dispatch_info().weight);
+				ensure!(!weight.any_gt(T::MaxServiceWeight::get()), Error::<T>::CallTooHeavy);
+			}
+
+			// Encode the call to dispatch if the proposal passes.
+			let encoded_call = call
+				.map(|call| BoundedVec::<u8, T::MaxCallLength>::try_from(call.encode()))
+				.transpose()
+				.map_err(|_| Error::<T>::CallTooLong)?;
+
+			// Fetch tier from the track, representing the duration in weeks.
 			let tier = match duration {
-				Duration::Tier1 => T::ProposalDurationTier1::get(),
-				Duration::Tier2 => T::ProposalDurationTier2::get(),
-				Duration::Tier3 => T::ProposalDurationTier3::get(),
+				Duration::Tier1 => info.durations[0],
+				Duration::Tier2 => info.durations[1],
+				Duration::Tier3 => info.durations[2],
 			};
 
 			// Get the block number from the FRAME system pallet.
 			let current_block = <frame_system::Pallet<T>>::block_number();
 
+			// Voting opens once the discussion period has ended.
+			let discussion_period = T::DiscussionPeriod::get();
+			let activation_block = current_block.saturating_add(discussion_period);
+			let status = if discussion_period.is_zero() {
+				Status::Active
+			} else {
+				Self::schedule(activation_block, &proposal)?;
+				Status::Pending
+			};
+
 			// Calculate the deadline block.
 			let week_in_seconds: u32 = 604800;
 			let blocks_in_week: u32 =
 				week_in_seconds.checked_div(6u32).ok_or(Error::<T>::ArithmeticError)?;
 			let duration_in_blocks =
 				u32::from(tier).checked_mul(blocks_in_week).ok_or(Error::<T>::ArithmeticError)?;
-			let deadline = current_block.saturating_add(duration_in_blocks.into());
+			let deadline = activation_block.saturating_add(duration_in_blocks.into());
+			Self::schedule(deadline, &proposal)?;
 
-			// Instantiate proposal data.
+			// Hold the track's deposit until the proposal is closed.
+			if !info.deposit.is_zero() {
+				T::NativeBalance::hold(&HoldReason::ProposalDeposit.into(), &who, info.deposit)?;
+			}
+
+			// Instantiate proposal data. Balances are measured when voting opens, so voters can
+			// record a checkpoint during the discussion period.
 			let metadata: Proposal<T> = Proposal {
-				status: Status::Active,
+				status,
 				owner: who.clone(),
 				deadline,
 				aye_votes: 0,
 				nay_votes: 0,
+				voter_count: 0,
+				snapshot_block: activation_block,
+				track,
+				deposit: info.deposit,
 			};
 
 			// Store the proposal data with its hash.
 			SubmittedProposals::<T>::insert(&proposal, metadata);
+			if let Some(encoded_call) = encoded_call {
+				ProposalCalls::<T>::insert(&proposal, encoded_call);
+			}
 
 			// Emit an event that the proposal was created.
-			Self::deposit_event(Event::ProposalCreated { who, proposal });
+			Self::deposit_event(Event::ProposalCreated {
+				who,
+				proposal,
+				track,
+				deadline,
+				deposit: info.deposit,
+			});
 
 			Ok(())
 		}
@@ -281,16 +727,25 @@ pub mod pallet {
 		/// Cast votes on a proposal.
 		///
 		/// An account is able to vote aye or nay on a proposal by freezing some of
-		/// their free balance.
+		/// their free balance. An optional conviction multiplies the votes added to the
+		/// proposal, while keeping the tokens frozen for longer after the deadline.
 		#[pallet::call_index(4)]
-		#[pallet::weight(Weight::default())]
-		pub fn cast_vote(origin: OriginFor<T>, proposal: T::Hash, vote: Vote) -> DispatchResult {
+		#[pallet::weight(T::WeightInfo::cast_vote(T::MaxProposalsAccountCanVote::get()))]
+		pub fn cast_vote(
+			origin: OriginFor<T>,
+			proposal: T::Hash,
+			vote: Vote,
+			conviction: Option<Conviction>,
+		) -> DispatchResult {
 			// Check that the function was signed and get the signer.
 			let who = ensure_signed(origin)?;
 
 			// Verify vote conditions are met.
 			Self::verify_vote_conditions(&who, &proposal)?;
 
+			// Add the account to the proposal's voters on its first vote.
+			Self::add_proposal_voter(&who, &proposal)?;
+
 			// Extract amount of votes.
 			let votes = Self::extract_votes(&vote);
 
@@ -300,11 +755,21 @@ pub mod pallet {
 			// Update account frozen balance.
 			Self::insert_and_update_account_frozen_balance(who.clone(), proposal.clone())?;
 
+			// Apply the conviction multiplier to the votes.
+			let weighted_vote = Self::apply_conviction(&vote, &conviction)?;
+			let weighted_votes = Self::extract_votes(&weighted_vote);
+
+			// Update the account's lock schedule for this proposal.
+			Self::update_vote_lock(&who, &proposal, weighted_votes, &conviction)?;
+
 			// Update proposal votes.
-			Self::add_votes_to_proposal(proposal, &vote)?;
+			Self::add_votes_to_proposal(proposal, &weighted_vote)?;
+
+			// Record the account's balance for future proposals.
+			Self::checkpoint_balance_of(&who);
 
 			// Dispatch event confirming vote.
-			Self::deposit_event(Self::get_vote_event(who, proposal, &vote, votes));
+			Self::deposit_event(Self::get_vote_event(who, proposal, &vote, weighted_votes));
 
 			Ok(())
 		}
@@ -312,30 +777,40 @@ pub mod pallet {
 		/// Close a proposal if the deadline has passed.
 		///
 		/// Sets a proposal's status to either Passed or Failed if its deadline has passed.
-		/// An error will be returned if the deadline has not yet been reached. After
-		/// a proposal has been closed, voters are allowed to unfreeze their voting tokens.
+		/// An error will be returned if the deadline has not yet been reached. Voters whose
+		/// vote lock has expired have their tokens unfrozen when the proposal is closed, while
+		/// conviction voters claim back their tokens once their lock expires. Proposals are
+		/// closed in `on_initialize` when their deadline is reached, so this is only needed for
+		/// proposals that could not be closed then.
 		#[pallet::call_index(5)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(
+			T::WeightInfo::close_proposal(T::MaxVotersPerProposal::get())
+				.saturating_add(Pallet::<T>::schedule_weight())
+		)]
 		pub fn close_proposal(origin: OriginFor<T>, hash: T::Hash) -> DispatchResult {
 			// Check that the function was signed and get the signer.
 			let who = ensure_signed(origin)?;
 
 			// Get proposal from state.
-			let mut proposal = Self::get_proposal(hash.clone())?;
+			let proposal = Self::get_proposal(hash.clone())?;
 
 			// Check if proposal is already closed.
 			ensure!(proposal.status == Status::Active, Error::<T>::ProposalNotActive);
 
 			// Check if deadline is passed and handle appropriately.
 			if <frame_system::Pallet<T>>::block_number() >= proposal.deadline {
-				// Set status depending on proposal success.
-				Self::set_proposal_closed_status(&mut proposal);
-
-				// Update proposal state.
-				SubmittedProposals::<T>::set(hash, Some(proposal));
+				// Set status depending on proposal success and unfreeze tokens.
+				Self::do_close_proposal(&hash)?;
 
 				// Dispatch event.
-				Self::deposit_event(Event::ProposalClosed { who, hash });
+				let proposal = Self::get_proposal(hash.clone())?;
+				Self::deposit_event(Event::ProposalClosed {
+					who,
+					hash,
+					status: proposal.status,
+					aye_votes: proposal.aye_votes,
+					nay_votes: proposal.nay_votes,
+				});
 
 				Ok(())
 			} else {
@@ -348,9 +823,10 @@ pub mod pallet {
 		/// Tokens can be unfrozen for an account once a proposal's deadline has
 		/// passed and its status is not active (Passed or Failed). In other words,
 		/// tokens frozen for voting can be returned to an accounts free balance 
-		/// after a proposal is closed.
+		/// after a proposal is closed. Tokens used for conviction votes can only be
+		/// claimed once their extended unlock block has been reached.
 		#[pallet::call_index(6)]
-		#[pallet::weight(Weight::default())]
+		#[pallet::weight(T::WeightInfo::claim_back_tokens(T::MaxProposalsAccountCanVote::get()))]
 		pub fn claim_back_tokens(origin: OriginFor<T>, proposal_hash: T::Hash) -> DispatchResult {
 			// Check that the function was signed and get the signer.
 			let who = ensure_signed(origin)?;
@@ -361,11 +837,36 @@ pub mod pallet {
 			// Check that proposal is closed.
 			Self::check_proposal_is_closed(&proposal)?;
 
-			// Re-calculate the account's frozen balance based on other proposals currently voted on.
-			Self::update_account_frozen_balance_after_claim(who.clone(), proposal_hash.clone())?;
+			// Check that the account still has tokens frozen for the proposal.
+			ensure!(
+				AccountVotes::<T>::contains_key(&who, &proposal_hash),
+				Error::<T>::NoVotesFoundForAccount
+			);
+
+			// Check that the account's vote lock has expired.
+			Self::check_vote_lock_expired(&who, &proposal_hash)?;
+
+			// Re-calculate the account's frozen balance based on other proposals currently voted
+			// on, and dispatch event.
+			Self::do_claim_back_tokens(&who, &proposal_hash)
+		}
+
+		/// Record a balance checkpoint.
+		///
+		/// Votes on a proposal are bounded by the voter's balance at the proposal's snapshot
+		/// block, so accounts record their free balance before proposals they want to vote on
+		/// are submitted.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::checkpoint_balance())]
+		pub fn checkpoint_balance(origin: OriginFor<T>) -> DispatchResult {
+			// Check that the function was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Record the signer's current balance.
+			Self::checkpoint_balance_of(&who);
 
 			// Dispatch event.
-			Self::deposit_event(Event::TokensClaimed { who });
+			Self::deposit_event(Event::BalanceCheckpointed { who });
 
 			Ok(())
 		}
@@ -395,7 +896,7 @@ impl<T: Config> Pallet<T> {
 
 	/// Check if proposal has closed.
 	pub fn check_proposal_is_closed(proposal: &Proposal<T>) -> DispatchResult {
-		if proposal.status == Status::Active {
+		if proposal.status == Status::Active || proposal.status == Status::Pending {
 			Err(Error::<T>::ProposalStillActive.into())
 		} else {
 			Ok(())
@@ -409,6 +910,8 @@ impl<T: Config> Pallet<T> {
 
 		if !RegisteredAccounts::<T>::contains_key(who.clone()) {
 			Err(Error::<T>::NotRegistered.into())
+		} else if fetched.status == Status::Pending {
+			Err(Error::<T>::ProposalPending.into())
 		} else if fetched.status != Status::Active {
 			Err(Error::<T>::VotingOnClosedProposalNotAllowed.into())
 		} else if <frame_system::Pallet<T>>::block_number() >= fetched.deadline {
@@ -446,19 +949,242 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
-	/// Get event from vote.
+	/// Get event from vote, reporting the account's votes and frozen balance after the vote.
 	pub fn get_vote_event(
 		who: T::AccountId,
 		proposal: T::Hash,
 		vote: &Vote,
 		votes: u32,
 	) -> Event<T> {
+		let account_votes = AccountVotes::<T>::get(&who, &proposal).unwrap_or(0);
+		let frozen = Self::frozen_balance_of(&who);
+		let unlock_block =
+			AccountVoteLocks::<T>::get(&who, &proposal).map_or_else(Zero::zero, |l| l.unlock_block);
+
+		match vote {
+			Vote::Aye(_) =>
+				Event::VotedAye { who, proposal, votes, account_votes, frozen, unlock_block },
+			Vote::Nay(_) =>
+				Event::VotedNay { who, proposal, votes, account_votes, frozen, unlock_block },
+		}
+	}
+
+	/// Apply a conviction multiplier to a vote.
+	pub fn apply_conviction(
+		vote: &Vote,
+		conviction: &Option<Conviction>,
+	) -> Result<Vote, DispatchError> {
+		let multiplier = conviction.map_or(1, |c| c.multiplier());
+
+		let weighted = Self::extract_votes(vote)
+			.checked_mul(multiplier)
+			.ok_or(Error::<T>::ArithmeticError)?;
+
 		match vote {
-			Vote::Aye(_) => Event::VotedAye { who, proposal, votes },
-			Vote::Nay(_) => Event::VotedNay { who, proposal, votes },
+			Vote::Aye(_) => Ok(Vote::Aye(weighted)),
+			Vote::Nay(_) => Ok(Vote::Nay(weighted)),
 		}
 	}
 
+	/// Record weighted votes and extend the unlock block in the account's lock schedule.
+	///
+	/// The unlock block is the proposal's deadline, extended by the conviction's lock periods.
+	/// Voting again with a weaker conviction never shortens an existing lock.
+	pub fn update_vote_lock(
+		who: &T::AccountId,
+		proposal_hash: &T::Hash,
+		weighted_votes: u32,
+		conviction: &Option<Conviction>,
+	) -> DispatchResult {
+		let proposal = Self::get_proposal(proposal_hash.clone())?;
+
+		let periods = conviction.map_or(0, |c| c.lock_periods());
+		let unlock_block = proposal
+			.deadline
+			.saturating_add(T::ConvictionBasePeriod::get().saturating_mul(periods.into()));
+
+		AccountVoteLocks::<T>::mutate(who, proposal_hash, |maybe_lock| {
+			let lock = match maybe_lock.take() {
+				Some(lock) => VoteLock {
+					weighted_votes: lock.weighted_votes.saturating_add(weighted_votes),
+					unlock_block: lock.unlock_block.max(unlock_block),
+				},
+				None => VoteLock { weighted_votes, unlock_block },
+			};
+			*maybe_lock = Some(lock);
+		});
+
+		Ok(())
+	}
+
+	/// Check that an account's tokens are no longer locked for a proposal.
+	pub fn check_vote_lock_expired(who: &T::AccountId, proposal_hash: &T::Hash) -> DispatchResult {
+		ensure!(Self::vote_lock_expired(who, proposal_hash), Error::<T>::VoteStillLocked);
+		Ok(())
+	}
+
+	/// Return whether an account's lock for a proposal has expired.
+	pub fn vote_lock_expired(who: &T::AccountId, proposal_hash: &T::Hash) -> bool {
+		match AccountVoteLocks::<T>::get(who, proposal_hash) {
+			Some(lock) => <frame_system::Pallet<T>>::block_number() >= lock.unlock_block,
+			None => true,
+		}
+	}
+
+	/// Register an account to vote, keeping track of the number of registered accounts.
+	pub fn do_register_account(who: &T::AccountId) {
+		if !RegisteredAccounts::<T>::get(who) {
+			RegisteredAccounts::<T>::insert(who, true);
+			RegisteredAccountsCount::<T>::mutate(|count| *count = count.saturating_add(1));
+		}
+
+		Self::checkpoint_balance_of(who);
+	}
+
+	/// Record an account's current free balance as its checkpoint for the current block.
+	pub fn checkpoint_balance_of(who: &T::AccountId) {
+		let now = <frame_system::Pallet<T>>::block_number();
+		let balance = <T::NativeBalance as fungible::Inspect<T::AccountId>>::balance(who);
+
+		BalanceCheckpoints::<T>::mutate(who, |checkpoints| {
+			// Overwrite the checkpoint already recorded in this block.
+			if let Some((block, amount)) = checkpoints.last_mut() {
+				if *block == now {
+					*amount = balance;
+					return
+				}
+			}
+
+			// Merge the two oldest checkpoints to make room for the new one. The lower balance is
+			// kept, so votes are never bounded by more than the account held.
+			if !checkpoints.is_empty() &&
+				checkpoints.len() as u32 >= T::MaxBalanceCheckpoints::get()
+			{
+				if checkpoints.len() > 1 {
+					let (_, merged) = checkpoints.remove(1);
+					if let Some((_, oldest)) = checkpoints.get_mut(0) {
+						*oldest = (*oldest).min(merged);
+					}
+				} else {
+					checkpoints.remove(0);
+				}
+			}
+			let _ = checkpoints.try_push((now, balance));
+		});
+	}
+
+	/// Return an account's balance at a block, taken from its latest checkpoint at or before
+	/// the block. Accounts without such a checkpoint have no balance at the block.
+	pub fn balance_at(who: &T::AccountId, block: BlockNumberFor<T>) -> BalanceOf<T> {
+		BalanceCheckpoints::<T>::get(who)
+			.iter()
+			.rev()
+			.find(|(checkpoint_block, _)| *checkpoint_block <= block)
+			.map_or_else(Zero::zero, |(_, balance)| *balance)
+	}
+
+	/// Return the balance an account can freeze to vote on a proposal: its balance at the
+	/// proposal's snapshot block, bounded by its current free balance.
+	pub fn voting_capacity(who: &T::AccountId, proposal: &Proposal<T>) -> BalanceOf<T> {
+		let current = <T::NativeBalance as fungible::Inspect<T::AccountId>>::balance(who);
+		Self::balance_at(who, proposal.snapshot_block).min(current)
+	}
+
+	/// Return the most votes an account's current balance can pay for, the integer square root
+	/// of its balance. Accounts that are not registered to vote have no voting power.
+	pub fn voting_power(who: &T::AccountId) -> u32 {
+		if !RegisteredAccounts::<T>::get(who) {
+			return 0
+		}
+
+		let balance = <T::NativeBalance as fungible::Inspect<T::AccountId>>::balance(who);
+		balance.integer_sqrt().try_into().unwrap_or(u32::MAX)
+	}
+
+	/// Add an account to a proposal's voter index if it has not voted on the proposal before.
+	pub fn add_proposal_voter(who: &T::AccountId, proposal_hash: &T::Hash) -> DispatchResult {
+		if ProposalVoters::<T>::contains_key(proposal_hash, who) {
+			return Ok(())
+		}
+
+		SubmittedProposals::<T>::try_mutate(proposal_hash, |maybe_proposal| -> DispatchResult {
+			let proposal = maybe_proposal.as_mut().ok_or(Error::<T>::ProposalNotFound)?;
+			ensure!(
+				proposal.voter_count < T::MaxVotersPerProposal::get(),
+				Error::<T>::TooManyVoters
+			);
+			proposal.voter_count = proposal.voter_count.saturating_add(1);
+			Ok(())
+		})?;
+
+		ProposalVoters::<T>::insert(proposal_hash, who, ());
+		Ok(())
+	}
+
+	/// Return the accounts that voted on a proposal.
+	pub fn proposal_voters(proposal_hash: &T::Hash) -> Vec<T::AccountId> {
+		ProposalVoters::<T>::iter_key_prefix(proposal_hash).collect()
+	}
+
+	/// Return the proportion of registered accounts that voted on a proposal.
+	pub fn proposal_turnout(proposal_hash: &T::Hash) -> Result<Perbill, DispatchError> {
+		let proposal = Self::get_proposal(proposal_hash.clone())?;
+		Ok(Self::turnout(&proposal))
+	}
+
+	/// Return the proportion of registered accounts that voted on a proposal.
+	pub fn turnout(proposal: &Proposal<T>) -> Perbill {
+		let registered = RegisteredAccountsCount::<T>::get();
+
+		if registered == 0 {
+			return Perbill::zero()
+		}
+
+		Perbill::from_rational(proposal.voter_count, registered)
+	}
+
+	/// Return the parameters of a track.
+	pub fn track_info(track: TrackId) -> Result<TrackInfo<BalanceOf<T>>, DispatchError> {
+		T::Tracks::get()
+			.into_iter()
+			.find(|(id, _)| *id == track)
+			.map(|(_, info)| info)
+			.ok_or(Error::<T>::UnknownTrack.into())
+	}
+
+	/// Unfreeze the tokens of every voter on a proposal whose vote lock has expired.
+	pub fn unfreeze_expired_voters(proposal_hash: &T::Hash) -> DispatchResult {
+		for who in ProposalVoters::<T>::iter_key_prefix(proposal_hash) {
+			if AccountVotes::<T>::contains_key(&who, proposal_hash) &&
+				Self::vote_lock_expired(&who, proposal_hash)
+			{
+				Self::do_claim_back_tokens(&who, proposal_hash)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Unfreeze the tokens an account froze for its votes on a proposal.
+	pub fn do_claim_back_tokens(who: &T::AccountId, proposal_hash: &T::Hash) -> DispatchResult {
+		let frozen_before = Self::frozen_balance_of(who);
+		Self::update_account_frozen_balance_after_claim(who.clone(), proposal_hash.clone())?;
+		let frozen = Self::frozen_balance_of(who);
+
+		Self::deposit_event(Event::TokensClaimed {
+			who: who.clone(),
+			proposal: proposal_hash.clone(),
+			amount: frozen_before.saturating_sub(frozen),
+			frozen,
+		});
+		Ok(())
+	}
+
+	/// Return the balance frozen for an account's votes.
+	pub fn frozen_balance_of(who: &T::AccountId) -> BalanceOf<T> {
+		T::NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), who)
+	}
+
 	/// Store account's votes for a particular proposal in state.
 	pub fn persist_account_vote_tokens(
 		who: T::AccountId,
@@ -510,6 +1236,15 @@ impl<T: Config> Pallet<T> {
 			return Err(Error::<T>::NotEnoughTokens.into());
 		}
 
+		// Check that the tokens for this proposal's votes were held at its snapshot block.
+		let proposal = Self::get_proposal(proposal_hash.clone())?;
+		let votes = AccountVotes::<T>::get(who.clone(), proposal_hash.clone()).unwrap_or(0);
+		let required = votes.checked_pow(2).ok_or(Error::<T>::ArithmeticError)?;
+		ensure!(
+			Self::voting_capacity(&who, &proposal) >= required.into(),
+			Error::<T>::NotEnoughTokens
+		);
+
 		// Update AccountProposalsMap vector for this account.
 		AccountProposalsMap::<T>::insert(who.clone(), cloned);
 
@@ -533,6 +1268,9 @@ impl<T: Config> Pallet<T> {
 			AccountVotes::<T>::remove(who.clone(), proposal_hash.clone());
 		}
 
+		// Remove entry from the account's lock schedule.
+		AccountVoteLocks::<T>::remove(who.clone(), proposal_hash.clone());
+
 		// Remove entry from vector in AccountProposalsMap.
 		let mut proposals = AccountProposalsMap::<T>::get(who.clone()).unwrap_or(BoundedVec::new());
 
@@ -566,6 +1304,9 @@ impl<T: Config> Pallet<T> {
 			T::NativeBalance::thaw(&FreezeReason::ProposalVote.into(), &who)?;
 		}
 
+		// Record the account's balance for future proposals.
+		Self::checkpoint_balance_of(&who);
+
 		Ok(())
 	}
 
@@ -585,12 +1326,374 @@ impl<T: Config> Pallet<T> {
 		Ok(frozen_balance)
 	}
 
+	/// Schedule a status transition of a proposal in a block, returning the block it was
+	/// scheduled in.
+	///
+	/// If `MaxScheduledPerBlock` transitions are already scheduled in the block, the transition
+	/// is scheduled in the next block with space in its agenda, up to `MaxScheduleLookahead`
+	/// blocks from the given one. Transitions are applied once their block is reached, so a
+	/// later block only delays them.
+	pub fn schedule(
+		block: BlockNumberFor<T>,
+		proposal_hash: &T::Hash,
+	) -> Result<BlockNumberFor<T>, DispatchError> {
+		let mut block = block;
+		for _ in 0..T::MaxScheduleLookahead::get() {
+			if Agenda::<T>::try_mutate(block, |agenda| agenda.try_push(proposal_hash.clone()))
+				.is_ok()
+			{
+				return Ok(block)
+			}
+			block = match block.checked_add(&One::one()) {
+				Some(next) => next,
+				None => break,
+			};
+		}
+		Err(Error::<T>::AgendaFull.into())
+	}
+
+	/// The weight of searching the agendas for space in `schedule`, on top of the single agenda
+	/// read and write counted in the weights of the calls that schedule transitions.
+	pub fn schedule_weight() -> Weight {
+		T::DbWeight::get().reads(T::MaxScheduleLookahead::get().saturating_sub(1).into())
+	}
+
+	/// Apply the status transitions scheduled up to `now`, starting from the earliest block whose
+	/// agenda was not fully serviced, while they fit in `MaxServiceWeight`. Transitions that
+	/// don't fit are left in their agenda and applied in the following blocks.
+	pub(crate) fn service_agenda(now: BlockNumberFor<T>) -> Weight {
+		let db = T::DbWeight::get();
+		let limit = T::MaxServiceWeight::get();
+
+		// Reading and updating the earliest incomplete block.
+		let mut used = db.reads_writes(1, 1);
+		let mut block = IncompleteSince::<T>::take().unwrap_or(now);
+		while block <= now {
+			// Reading the agenda and writing back the transitions left in it.
+			let agenda_weight = db.reads_writes(1, 1);
+			if used.saturating_add(agenda_weight).any_gt(limit) {
+				IncompleteSince::<T>::put(block);
+				return used
+			}
+			used = used.saturating_add(agenda_weight);
+
+			let mut agenda = Agenda::<T>::take(block).into_inner();
+			let mut serviced = 0;
+			for hash in agenda.iter() {
+				match Self::service_proposal(hash, limit.saturating_sub(used)) {
+					Ok(weight) => used = used.saturating_add(weight),
+					// A transition that doesn't fit in an empty block never will, so it is skipped
+					// rather than holding up the rest of the agenda.
+					Err(needed) if needed.any_gt(limit) => {
+						used = used.saturating_add(db.reads(2));
+						Self::deposit_event(Event::ProposalOverweight { proposal: hash.clone() });
+					},
+					Err(_) => break,
+				}
+				serviced += 1;
+			}
+			if serviced < agenda.len() {
+				let remaining = BoundedVec::truncate_from(agenda.split_off(serviced));
+				Agenda::<T>::insert(block, remaining);
+				IncompleteSince::<T>::put(block);
+				return used
+			}
+			block = block.saturating_add(One::one());
+		}
+
+		used
+	}
+
+	/// Apply the status transition a scheduled proposal is due for if its weight fits in
+	/// `remaining`, returning the weight used. Otherwise nothing is done, and the weight the
+	/// transition needs is returned as the error.
+	pub fn service_proposal(proposal_hash: &T::Hash, remaining: Weight) -> Result<Weight, Weight> {
+		let fits = |weight: Weight| if weight.any_gt(remaining) { Err(weight) } else { Ok(weight) };
+		let proposal = match SubmittedProposals::<T>::get(proposal_hash) {
+			Some(proposal) => proposal,
+			None => return fits(T::DbWeight::get().reads(1)),
+		};
+
+		match proposal.status {
+			Status::Pending => {
+				let weight = fits(T::WeightInfo::activate_proposal())?;
+				let _ = Self::do_activate_proposal(proposal_hash);
+				Ok(weight)
+			},
+			Status::Active if <frame_system::Pallet<T>>::block_number() >= proposal.deadline => {
+				let weight = fits(
+					T::WeightInfo::close_proposal(proposal.voter_count)
+						.saturating_add(Self::schedule_weight()),
+				)?;
+				// A proposal that fails to close is left active, so it can be closed manually.
+				let _ = frame_support::storage::with_storage_layer(|| {
+					Self::do_close_proposal(proposal_hash)
+				});
+				Ok(weight)
+			},
+			Status::Passed => match Self::call_weight(proposal_hash) {
+				Some(call_weight) => {
+					let weight = fits(T::WeightInfo::enact_proposal().saturating_add(call_weight))?;
+					let _ = Self::do_enact_proposal(proposal_hash);
+					Ok(weight)
+				},
+				None => fits(T::DbWeight::get().reads(2)),
+			},
+			_ => fits(T::DbWeight::get().reads(2)),
+		}
+	}
+
+	/// Return the dispatch weight of the call attached to a proposal, or zero if it can't be
+	/// decoded, or `None` if the proposal has no attached call.
+	pub fn call_weight(proposal_hash: &T::Hash) -> Option<Weight> {
+		ProposalCalls::<T>::get(proposal_hash).map(|encoded| {
+			<T as Config>::RuntimeCall::decode(&mut &encoded[..])
+				.map(|call| call.get_dispatch_info().weight)
+				.unwrap_or_default()
+		})
+	}
+
+	/// Open a pending proposal for voting.
+	pub fn do_activate_proposal(proposal_hash: &T::Hash) -> DispatchResult {
+		let mut proposal = Self::get_proposal(proposal_hash.clone())?;
+		proposal.status = Status::Active;
+		SubmittedProposals::<T>::insert(proposal_hash, proposal);
+
+		Self::deposit_event(Event::ProposalActivated { proposal: proposal_hash.clone() });
+		Ok(())
+	}
+
+	/// Close a proposal, unfreezing the tokens of voters that are no longer locked and
+	/// returning the owner's deposit.
+	///
+	/// The attached call of a passed proposal is scheduled for enactment, while the call of a
+	/// failed proposal is removed.
+	pub fn do_close_proposal(proposal_hash: &T::Hash) -> DispatchResult {
+		let mut proposal = Self::get_proposal(proposal_hash.clone())?;
+
+		// Set status depending on proposal success.
+		Self::set_proposal_closed_status(&mut proposal)?;
+		let passed = proposal.status == Status::Passed;
+		let (aye_votes, nay_votes) = (proposal.aye_votes, proposal.nay_votes);
+		let turnout = Self::turnout(&proposal);
+
+		// Return the owner's deposit.
+		if !proposal.deposit.is_zero() {
+			T::NativeBalance::release(
+				&HoldReason::ProposalDeposit.into(),
+				&proposal.owner,
+				proposal.deposit,
+				Precision::BestEffort,
+			)?;
+		}
+		SubmittedProposals::<T>::insert(proposal_hash, proposal);
+
+		// Unfreeze the tokens of voters that are no longer locked.
+		Self::unfreeze_expired_voters(proposal_hash)?;
+
+		if passed {
+			if ProposalCalls::<T>::contains_key(proposal_hash) {
+				let enactment_block = <frame_system::Pallet<T>>::block_number()
+					.saturating_add(T::EnactmentDelay::get());
+				Self::schedule(enactment_block, proposal_hash)?;
+			}
+			Self::deposit_event(Event::ProposalPassed {
+				proposal: proposal_hash.clone(),
+				aye_votes,
+				nay_votes,
+				turnout,
+			});
+		} else {
+			ProposalCalls::<T>::remove(proposal_hash);
+			Self::deposit_event(Event::ProposalFailed {
+				proposal: proposal_hash.clone(),
+				aye_votes,
+				nay_votes,
+				turnout,
+			});
+		}
+
+		Ok(())
+	}
+
+	/// Dispatch the call attached to a passed proposal with its track's origin, returning the
+	/// weight of the call. Proposals on tracks that no longer exist are dispatched with the
+	/// track's `Origin::Track` rather than root.
+	pub fn do_enact_proposal(proposal_hash: &T::Hash) -> Result<Weight, DispatchError> {
+		let mut proposal = Self::get_proposal(proposal_hash.clone())?;
+		let encoded = ProposalCalls::<T>::take(proposal_hash).ok_or(Error::<T>::CallNotFound)?;
+		let origin: <T as frame_system::Config>::RuntimeOrigin =
+			match Self::track_info(proposal.track).map(|info| info.enactment) {
+				Ok(EnactmentOrigin::Root) => frame_system::RawOrigin::Root.into(),
+				_ => T::PalletsOrigin::from(Origin::Track(proposal.track)).into(),
+			};
+
+		proposal.status = Status::Enacted;
+		SubmittedProposals::<T>::insert(proposal_hash, proposal);
+
+		let (result, weight) = match <T as Config>::RuntimeCall::decode(&mut &encoded[..]) {
+			Ok(call) => {
+				let weight = call.get_dispatch_info().weight;
+				let result = call.dispatch(origin).map(|_| ()).map_err(|e| e.error);
+				(result, weight)
+			},
+			Err(_) => (Err(Error::<T>::UndecodableCall.into()), Weight::zero()),
+		};
+
+		Self::deposit_event(Event::ProposalEnacted { proposal: proposal_hash.clone(), result });
+		Ok(weight)
+	}
+
 	/// Set proposal closed status.
-	pub fn set_proposal_closed_status(proposal: &mut Proposal<T>) {
-		if proposal.aye_votes > proposal.nay_votes {
+	///
+	/// A proposal passes if its turnout reaches its track's quorum and its aye votes exceed the
+	/// track's approval threshold.
+	pub fn set_proposal_closed_status(proposal: &mut Proposal<T>) -> DispatchResult {
+		let info = Self::track_info(proposal.track)?;
+		let total_votes = proposal.aye_votes.saturating_add(proposal.nay_votes);
+
+		if Self::turnout(proposal) >= info.quorum &&
+			proposal.aye_votes > info.approval.mul_floor(total_votes)
+		{
 			proposal.status = Status::Passed;
 		} else {
 			proposal.status = Status::Failed;
 		}
+
+		Ok(())
+	}
+}
+
+/// The balances pallet's account store, wrapping `Store`, usually `frame_system::Pallet`, to
+/// record a balance checkpoint whenever the balance of an account with checkpoints changes.
+/// Accounts without checkpoints have no balance at any snapshot block, so are left alone.
+///
+/// This adds a read to every balance change, and a write for accounts with checkpoints, that the
+/// balances pallet's weights don't include.
+pub struct CheckpointedAccountStore<T, Store>(PhantomData<(T, Store)>);
+
+impl<T: Config, Data: Default, Store: StoredMap<T::AccountId, Data>> StoredMap<T::AccountId, Data>
+	for CheckpointedAccountStore<T, Store>
+{
+	fn get(who: &T::AccountId) -> Data {
+		Store::get(who)
+	}
+
+	fn try_mutate_exists<R, E: From<DispatchError>>(
+		who: &T::AccountId,
+		f: impl FnOnce(&mut Option<Data>) -> Result<R, E>,
+	) -> Result<R, E> {
+		let result = Store::try_mutate_exists(who, f)?;
+		if BalanceCheckpoints::<T>::contains_key(who) {
+			Pallet::<T>::checkpoint_balance_of(who);
+		}
+		Ok(result)
+	}
+}
+
+/// Ensures the origin is a passed proposal on the track `Track`, which must be enacted with
+/// `EnactmentOrigin::Track`.
+pub struct EnsureTrack<Track>(PhantomData<Track>);
+
+impl<O: Into<Result<Origin, O>> + From<Origin>, Track: Get<TrackId>> EnsureOrigin<O>
+	for EnsureTrack<Track>
+{
+	type Success = ();
+
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		o.into().and_then(|o| match o {
+			Origin::Track(track) if track == Track::get() => Ok(()),
+			o => Err(O::from(o)),
+		})
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn try_successful_origin() -> Result<O, ()> {
+		Ok(O::from(Origin::Track(Track::get())))
+	}
+}
+
+#[cfg(any(feature = "try-runtime", test))]
+impl<T: Config> Pallet<T> {
+	/// Ensure the correctness of the pallet's state.
+	///
+	/// Invariants:
+	/// * The votes of an active proposal equal the sum of the weighted votes in its voters' lock
+	///   schedules. Without conviction, weighted votes equal the votes in `AccountVotes`. Tokens
+	///   can only be claimed back from closed proposals, so closed proposals are skipped.
+	/// * Every proposal in an account's `AccountProposalsMap` entry has an `AccountVotes` entry.
+	/// * The amount frozen under `FreezeReason::ProposalVote` equals `get_frozen_balance`.
+	/// * The voter count of a proposal equals the number of accounts in its voter index.
+	/// * `RegisteredAccountsCount` equals the number of registered accounts.
+	/// * Every attached call belongs to a proposal that has not failed or been enacted.
+	/// * The amount held from an account under `HoldReason::ProposalDeposit` equals the deposits of
+	///   its pending and active proposals.
+	pub fn do_try_state() -> Result<(), TryRuntimeError> {
+		use frame_support::sp_std::collections::btree_map::BTreeMap;
+
+		let mut weighted_votes: BTreeMap<T::Hash, u32> = BTreeMap::new();
+		for (_, hash, lock) in AccountVoteLocks::<T>::iter() {
+			let total = weighted_votes.entry(hash).or_default();
+			*total = total.saturating_add(lock.weighted_votes);
+		}
+
+		for (hash, proposal) in SubmittedProposals::<T>::iter() {
+			ensure!(
+				proposal.voter_count as usize ==
+					ProposalVoters::<T>::iter_key_prefix(&hash).count(),
+				"Proposal voter count does not match its voter index"
+			);
+
+			if proposal.status != Status::Active {
+				continue
+			}
+
+			let expected = weighted_votes.get(&hash).copied().unwrap_or(0);
+			ensure!(
+				proposal.aye_votes.saturating_add(proposal.nay_votes) == expected,
+				"Proposal votes do not match the votes of its voters"
+			);
+		}
+
+		for (who, proposals) in AccountProposalsMap::<T>::iter() {
+			for hash in proposals.iter() {
+				ensure!(
+					AccountVotes::<T>::contains_key(&who, hash),
+					"Proposal voted on by account has no account votes entry"
+				);
+			}
+
+			let expected = Self::get_frozen_balance(&who, &proposals)?;
+			let frozen = T::NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &who);
+			ensure!(frozen == expected.into(), "Frozen balance does not match account votes");
+		}
+
+		let registered = RegisteredAccounts::<T>::iter_values().filter(|r| *r).count();
+		ensure!(
+			RegisteredAccountsCount::<T>::get() as usize == registered,
+			"Registered accounts count does not match registered accounts"
+		);
+
+		let mut deposits: BTreeMap<T::AccountId, BalanceOf<T>> = BTreeMap::new();
+		for proposal in SubmittedProposals::<T>::iter_values() {
+			let total = deposits.entry(proposal.owner).or_insert_with(Zero::zero);
+			if proposal.status == Status::Pending || proposal.status == Status::Active {
+				*total = total.saturating_add(proposal.deposit);
+			}
+		}
+		for (who, expected) in deposits {
+			let held = T::NativeBalance::balance_on_hold(&HoldReason::ProposalDeposit.into(), &who);
+			ensure!(held == expected, "Held deposit does not match open proposal deposits");
+		}
+
+		for hash in ProposalCalls::<T>::iter_keys() {
+			let status = SubmittedProposals::<T>::get(&hash).map(|p| p.status);
+			ensure!(
+				matches!(status, Some(Status::Pending | Status::Active | Status::Passed)),
+				"Attached call belongs to a missing, failed or enacted proposal"
+			);
+		}
+
+		Ok(())
 	}
 }
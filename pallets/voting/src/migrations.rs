@@ -0,0 +1,464 @@
+//! Storage migrations for the voting pallet.
+
+use super::*;
+use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+#[cfg(feature = "try-runtime")]
+use frame_support::sp_runtime::TryRuntimeError;
+#[cfg(feature = "try-runtime")]
+use frame_support::sp_std::vec::Vec;
+
+pub mod v1 {
+	use super::*;
+
+	/// Proposal layout used up to storage version 1.
+	#[derive(Clone, PartialEq, DebugNoBound, Encode, Decode)]
+	pub struct ProposalV1<T: Config> {
+		pub status: Status,
+		pub deadline: BlockNumberFor<T>,
+		pub owner: T::AccountId,
+		pub aye_votes: u32,
+		pub nay_votes: u32,
+	}
+
+	/// `SubmittedProposals` with the proposal layout used up to storage version 1.
+	#[frame_support::storage_alias]
+	pub type SubmittedProposals<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, <T as frame_system::Config>::Hash, ProposalV1<T>>;
+
+	/// Migrate pallet storage from version 0 to version 1.
+	///
+	/// Votes cast before conviction voting was introduced have no entry in `AccountVoteLocks`.
+	/// An entry is added for each of them, using the raw votes as weighted votes and the
+	/// proposal's deadline as unlock block, which matches a vote cast without conviction.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 0u64;
+
+			for (who, hash, votes) in AccountVotes::<T>::iter() {
+				reads = reads.saturating_add(2);
+
+				if AccountVoteLocks::<T>::contains_key(&who, &hash) {
+					continue
+				}
+
+				if let Some(proposal) = SubmittedProposals::<T>::get(&hash) {
+					let lock = VoteLock { weighted_votes: votes, unlock_block: proposal.deadline };
+					AccountVoteLocks::<T>::insert(&who, &hash, lock);
+					writes = writes.saturating_add(1);
+				}
+			}
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(reads, writes.saturating_add(1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let votes = AccountVotes::<T>::iter().count() as u32;
+			Ok(votes.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let votes_before: u32 = Decode::decode(&mut &state[..])
+				.map_err(|_| "Failed to decode the number of votes before the upgrade")?;
+
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 1,
+				"Storage version was not updated by the migration"
+			);
+			ensure!(
+				AccountVotes::<T>::iter().count() as u32 == votes_before,
+				"Account votes changed during the migration"
+			);
+
+			for (who, hash, _) in AccountVotes::<T>::iter() {
+				if SubmittedProposals::<T>::contains_key(&hash) {
+					ensure!(
+						AccountVoteLocks::<T>::contains_key(&who, &hash),
+						"Vote has no lock schedule entry after the migration"
+					);
+				}
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v2 {
+	use super::*;
+	use frame_support::sp_std::collections::btree_map::BTreeMap;
+
+	/// Proposal layout used in storage version 2.
+	#[derive(Clone, PartialEq, DebugNoBound, Encode, Decode)]
+	pub struct ProposalV2<T: Config> {
+		pub status: Status,
+		pub deadline: BlockNumberFor<T>,
+		pub owner: T::AccountId,
+		pub aye_votes: u32,
+		pub nay_votes: u32,
+		pub voter_count: u32,
+	}
+
+	/// `SubmittedProposals` with the proposal layout used in storage version 2.
+	#[frame_support::storage_alias]
+	pub type SubmittedProposals<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, <T as frame_system::Config>::Hash, ProposalV2<T>>;
+
+	/// Migrate pallet storage from version 1 to version 2.
+	///
+	/// Builds the `ProposalVoters` index from `AccountVotes`, adds a voter count to every
+	/// proposal and counts the registered accounts. Voters that already claimed back their
+	/// tokens have no `AccountVotes` entry left, so they are missing from the index.
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 0u64;
+
+			let mut voter_counts: BTreeMap<T::Hash, u32> = BTreeMap::new();
+			for (who, hash, _) in AccountVotes::<T>::iter() {
+				ProposalVoters::<T>::insert(&hash, &who, ());
+				let count = voter_counts.entry(hash).or_default();
+				*count = count.saturating_add(1);
+				reads = reads.saturating_add(1);
+				writes = writes.saturating_add(1);
+			}
+
+			SubmittedProposals::<T>::translate::<v1::ProposalV1<T>, _>(|hash, old| {
+				reads = reads.saturating_add(1);
+				writes = writes.saturating_add(1);
+				Some(ProposalV2 {
+					status: old.status,
+					deadline: old.deadline,
+					owner: old.owner,
+					aye_votes: old.aye_votes,
+					nay_votes: old.nay_votes,
+					voter_count: voter_counts.get(&hash).copied().unwrap_or(0),
+				})
+			});
+
+			let mut registered = 0u32;
+			for is_registered in RegisteredAccounts::<T>::iter_values() {
+				reads = reads.saturating_add(1);
+				if is_registered {
+					registered = registered.saturating_add(1);
+				}
+			}
+			RegisteredAccountsCount::<T>::put(registered);
+
+			StorageVersion::new(2).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(reads, writes.saturating_add(2))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let proposals = v1::SubmittedProposals::<T>::iter().count() as u32;
+			Ok(proposals.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let proposals_before: u32 = Decode::decode(&mut &state[..])
+				.map_err(|_| "Failed to decode the number of proposals before the upgrade")?;
+
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 2,
+				"Storage version was not updated by the migration"
+			);
+			ensure!(
+				SubmittedProposals::<T>::iter().count() as u32 == proposals_before,
+				"Proposals were lost during the migration"
+			);
+
+			for (hash, proposal) in SubmittedProposals::<T>::iter() {
+				ensure!(
+					proposal.voter_count as usize ==
+						ProposalVoters::<T>::iter_key_prefix(&hash).count(),
+					"Proposal voter count does not match its voter index"
+				);
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v3 {
+	use super::*;
+
+	/// Proposal layout used in storage version 3.
+	#[derive(Clone, PartialEq, DebugNoBound, Encode, Decode)]
+	pub struct ProposalV3<T: Config> {
+		pub status: Status,
+		pub deadline: BlockNumberFor<T>,
+		pub owner: T::AccountId,
+		pub aye_votes: u32,
+		pub nay_votes: u32,
+		pub voter_count: u32,
+		pub snapshot_block: BlockNumberFor<T>,
+	}
+
+	/// `SubmittedProposals` with the proposal layout used in storage version 3.
+	#[frame_support::storage_alias]
+	pub type SubmittedProposals<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, <T as frame_system::Config>::Hash, ProposalV3<T>>;
+
+	/// Migrate pallet storage from version 2 to version 3.
+	///
+	/// Sets the snapshot block of every proposal to the block of the upgrade, and records a
+	/// balance checkpoint for every registered account in that block, so registered accounts
+	/// can keep voting on proposals submitted before the upgrade.
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 0u64;
+			let now = <frame_system::Pallet<T>>::block_number();
+
+			SubmittedProposals::<T>::translate::<v2::ProposalV2<T>, _>(|_, old| {
+				reads = reads.saturating_add(1);
+				writes = writes.saturating_add(1);
+				Some(ProposalV3 {
+					status: old.status,
+					deadline: old.deadline,
+					owner: old.owner,
+					aye_votes: old.aye_votes,
+					nay_votes: old.nay_votes,
+					voter_count: old.voter_count,
+					snapshot_block: now,
+				})
+			});
+
+			for (who, is_registered) in RegisteredAccounts::<T>::iter() {
+				reads = reads.saturating_add(1);
+				if is_registered {
+					Pallet::<T>::checkpoint_balance_of(&who);
+					reads = reads.saturating_add(2);
+					writes = writes.saturating_add(1);
+				}
+			}
+
+			StorageVersion::new(3).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(reads, writes.saturating_add(1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let proposals = v2::SubmittedProposals::<T>::iter().count() as u32;
+			Ok(proposals.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let proposals_before: u32 = Decode::decode(&mut &state[..])
+				.map_err(|_| "Failed to decode the number of proposals before the upgrade")?;
+
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 3,
+				"Storage version was not updated by the migration"
+			);
+			ensure!(
+				SubmittedProposals::<T>::iter().count() as u32 == proposals_before,
+				"Proposals were lost during the migration"
+			);
+
+			for (who, is_registered) in RegisteredAccounts::<T>::iter() {
+				ensure!(
+					!is_registered || !BalanceCheckpoints::<T>::get(&who).is_empty(),
+					"Registered account has no balance checkpoint"
+				);
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v4 {
+	use super::*;
+
+	/// Migrate pallet storage from version 3 to version 4.
+	///
+	/// Assigns every proposal to the first configured track. Proposals submitted before tracks
+	/// were introduced hold no deposit.
+	pub struct MigrateToV4<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV4<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 3 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 0u64;
+			let track = T::Tracks::get().first().map_or(0, |(id, _)| *id);
+
+			SubmittedProposals::<T>::translate::<v3::ProposalV3<T>, _>(|_, old| {
+				reads = reads.saturating_add(1);
+				writes = writes.saturating_add(1);
+				Some(Proposal {
+					status: old.status,
+					deadline: old.deadline,
+					owner: old.owner,
+					aye_votes: old.aye_votes,
+					nay_votes: old.nay_votes,
+					voter_count: old.voter_count,
+					snapshot_block: old.snapshot_block,
+					track,
+					deposit: Zero::zero(),
+				})
+			});
+
+			StorageVersion::new(4).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(reads, writes.saturating_add(1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let proposals = v3::SubmittedProposals::<T>::iter().count() as u32;
+			Ok(proposals.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let proposals_before: u32 = Decode::decode(&mut &state[..])
+				.map_err(|_| "Failed to decode the number of proposals before the upgrade")?;
+
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 4,
+				"Storage version was not updated by the migration"
+			);
+			ensure!(
+				SubmittedProposals::<T>::iter().count() as u32 == proposals_before,
+				"Proposals were lost during the migration"
+			);
+
+			for proposal in SubmittedProposals::<T>::iter_values() {
+				ensure!(
+					Pallet::<T>::track_info(proposal.track).is_ok(),
+					"Proposal was assigned to an unknown track"
+				);
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v5 {
+	use super::*;
+	use frame_support::sp_std::collections::btree_set::BTreeSet;
+
+	/// Migrate pallet storage from version 4 to version 5.
+	///
+	/// Proposals submitted before the `Agenda` was introduced are never activated, closed or
+	/// enacted automatically. Each live proposal without a scheduled transition is scheduled:
+	/// pending proposals for activation and closing, active proposals for closing and passed
+	/// proposals with an attached call for enactment. Transitions due in the past are
+	/// scheduled in the next block.
+	pub struct MigrateToV5<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV5<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 4 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 0u64;
+			let next_block = <frame_system::Pallet<T>>::block_number().saturating_add(One::one());
+
+			let scheduled: BTreeSet<T::Hash> = Agenda::<T>::iter_values()
+				.inspect(|_| reads = reads.saturating_add(1))
+				.flatten()
+				.collect();
+
+			for (hash, proposal) in SubmittedProposals::<T>::iter() {
+				reads = reads.saturating_add(1);
+				if scheduled.contains(&hash) {
+					continue
+				}
+
+				let blocks = match proposal.status {
+					Status::Pending => [Some(proposal.snapshot_block), Some(proposal.deadline)],
+					Status::Active => [Some(proposal.deadline), None],
+					Status::Passed => {
+						reads = reads.saturating_add(1);
+						if ProposalCalls::<T>::contains_key(&hash) {
+							[Some(next_block), None]
+						} else {
+							[None, None]
+						}
+					},
+					_ => [None, None],
+				};
+
+				// Scheduling only fails if more transitions are due in the same block than the
+				// agendas of the `MaxScheduleLookahead` blocks from it can hold.
+				for block in blocks.into_iter().flatten() {
+					let lookahead = u64::from(T::MaxScheduleLookahead::get());
+					reads = reads.saturating_add(lookahead);
+					if Pallet::<T>::schedule(block.max(next_block), &hash).is_ok() {
+						writes = writes.saturating_add(1);
+					}
+				}
+			}
+
+			StorageVersion::new(5).put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(reads, writes.saturating_add(1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let proposals = SubmittedProposals::<T>::iter().count() as u32;
+			Ok(proposals.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let proposals_before: u32 = Decode::decode(&mut &state[..])
+				.map_err(|_| "Failed to decode the number of proposals before the upgrade")?;
+
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 5,
+				"Storage version was not updated by the migration"
+			);
+			ensure!(
+				SubmittedProposals::<T>::iter().count() as u32 == proposals_before,
+				"Proposals were lost during the migration"
+			);
+
+			let scheduled: BTreeSet<T::Hash> = Agenda::<T>::iter_values().flatten().collect();
+			for (hash, proposal) in SubmittedProposals::<T>::iter() {
+				let live = match proposal.status {
+					Status::Pending | Status::Active => true,
+					Status::Passed => ProposalCalls::<T>::contains_key(&hash),
+					_ => false,
+				};
+				ensure!(!live || scheduled.contains(&hash), "Live proposal was not scheduled");
+			}
+
+			Ok(())
+		}
+	}
+}
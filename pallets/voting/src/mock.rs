@@ -1,12 +1,14 @@
 use crate as pallet_voting;
+use crate::{EnactmentOrigin, SubmitterOrigin, TrackId, TrackInfo};
 use frame_support::{
 	parameter_types,
 	traits::{ConstU128, ConstU16, ConstU32, ConstU64},
+	weights::Weight,
 };
 use sp_core::H256;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
-	BuildStorage,
+	BuildStorage, Perbill,
 };
 
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -53,22 +55,66 @@ impl pallet_balances::Config for Test {
 	type DustRemoval = ();
 	type RuntimeEvent = RuntimeEvent;
 	type ExistentialDeposit = ConstU128<1>;
-	type AccountStore = System;
+	type AccountStore = pallet_voting::CheckpointedAccountStore<Test, System>;
 	type WeightInfo = ();
 	type MaxLocks = ConstU32<10>;
 	type MaxReserves = ();
 	type ReserveIdentifier = [u8; 8];
-	type RuntimeHoldReason = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type FreezeIdentifier = RuntimeFreezeReason;
 	type MaxHolds = ConstU32<10>;
 	type MaxFreezes = ConstU32<10>;
 }
 
 parameter_types! {
-	pub const ProposalDurationTier1: u8 = 1;
-	pub const ProposalDurationTier2: u8 = 2;
-	pub const ProposalDurationTier3: u8 = 4;
+	pub Tracks: Vec<(TrackId, TrackInfo<Balance>)> = vec![
+		(
+			0,
+			TrackInfo {
+				name: "signal",
+				durations: [1, 2, 4],
+				quorum: Perbill::zero(),
+				approval: Perbill::from_percent(50),
+				deposit: 0,
+				submitter: SubmitterOrigin::Signed,
+				enactment: EnactmentOrigin::Root,
+			},
+		),
+		(
+			1,
+			TrackInfo {
+				name: "treasury",
+				durations: [2, 4, 8],
+				quorum: Perbill::from_percent(50),
+				approval: Perbill::from_percent(60),
+				deposit: 10,
+				submitter: SubmitterOrigin::Registered,
+				enactment: EnactmentOrigin::Root,
+			},
+		),
+		(
+			2,
+			TrackInfo {
+				name: "parameters",
+				durations: [1, 2, 4],
+				quorum: Perbill::zero(),
+				approval: Perbill::from_percent(50),
+				deposit: 0,
+				submitter: SubmitterOrigin::Signed,
+				enactment: EnactmentOrigin::Track,
+			},
+		),
+	];
 	pub const MaxProposalsAccountCanVote: u32 = 3;
+	pub const ConvictionBasePeriod: u64 = 1_000;
+	pub const MaxVotersPerProposal: u32 = 2;
+	pub const MaxBalanceCheckpoints: u32 = 3;
+	pub static DiscussionPeriod: u64 = 0;
+	pub const EnactmentDelay: u64 = 10;
+	pub const MaxCallLength: u32 = 128;
+	pub const MaxScheduledPerBlock: u32 = 5;
+	pub const MaxScheduleLookahead: u32 = 2;
+	pub static MaxServiceWeight: Weight = Weight::MAX;
 }
 
 impl pallet_voting::Config for Test {
@@ -76,13 +122,48 @@ impl pallet_voting::Config for Test {
 	type NativeBalance = Balances;
 	type BlockNumberToBalance = sp_runtime::traits::ConvertInto;
 	type RuntimeFreezeReason = RuntimeFreezeReason;
-	type ProposalDurationTier1 = ProposalDurationTier1;
-	type ProposalDurationTier2 = ProposalDurationTier2;
-	type ProposalDurationTier3 = ProposalDurationTier3;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type RegisterOrigin = frame_system::EnsureRoot<u64>;
+	type Tracks = Tracks;
 	type MaxProposalsAccountCanVote = MaxProposalsAccountCanVote;
+	type ConvictionBasePeriod = ConvictionBasePeriod;
+	type MaxVotersPerProposal = MaxVotersPerProposal;
+	type MaxBalanceCheckpoints = MaxBalanceCheckpoints;
+	type DiscussionPeriod = DiscussionPeriod;
+	type EnactmentDelay = EnactmentDelay;
+	type MaxCallLength = MaxCallLength;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type MaxScheduleLookahead = MaxScheduleLookahead;
+	type MaxServiceWeight = MaxServiceWeight;
+	type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	frame_system::GenesisConfig::<Test>::default().build_storage().unwrap().into()
 }
+
+// Build genesis storage, execute the test and check the pallet's invariants afterwards.
+pub fn build_and_execute(test: impl FnOnce()) {
+	new_test_ext().execute_with(|| {
+		test();
+		Voting::do_try_state().expect("All invariants must hold after a test");
+	});
+}
+
+// Build genesis storage with the given balances and voting pallet genesis config.
+pub fn new_test_ext_with_genesis(
+	balances: Vec<(u64, Balance)>,
+	voting: pallet_voting::GenesisConfig<Test>,
+) -> sp_io::TestExternalities {
+	RuntimeGenesisConfig {
+		balances: pallet_balances::GenesisConfig { balances },
+		voting,
+		..Default::default()
+	}
+	.build_storage()
+	.unwrap()
+	.into()
+}
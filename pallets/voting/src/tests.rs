@@ -1,12 +1,28 @@
-use crate::pallet::{AccountVotes, Duration, Proposal, Status, SubmittedProposals, Vote};
-use crate::{mock::*, Error, FreezeReason, RegisteredAccounts};
+use crate::pallet::{
+	AccountVoteLocks, AccountVotes, Conviction, Duration, Proposal, Status, SubmittedProposals,
+	Vote,
+};
+use crate::migrations::{
+	v1::{self, MigrateToV1, ProposalV1},
+	v2::{self, MigrateToV2, ProposalV2},
+	v3::{self, MigrateToV3, ProposalV3},
+	v4::MigrateToV4,
+	v5::MigrateToV5,
+};
+use crate::{
+	mock::*, Agenda, BalanceCheckpoints, EnsureTrack, Error, Event, FreezeReason, HoldReason,
+	IncompleteSince, Origin, ProposalCalls, ProposalVoters, RegisteredAccounts,
+	RegisteredAccountsCount, WeightInfo,
+};
 
 use frame_support::assert_err;
+use frame_support::traits::{ConstU8, EnsureOrigin};
 use frame_support::traits::{fungible::*, Hooks};
-use frame_support::traits::tokens::WithdrawConsequence;
-use frame_support::{assert_ok, traits::fungible::Mutate};
+use frame_support::traits::tokens::{Preservation, WithdrawConsequence};
+use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+use frame_support::{assert_ok, traits::fungible::Mutate, weights::Weight};
 use sp_core::Hasher;
-use sp_runtime::traits::BlakeTwo256;
+use sp_runtime::{traits::BlakeTwo256, DispatchError, Perbill};
 
 type NativeBalance = <Test as crate::Config>::NativeBalance;
 type THash<T> = <T as frame_system::Config>::Hash;
@@ -38,6 +54,50 @@ fn go_to_block(block: u64) {
 	Voting::on_initialize(block);
 }
 
+// Rewrite a proposal and its voter index in the storage layout of version 1.
+fn downgrade_to_v1(hash: THash<Test>) {
+	let proposal = SubmittedProposals::<Test>::take(hash).unwrap();
+	let old = ProposalV1::<Test> {
+		status: proposal.status,
+		deadline: proposal.deadline,
+		owner: proposal.owner,
+		aye_votes: proposal.aye_votes,
+		nay_votes: proposal.nay_votes,
+	};
+	v1::SubmittedProposals::<Test>::insert(hash, old);
+	let _ = ProposalVoters::<Test>::clear_prefix(hash, u32::MAX, None);
+	RegisteredAccountsCount::<Test>::kill();
+}
+
+// Rewrite a proposal in the storage layout of version 3.
+fn downgrade_to_v3(hash: THash<Test>) {
+	let proposal = SubmittedProposals::<Test>::take(hash).unwrap();
+	let old = ProposalV3::<Test> {
+		status: proposal.status,
+		deadline: proposal.deadline,
+		owner: proposal.owner,
+		aye_votes: proposal.aye_votes,
+		nay_votes: proposal.nay_votes,
+		voter_count: proposal.voter_count,
+		snapshot_block: proposal.snapshot_block,
+	};
+	v3::SubmittedProposals::<Test>::insert(hash, old);
+}
+
+// Rewrite a proposal in the storage layout of version 2.
+fn downgrade_to_v2(hash: THash<Test>) {
+	let proposal = SubmittedProposals::<Test>::take(hash).unwrap();
+	let old = ProposalV2::<Test> {
+		status: proposal.status,
+		deadline: proposal.deadline,
+		owner: proposal.owner,
+		aye_votes: proposal.aye_votes,
+		nay_votes: proposal.nay_votes,
+		voter_count: proposal.voter_count,
+	};
+	v2::SubmittedProposals::<Test>::insert(hash, old);
+}
+
 // Test setup function.
 fn submit_proposal_setup() -> THash<Test> {
 	// Go past genesis block so events get deposited.
@@ -52,6 +112,10 @@ fn submit_proposal_setup() -> THash<Test> {
 	assert_ok!(NativeBalance::mint_into(&bob, 100));
 	assert_ok!(NativeBalance::mint_into(&charlie, 100));
 
+	// Alice and Charlie record their balances so they can vote if they are registered later.
+	assert_ok!(Voting::checkpoint_balance(RuntimeOrigin::signed(alice)));
+	assert_ok!(Voting::checkpoint_balance(RuntimeOrigin::signed(charlie)));
+
 	// Register bob to allow voting.
 	assert_ok!(Voting::register_account(RuntimeOrigin::root(), bob));
 
@@ -65,7 +129,9 @@ fn submit_proposal_setup() -> THash<Test> {
 	assert_ok!(Voting::submit_proposal(
 		RuntimeOrigin::signed(alice),
 		hash.clone(),
-		Duration::Tier1
+		0,
+		Duration::Tier1,
+		None
 	));
 
 	next_block();
@@ -86,6 +152,10 @@ fn submit_proposal_multiple_setup() -> (THash<Test>, THash<Test>, THash<Test>, T
 	assert_ok!(NativeBalance::mint_into(&bob, 100));
 	assert_ok!(NativeBalance::mint_into(&charlie, 100));
 
+	// Alice and Charlie record their balances so they can vote if they are registered later.
+	assert_ok!(Voting::checkpoint_balance(RuntimeOrigin::signed(alice)));
+	assert_ok!(Voting::checkpoint_balance(RuntimeOrigin::signed(charlie)));
+
 	// Register bob to allow voting.
 	assert_ok!(Voting::register_account(RuntimeOrigin::root(), bob));
 	next_block();
@@ -105,22 +175,30 @@ fn submit_proposal_multiple_setup() -> (THash<Test>, THash<Test>, THash<Test>, T
 	assert_ok!(Voting::submit_proposal(
 		RuntimeOrigin::signed(alice),
 		hash_1.clone(),
-		Duration::Tier1
+		0,
+		Duration::Tier1,
+		None
 	));
 	assert_ok!(Voting::submit_proposal(
 		RuntimeOrigin::signed(alice),
 		hash_2.clone(),
-		Duration::Tier1
+		0,
+		Duration::Tier1,
+		None
 	));
 	assert_ok!(Voting::submit_proposal(
 		RuntimeOrigin::signed(alice),
 		hash_3.clone(),
-		Duration::Tier1
+		0,
+		Duration::Tier1,
+		None
 	));
 	assert_ok!(Voting::submit_proposal(
 		RuntimeOrigin::signed(alice),
 		hash_4.clone(),
-		Duration::Tier1
+		0,
+		Duration::Tier1,
+		None
 	));
 
 	next_block();
@@ -130,7 +208,7 @@ fn submit_proposal_multiple_setup() -> (THash<Test>, THash<Test>, THash<Test>, T
 
 #[test]
 fn register_account_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		// Go past genesis block so events get depositd.
 		next_block();
 
@@ -150,7 +228,7 @@ fn register_account_works() {
 
 #[test]
 fn circle_of_trust_register_account_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		next_block();
 
 		let bob = 1;
@@ -178,7 +256,7 @@ fn circle_of_trust_register_account_works() {
 
 #[test]
 fn submit_proposal_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		// Go past genesis block so events get depositd.
 		next_block();
 
@@ -192,7 +270,9 @@ fn submit_proposal_works() {
 		assert_ok!(Voting::submit_proposal(
 			RuntimeOrigin::signed(alice),
 			hash.clone(),
-			Duration::Tier1
+			0,
+			Duration::Tier1,
+			None
 		));
 
 		// Read pallet storage and assert an expected result.
@@ -206,7 +286,11 @@ fn submit_proposal_works() {
 				owner: alice.into(),
 				aye_votes: 0,
 				nay_votes: 0,
-				deadline: 100_801
+				deadline: 100_801,
+				voter_count: 0,
+				snapshot_block: 1,
+				track: 0,
+				deposit: 0,
 			}
 		);
 	});
@@ -214,7 +298,7 @@ fn submit_proposal_works() {
 
 #[test]
 fn not_enough_tokens_error_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		next_block();
 
 		let alice = 0;
@@ -234,13 +318,15 @@ fn not_enough_tokens_error_works() {
 		assert_ok!(Voting::submit_proposal(
 			RuntimeOrigin::signed(alice),
 			hash.clone(),
-			Duration::Tier1
+			0,
+			Duration::Tier1,
+			None
 		));
 		next_block();
 
 		// Bob tries to vote on the proposal (error).
 		assert_err!(
-			Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(5)),
+			Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(5), None),
 			Error::<Test>::NotEnoughTokens
 		);
 		next_block();
@@ -248,7 +334,7 @@ fn not_enough_tokens_error_works() {
 		// Confirm proposal still has no votes.
 		assert_eq!(SubmittedProposals::<Test>::get(hash.clone()).unwrap().aye_votes, 0);
 		// Bob votes on the proposal successfully.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3)));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
 		// Confirm proposal still has votes.
 		assert_eq!(SubmittedProposals::<Test>::get(hash.clone()).unwrap().aye_votes, 3);
 		// Ensure Bob's tokens are frozen.
@@ -258,14 +344,14 @@ fn not_enough_tokens_error_works() {
 
 #[test]
 fn vote_aye_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let bob = 1;
 
 		// Alice submits proposal, bob registered to vote with 100 tokens.
 		let hash = submit_proposal_setup();
 
 		// Bob votes on the proposal.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3)));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
 		next_block();
 
 		// Assert that the account vote state is correct.
@@ -290,14 +376,14 @@ fn vote_aye_works() {
 
 #[test]
 fn vote_nay_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let bob = 1;
 
 		// Alice submits proposal, bob registered to vote with 100 tokens.
 		let hash = submit_proposal_setup();
 
 		// Bob votes on the proposal.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Nay(3)));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Nay(3), None));
 		next_block();
 
 		// Assert that the account vote state is correct.
@@ -322,17 +408,17 @@ fn vote_nay_works() {
 
 #[test]
 fn account_can_vote_on_same_proposal_multiple_times() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let bob = 1;
 
 		// Alice submits proposal, bob registered to vote with 100 tokens.
 		let hash = submit_proposal_setup();
 
 		// Bob casts 3 votes on the proposal.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3)));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
 		next_block();
 		// Bob casts 5 more votes on the proposal.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(5)));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(5), None));
 		next_block();
 
 		// Assert that the account vote state is correct.
@@ -357,14 +443,14 @@ fn account_can_vote_on_same_proposal_multiple_times() {
 
 #[test]
 fn unregistered_account_cannot_vote() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let charlie = 2;
 
 		// Alice submits proposal, bob registered to vote with 100 tokens.
 		let hash = submit_proposal_setup();
 
 		assert_err!(
-			Voting::cast_vote(RuntimeOrigin::signed(charlie), hash, Vote::Aye(1)),
+			Voting::cast_vote(RuntimeOrigin::signed(charlie), hash, Vote::Aye(1), None),
 			Error::<Test>::NotRegistered
 		);
 	});
@@ -372,7 +458,7 @@ fn unregistered_account_cannot_vote() {
 
 #[test]
 fn cannot_vote_on_closed_proposal() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let alice = 0;
 		let bob = 1;
 
@@ -380,7 +466,7 @@ fn cannot_vote_on_closed_proposal() {
 		let hash = submit_proposal_setup();
 
 		// Bob votes on the proposal.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3)));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
 		// Go past deadline.
 		go_to_block(100_900);
 		// Close proposal.
@@ -390,7 +476,7 @@ fn cannot_vote_on_closed_proposal() {
 
 		// Bob tries to vote again (error).
 		assert_err!(
-			Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3)),
+			Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None),
 			Error::<Test>::VotingOnClosedProposalNotAllowed
 		)
 	});
@@ -398,20 +484,35 @@ fn cannot_vote_on_closed_proposal() {
 
 #[test]
 fn cannot_vote_on_too_many_proposals() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let bob = 1;
 
 		// Alice submits proposal, bob registered to vote with 100 tokens.
 		let (hash_1, hash_2, hash_3, hash_4) = submit_proposal_multiple_setup();
 
 		// Bob votes on three proposals successfully.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash_1.clone(), Vote::Aye(2)));
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash_2.clone(), Vote::Aye(2)));
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash_3.clone(), Vote::Aye(2)));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash_1.clone(),
+			Vote::Aye(2),
+			None
+		));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash_2.clone(),
+			Vote::Aye(2),
+			None
+		));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash_3.clone(),
+			Vote::Aye(2),
+			None
+		));
 
 		// Bob tries to vote on a fourth proposal (error).
 		assert_err!(
-			Voting::cast_vote(RuntimeOrigin::signed(bob), hash_4.clone(), Vote::Aye(2)),
+			Voting::cast_vote(RuntimeOrigin::signed(bob), hash_4.clone(), Vote::Aye(2), None),
 			Error::<Test>::VoteProposalsExceeded
 		);
 	});
@@ -419,7 +520,7 @@ fn cannot_vote_on_too_many_proposals() {
 
 #[test]
 fn close_proposal_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let alice = 0;
 		let bob = 1;
 
@@ -427,7 +528,7 @@ fn close_proposal_works() {
 		let hash = submit_proposal_setup();
 
 		// Bob votes on the proposal (first time).
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3)));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
 		// Go past deadline.
 		go_to_block(100_900);
 		// Close proposal.
@@ -447,7 +548,7 @@ fn close_proposal_works() {
 
 #[test]
 fn anyone_can_close_proposal() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let alice = 0;
 		let bob = 1;
 
@@ -468,7 +569,7 @@ fn anyone_can_close_proposal() {
 
 #[test]
 fn frozen_balance_is_correct_after_multiple_votes() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let alice = 0;
 		let bob = 1;
 
@@ -476,47 +577,58 @@ fn frozen_balance_is_correct_after_multiple_votes() {
 		let (hash_1, hash_2, hash_3, _hash_4) = submit_proposal_multiple_setup();
 
 		// Bob votes 9 tokens on proposal 1.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash_1.clone(), Vote::Aye(3)));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash_1.clone(),
+			Vote::Aye(3),
+			None
+		));
 		// Bob votes 16 tokens on proposal 2.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash_2.clone(), Vote::Aye(4)));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash_2.clone(),
+			Vote::Aye(4),
+			None
+		));
 		// Assert frozen balance is max(9, 16).
 		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 16);
 
 		// Bob votes 25 tokens on proposal 3.
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash_3.clone(), Vote::Aye(5)));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash_3.clone(),
+			Vote::Aye(5),
+			None
+		));
 		// Assert frozen balance is max(9, 16, 25).
 		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 25);
 
 		// Bob votes another 2 tokens on proposal, now requiring 64 votes (8th vote).
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash_3.clone(), Vote::Aye(3)));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash_3.clone(),
+			Vote::Aye(3),
+			None
+		));
 
 		// Go past deadline block.
 		System::set_block_number(100_910);
 		// Assert frozen balance is max(9, 16, 49).
 		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 64);
 
-		// Alice closes proposal 3.
+		// Alice closes proposal 3, unfreezing Bob's tokens for it.
 		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash_3.clone()));
 		next_block();
 
-		// Bob claims back his tokens on proposal 3 (currently 29).
-		assert_ok!(Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash_3.clone()));
-		next_block();
-
 		// Assert bob's new frozen balance is now 16.
 		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 16);
 
-		// Alice closes proposal 1 and 2.
+		// Alice closes proposal 2, leaving a frozen balance of 9.
 		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash_2.clone()));
-		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash_1.clone()));
-		next_block();
-
-		// Bob claims back his 16 tokens on proposal 2, leaving a frozen balance of 9.
-		assert_ok!(Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash_2.clone()));
 		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 9);
 
-		// Bob claims back his 9 tokens on proposal 1, leaving a frozen balance of 0.
-		assert_ok!(Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash_1.clone()));
+		// Alice closes proposal 1, leaving a frozen balance of 0.
+		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash_1.clone()));
 		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 0);
 
 		assert_eq!(NativeBalance::free_balance(&bob), 100);
@@ -526,21 +638,26 @@ fn frozen_balance_is_correct_after_multiple_votes() {
 
 #[test]
 fn claim_back_tokens_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let alice = 0;
 		let bob = 1;
 
 		// Alice submits proposal, bob registered to vote with 100 tokens.
 		let hash = submit_proposal_setup();
 
-		// Bob votes on the proposal (first time).
-		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3)));
+		// Bob votes on the proposal with conviction, keeping his tokens frozen after closing.
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash.clone(),
+			Vote::Aye(3),
+			Some(Conviction::Locked2x)
+		));
 		// Go past deadline.
 		System::set_block_number(100_900);
 		// Close proposal.
 		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash.clone()));
-		// Go to next block.
-		next_block();
+		// Go to the unlock block.
+		go_to_block(101_802);
 		// Bob claims back his tokens.
 		assert_ok!(Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash.clone()));
 		next_block();
@@ -552,3 +669,996 @@ fn claim_back_tokens_works() {
 		assert_eq!(NativeBalance::can_withdraw(&bob, 98), WithdrawConsequence::Success);
 	});
 }
+
+#[test]
+fn conviction_multiplies_votes() {
+	build_and_execute(|| {
+		let bob = 1;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		// Bob casts 3 votes with a 3x conviction.
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash.clone(),
+			Vote::Aye(3),
+			Some(Conviction::Locked3x)
+		));
+
+		// Proposal receives 9 weighted votes, while Bob only freezes 9 tokens for his 3 votes.
+		assert_eq!(SubmittedProposals::<Test>::get(hash.clone()).unwrap().aye_votes, 9);
+		assert_eq!(AccountVotes::<Test>::get(bob, hash.clone()).unwrap(), 3);
+		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 9);
+
+		// Tokens are locked for 2 periods after the deadline (block 100_802).
+		let lock = AccountVoteLocks::<Test>::get(bob, hash).unwrap();
+		assert_eq!(lock.weighted_votes, 9);
+		assert_eq!(lock.unlock_block, 102_802);
+	});
+}
+
+#[test]
+fn weaker_conviction_does_not_shorten_lock() {
+	build_and_execute(|| {
+		let bob = 1;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		// Bob votes with a 6x conviction, then again without conviction.
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash.clone(),
+			Vote::Nay(1),
+			Some(Conviction::Locked6x)
+		));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Nay(2), None));
+
+		// Weighted votes accumulate and the lock keeps the 16 period extension.
+		let proposal = SubmittedProposals::<Test>::get(hash.clone()).unwrap();
+		assert_eq!(proposal.nay_votes, 8);
+		let lock = AccountVoteLocks::<Test>::get(bob, hash).unwrap();
+		assert_eq!(lock.weighted_votes, 8);
+		assert_eq!(lock.unlock_block, 116_802);
+	});
+}
+
+#[test]
+fn cannot_claim_back_tokens_before_conviction_unlock() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		// Bob votes with a 2x conviction, locking his tokens 1 period after the deadline.
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash.clone(),
+			Vote::Aye(3),
+			Some(Conviction::Locked2x)
+		));
+
+		// Go past deadline and close proposal.
+		go_to_block(100_900);
+		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash.clone()));
+
+		// Bob tries to claim back his tokens (error).
+		assert_err!(
+			Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash.clone()),
+			Error::<Test>::VoteStillLocked
+		);
+		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 9);
+
+		// Go to the unlock block and claim back the tokens.
+		go_to_block(101_802);
+		assert_ok!(Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash.clone()));
+		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 0);
+		assert!(AccountVoteLocks::<Test>::get(bob, hash).is_none());
+	});
+}
+
+#[test]
+fn migration_to_v1_adds_missing_vote_locks() {
+	build_and_execute(|| {
+		let bob = 1;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
+
+		// Simulate a vote cast before lock schedules existed.
+		downgrade_to_v1(hash);
+		AccountVoteLocks::<Test>::remove(bob, hash);
+		StorageVersion::new(0).put::<Voting>();
+
+		<(MigrateToV1<Test>, MigrateToV2<Test>, MigrateToV3<Test>, MigrateToV4<Test>)>::on_runtime_upgrade(
+		);
+
+		// The lock unlocks at the proposal deadline, as for a vote without conviction.
+		assert_eq!(Voting::on_chain_storage_version(), 4);
+		let lock = AccountVoteLocks::<Test>::get(bob, hash).unwrap();
+		assert_eq!(lock.weighted_votes, 3);
+		assert_eq!(lock.unlock_block, 100_802);
+	});
+}
+
+#[test]
+fn migration_to_v1_does_nothing_when_already_applied() {
+	// The vote lock is removed on purpose, so the pallet's invariants do not hold.
+	new_test_ext().execute_with(|| {
+		let bob = 1;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
+
+		AccountVoteLocks::<Test>::remove(bob, hash);
+		StorageVersion::new(1).put::<Voting>();
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(Voting::on_chain_storage_version(), 1);
+		assert!(AccountVoteLocks::<Test>::get(bob, hash).is_none());
+	});
+}
+
+#[test]
+fn genesis_config_registers_accounts_and_proposals() {
+	let alice = 0;
+	let bob = 1;
+	let hash = BlakeTwo256::hash(&codec::Encode::encode(&"Genesis proposal."));
+
+	let genesis = crate::GenesisConfig::<Test> {
+		registered_accounts: vec![alice, bob],
+		initial_proposals: vec![(alice, hash, 0, 100)],
+	};
+
+	new_test_ext_with_genesis(vec![(bob, 100)], genesis).execute_with(|| {
+		assert!(RegisteredAccounts::<Test>::get(alice));
+		assert!(RegisteredAccounts::<Test>::get(bob));
+
+		let proposal = SubmittedProposals::<Test>::get(hash).unwrap();
+		assert_eq!(proposal.status, Status::Active);
+		assert_eq!(proposal.owner, alice);
+		assert_eq!(proposal.deadline, 100);
+		assert_eq!(Agenda::<Test>::get(100).into_inner(), vec![hash]);
+		assert_eq!(RegisteredAccountsCount::<Test>::get(), 2);
+		assert_eq!(Voting::on_chain_storage_version(), 5);
+
+		// Registered accounts can vote on the genesis proposal straight away.
+		System::set_block_number(1);
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash, Vote::Aye(3), None));
+
+		assert_ok!(Voting::do_try_state());
+	});
+}
+
+#[test]
+fn migration_to_v2_indexes_proposal_voters() {
+	build_and_execute(|| {
+		let bob = 1;
+		let charlie = 2;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), charlie));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(charlie),
+			hash.clone(),
+			Vote::Nay(2),
+			None
+		));
+
+		downgrade_to_v1(hash);
+		StorageVersion::new(1).put::<Voting>();
+
+		<(MigrateToV2<Test>, MigrateToV3<Test>, MigrateToV4<Test>)>::on_runtime_upgrade();
+
+		assert_eq!(Voting::on_chain_storage_version(), 4);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().voter_count, 2);
+		assert!(ProposalVoters::<Test>::contains_key(hash, bob));
+		assert!(ProposalVoters::<Test>::contains_key(hash, charlie));
+		assert_eq!(RegisteredAccountsCount::<Test>::get(), 2);
+	});
+}
+
+#[test]
+fn proposal_voters_are_indexed() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+		let charlie = 2;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), alice));
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), charlie));
+		assert_eq!(RegisteredAccountsCount::<Test>::get(), 3);
+
+		// Voting twice on the same proposal only counts the voter once.
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(2), None));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(1), None));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(charlie),
+			hash.clone(),
+			Vote::Nay(1),
+			None
+		));
+
+		let mut voters = Voting::proposal_voters(&hash);
+		voters.sort();
+		assert_eq!(voters, vec![bob, charlie]);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().voter_count, 2);
+		assert_eq!(Voting::proposal_turnout(&hash).unwrap(), Perbill::from_rational(2u32, 3u32));
+
+		// The proposal already has the maximum number of voters.
+		assert_err!(
+			Voting::cast_vote(RuntimeOrigin::signed(alice), hash.clone(), Vote::Aye(1), None),
+			Error::<Test>::TooManyVoters
+		);
+	});
+}
+
+#[test]
+fn close_proposal_unfreezes_expired_voters() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+		let charlie = 2;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), charlie));
+
+		// Bob votes without conviction, Charlie with a 2x conviction.
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(charlie),
+			hash.clone(),
+			Vote::Nay(2),
+			Some(Conviction::Locked2x)
+		));
+
+		// Go past deadline and close proposal.
+		go_to_block(100_900);
+		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash.clone()));
+
+		// Bob's tokens are unfrozen, Charlie's stay frozen until his lock expires.
+		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 0);
+		assert!(AccountVotes::<Test>::get(bob, hash).is_none());
+		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &charlie), 4);
+		System::assert_has_event(RuntimeEvent::Voting(Event::TokensClaimed {
+			who: bob,
+			proposal: hash,
+			amount: 9,
+			frozen: 0,
+		}));
+
+		// Bob has nothing left to claim.
+		assert_err!(
+			Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash.clone()),
+			Error::<Test>::NoVotesFoundForAccount
+		);
+
+		// Voters remain listed after their tokens are unfrozen.
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().voter_count, 2);
+		assert!(ProposalVoters::<Test>::contains_key(hash, bob));
+	});
+}
+
+#[test]
+fn votes_are_bounded_by_snapshot_balance() {
+	build_and_execute(|| {
+		let bob = 1;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		// Bob receives more tokens after the proposal's snapshot block.
+		assert_ok!(NativeBalance::mint_into(&bob, 200));
+
+		// Bob can only freeze the 100 tokens he held at the snapshot block.
+		assert_err!(
+			Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(11), None),
+			Error::<Test>::NotEnoughTokens
+		);
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash.clone(),
+			Vote::Aye(10),
+			None
+		));
+	});
+}
+
+#[test]
+fn transfers_around_the_snapshot_block_are_checkpointed() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+		let eve = 4;
+		DiscussionPeriod::set(10);
+
+		// Bob is registered with 100 tokens, and alice submits a proposal whose snapshot is at
+		// block 11.
+		System::set_block_number(1);
+		assert_ok!(NativeBalance::set_balance(&bob, 100));
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), bob));
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&"Snapshot proposal."));
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			hash,
+			0,
+			Duration::Tier1,
+			None
+		));
+
+		// Bob sends 60 tokens away before the snapshot block, without recording a checkpoint.
+		go_to_block(5);
+		assert_ok!(NativeBalance::transfer(&bob, &eve, 60, Preservation::Expendable));
+		assert_eq!(BalanceCheckpoints::<Test>::get(bob).into_inner(), vec![(1, 100), (5, 40)]);
+
+		// The tokens come back after the snapshot block, and only count for later proposals.
+		go_to_block(11);
+		go_to_block(12);
+		assert_ok!(NativeBalance::transfer(&eve, &bob, 60, Preservation::Expendable));
+		assert_eq!(Voting::balance_at(&bob, 11), 40);
+		assert_err!(
+			Voting::cast_vote(RuntimeOrigin::signed(bob), hash, Vote::Aye(7), None),
+			Error::<Test>::NotEnoughTokens
+		);
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash, Vote::Aye(6), None));
+	});
+}
+
+#[test]
+fn tokens_received_after_snapshot_cannot_vote() {
+	build_and_execute(|| {
+		let alice = 0;
+		let dave = 3;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		// Dave receives tokens and registers after the proposal's snapshot block.
+		assert_ok!(NativeBalance::mint_into(&dave, 100));
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), dave));
+
+		assert_err!(
+			Voting::cast_vote(RuntimeOrigin::signed(dave), hash.clone(), Vote::Aye(1), None),
+			Error::<Test>::NotEnoughTokens
+		);
+
+		// Dave can vote on proposals submitted after his checkpoint.
+		next_block();
+		let new_hash = BlakeTwo256::hash(&codec::Encode::encode(&"Another proposal."));
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			new_hash.clone(),
+			0,
+			Duration::Tier1,
+			None
+		));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(dave),
+			new_hash.clone(),
+			Vote::Aye(1),
+			None
+		));
+	});
+}
+
+#[test]
+fn balance_checkpoints_are_bounded() {
+	build_and_execute(|| {
+		let eve = 4;
+		assert_ok!(NativeBalance::mint_into(&eve, 10));
+
+		// Checkpoints in the same block overwrite each other.
+		System::set_block_number(1);
+		assert_ok!(Voting::checkpoint_balance(RuntimeOrigin::signed(eve)));
+		assert_ok!(NativeBalance::mint_into(&eve, 10));
+		assert_ok!(Voting::checkpoint_balance(RuntimeOrigin::signed(eve)));
+		assert_eq!(BalanceCheckpoints::<Test>::get(eve).into_inner(), vec![(1, 20)]);
+		System::assert_last_event(RuntimeEvent::Voting(Event::BalanceCheckpointed { who: eve }));
+
+		// Balance changes record a checkpoint once the account has one.
+		System::set_block_number(2);
+		assert_ok!(NativeBalance::mint_into(&eve, 10));
+		assert_eq!(BalanceCheckpoints::<Test>::get(eve).into_inner(), vec![(1, 20), (2, 30)]);
+
+		// The two oldest checkpoints are merged, keeping the lower balance, once the maximum is
+		// reached.
+		for block in 3..=4 {
+			System::set_block_number(block);
+			assert_ok!(Voting::checkpoint_balance(RuntimeOrigin::signed(eve)));
+		}
+		assert_eq!(
+			BalanceCheckpoints::<Test>::get(eve).into_inner(),
+			vec![(1, 20), (3, 30), (4, 30)]
+		);
+
+		// The balance at a block comes from the latest checkpoint at or before it.
+		assert_eq!(Voting::balance_at(&eve, 0), 0);
+		assert_eq!(Voting::balance_at(&eve, 2), 20);
+		assert_eq!(Voting::balance_at(&eve, 3), 30);
+	});
+}
+
+#[test]
+fn migration_to_v3_sets_snapshot_and_checkpoints() {
+	build_and_execute(|| {
+		let bob = 1;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		downgrade_to_v2(hash);
+		BalanceCheckpoints::<Test>::remove(bob);
+		StorageVersion::new(2).put::<Voting>();
+
+		go_to_block(50);
+		<(MigrateToV3<Test>, MigrateToV4<Test>)>::on_runtime_upgrade();
+
+		assert_eq!(Voting::on_chain_storage_version(), 4);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().snapshot_block, 50);
+		assert_eq!(BalanceCheckpoints::<Test>::get(bob).into_inner(), vec![(50, 100)]);
+
+		// Bob can keep voting on the proposal submitted before the upgrade.
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash.clone(), Vote::Aye(3), None));
+	});
+}
+
+#[test]
+fn proposal_lifecycle_is_driven_by_hooks() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+		let eve = 4;
+		DiscussionPeriod::set(10);
+
+		// Bob is registered to vote with 100 tokens.
+		System::set_block_number(1);
+		assert_ok!(NativeBalance::set_balance(&bob, 100));
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), bob));
+
+		// Alice submits a proposal that sets eve's balance when enacted.
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&"Lifecycle proposal."));
+		let call = RuntimeCall::Balances(pallet_balances::Call::force_set_balance {
+			who: eve,
+			new_free: 50,
+		});
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			hash,
+			0,
+			Duration::Tier1,
+			Some(Box::new(call))
+		));
+
+		// No votes are accepted during the discussion period.
+		let proposal = SubmittedProposals::<Test>::get(hash).unwrap();
+		assert_eq!(proposal.status, Status::Pending);
+		assert_eq!(proposal.snapshot_block, 11);
+		assert_eq!(proposal.deadline, 100_811);
+		assert_err!(
+			Voting::cast_vote(RuntimeOrigin::signed(bob), hash, Vote::Aye(3), None),
+			Error::<Test>::ProposalPending
+		);
+
+		// Voting opens once the discussion period has ended.
+		go_to_block(11);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Active);
+		System::assert_last_event(Event::ProposalActivated { proposal: hash }.into());
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash, Vote::Aye(3), None));
+
+		// The proposal is closed at its deadline and bob's tokens are unfrozen.
+		go_to_block(100_811);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Passed);
+		System::assert_last_event(
+			Event::ProposalPassed {
+				proposal: hash,
+				aye_votes: 3,
+				nay_votes: 0,
+				turnout: Perbill::one(),
+			}
+			.into(),
+		);
+		assert_eq!(NativeBalance::balance_frozen(&FreezeReason::ProposalVote.into(), &bob), 0);
+		assert_eq!(NativeBalance::balance(&eve), 0);
+
+		// The call is dispatched after the enactment delay, as root since the signal track
+		// enacts with the root origin.
+		go_to_block(100_821);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Enacted);
+		System::assert_last_event(Event::ProposalEnacted { proposal: hash, result: Ok(()) }.into());
+		assert_eq!(NativeBalance::balance(&eve), 50);
+		assert!(!ProposalCalls::<Test>::contains_key(hash));
+	});
+}
+
+#[test]
+fn track_origin_proposals_are_not_enacted_as_root() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+		let eve = 4;
+
+		// Bob is registered to vote with 100 tokens.
+		System::set_block_number(1);
+		assert_ok!(NativeBalance::set_balance(&bob, 100));
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), bob));
+
+		// Alice submits a proposal on the parameters track that needs root to enact.
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&"Parameters proposal."));
+		let call = RuntimeCall::Balances(pallet_balances::Call::force_set_balance {
+			who: eve,
+			new_free: 50,
+		});
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			hash,
+			2,
+			Duration::Tier1,
+			Some(Box::new(call))
+		));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash, Vote::Aye(3), None));
+
+		// The call is dispatched with the track's origin, which is not root.
+		go_to_block(100_811);
+		System::assert_last_event(
+			Event::ProposalEnacted { proposal: hash, result: Err(DispatchError::BadOrigin) }.into(),
+		);
+		assert_eq!(NativeBalance::balance(&eve), 0);
+
+		// `EnsureTrack` only accepts the origin of its own track.
+		assert!(
+			EnsureTrack::<ConstU8<2>>::try_origin(RuntimeOrigin::from(Origin::Track(2))).is_ok()
+		);
+		assert!(
+			EnsureTrack::<ConstU8<2>>::try_origin(RuntimeOrigin::from(Origin::Track(1))).is_err()
+		);
+		assert!(EnsureTrack::<ConstU8<2>>::try_origin(RuntimeOrigin::root()).is_err());
+	});
+}
+
+#[test]
+fn failed_proposal_call_is_not_enacted() {
+	build_and_execute(|| {
+		let alice = 0;
+		let eve = 4;
+
+		// Alice submits a proposal that nobody votes on.
+		System::set_block_number(1);
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&"Failed proposal."));
+		let call = RuntimeCall::Balances(pallet_balances::Call::force_set_balance {
+			who: eve,
+			new_free: 50,
+		});
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			hash,
+			0,
+			Duration::Tier1,
+			Some(Box::new(call))
+		));
+
+		// The proposal fails at its deadline and its call is removed.
+		go_to_block(100_801);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Failed);
+		System::assert_last_event(
+			Event::ProposalFailed {
+				proposal: hash,
+				aye_votes: 0,
+				nay_votes: 0,
+				turnout: Perbill::zero(),
+			}
+			.into(),
+		);
+		assert!(!ProposalCalls::<Test>::contains_key(hash));
+
+		go_to_block(100_811);
+		assert_eq!(NativeBalance::balance(&eve), 0);
+	});
+}
+
+#[test]
+fn submit_proposal_checks_call_length_and_agenda() {
+	build_and_execute(|| {
+		let alice = 0;
+		System::set_block_number(1);
+
+		// Calls longer than `MaxCallLength` cannot be attached.
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&0u32));
+		let call = RuntimeCall::System(frame_system::Call::remark { remark: vec![0; 128] });
+		assert_err!(
+			Voting::submit_proposal(
+				RuntimeOrigin::signed(alice),
+				hash,
+				0,
+				Duration::Tier1,
+				Some(Box::new(call))
+			),
+			Error::<Test>::CallTooLong
+		);
+
+		// Calls that can't be enacted within `MaxServiceWeight` cannot be attached.
+		MaxServiceWeight::set(<() as WeightInfo>::enact_proposal());
+		let call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		assert_err!(
+			Voting::submit_proposal(
+				RuntimeOrigin::signed(alice),
+				hash,
+				0,
+				Duration::Tier1,
+				Some(Box::new(call))
+			),
+			Error::<Test>::CallTooHeavy
+		);
+		MaxServiceWeight::set(Weight::MAX);
+
+		// Proposals submitted in the same block share their deadline.
+		for i in 0..MaxScheduledPerBlock::get() {
+			let hash = BlakeTwo256::hash(&codec::Encode::encode(&i));
+			assert_ok!(Voting::submit_proposal(
+				RuntimeOrigin::signed(alice),
+				hash,
+				0,
+				Duration::Tier1,
+				None
+			));
+		}
+
+		// Once the agenda of the deadline block is full, closing is scheduled in the next block.
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&MaxScheduledPerBlock::get()));
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			hash,
+			0,
+			Duration::Tier1,
+			None
+		));
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().deadline, 100_801);
+		assert_eq!(Agenda::<Test>::get(100_801).len() as u32, MaxScheduledPerBlock::get());
+		assert_eq!(Agenda::<Test>::get(100_802).into_inner(), vec![hash]);
+
+		// Once the agendas of the `MaxScheduleLookahead` blocks from the deadline are full,
+		// proposals can't be submitted.
+		for i in 1..MaxScheduledPerBlock::get() {
+			assert_ok!(Voting::submit_proposal(
+				RuntimeOrigin::signed(alice),
+				BlakeTwo256::hash(&codec::Encode::encode(&(i + 100))),
+				0,
+				Duration::Tier1,
+				None
+			));
+		}
+		assert_err!(
+			Voting::submit_proposal(
+				RuntimeOrigin::signed(alice),
+				BlakeTwo256::hash(&codec::Encode::encode(&200u32)),
+				0,
+				Duration::Tier1,
+				None
+			),
+			Error::<Test>::AgendaFull
+		);
+
+		// The proposal is closed a block after its deadline.
+		go_to_block(100_801);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Active);
+		go_to_block(100_802);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Failed);
+	});
+}
+
+#[test]
+fn transitions_beyond_the_service_weight_are_carried_over() {
+	build_and_execute(|| {
+		let alice = 0;
+		DiscussionPeriod::set(10);
+		System::set_block_number(1);
+
+		// Three proposals are activated at block 11, but only two activations fit in a block.
+		let hashes: Vec<_> =
+			(0u32..3).map(|i| BlakeTwo256::hash(&codec::Encode::encode(&i))).collect();
+		for hash in hashes.iter() {
+			assert_ok!(Voting::submit_proposal(
+				RuntimeOrigin::signed(alice),
+				*hash,
+				0,
+				Duration::Tier1,
+				None
+			));
+		}
+		MaxServiceWeight::set(<() as WeightInfo>::activate_proposal().saturating_mul(2));
+
+		go_to_block(11);
+		let status = |hash| SubmittedProposals::<Test>::get(hash).unwrap().status;
+		assert_eq!(status(hashes[0]), Status::Active);
+		assert_eq!(status(hashes[1]), Status::Active);
+		assert_eq!(status(hashes[2]), Status::Pending);
+		assert_eq!(Agenda::<Test>::get(11).into_inner(), vec![hashes[2]]);
+		assert_eq!(IncompleteSince::<Test>::get(), Some(11));
+
+		// The rest of the agenda is serviced in the next block.
+		next_block();
+		assert_eq!(status(hashes[2]), Status::Active);
+		assert!(!Agenda::<Test>::contains_key(11));
+		assert_eq!(IncompleteSince::<Test>::get(), None);
+	});
+}
+
+#[test]
+fn transitions_heavier_than_the_service_weight_are_skipped() {
+	build_and_execute(|| {
+		let alice = 0;
+		DiscussionPeriod::set(10);
+		System::set_block_number(1);
+
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&0u32));
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			hash,
+			0,
+			Duration::Tier1,
+			None
+		));
+
+		// The activation can never fit, so it is skipped instead of holding up the agenda.
+		MaxServiceWeight::set(Weight::from_parts(1, 1));
+		go_to_block(11);
+		System::assert_last_event(Event::ProposalOverweight { proposal: hash }.into());
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Pending);
+		assert!(!Agenda::<Test>::contains_key(11));
+		assert_eq!(IncompleteSince::<Test>::get(), None);
+	});
+}
+
+#[test]
+fn migration_to_v4_assigns_first_track() {
+	build_and_execute(|| {
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		downgrade_to_v3(hash);
+		StorageVersion::new(3).put::<Voting>();
+
+		MigrateToV4::<Test>::on_runtime_upgrade();
+
+		assert_eq!(Voting::on_chain_storage_version(), 4);
+		let proposal = SubmittedProposals::<Test>::get(hash).unwrap();
+		assert_eq!(proposal.track, 0);
+		assert_eq!(proposal.deposit, 0);
+		assert_eq!(proposal.snapshot_block, 2);
+	});
+}
+
+#[test]
+fn migration_to_v5_schedules_live_proposals() {
+	build_and_execute(|| {
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+
+		// Simulate a proposal submitted before the agenda existed.
+		Agenda::<Test>::remove(100_802);
+		StorageVersion::new(4).put::<Voting>();
+
+		MigrateToV5::<Test>::on_runtime_upgrade();
+
+		assert_eq!(Voting::on_chain_storage_version(), 5);
+		assert_eq!(Agenda::<Test>::get(100_802).into_inner(), vec![hash]);
+
+		// Running the migration again does not schedule the proposal twice.
+		MigrateToV5::<Test>::on_runtime_upgrade();
+		assert_eq!(Agenda::<Test>::get(100_802).into_inner(), vec![hash]);
+
+		// The proposal is closed at its deadline.
+		go_to_block(100_802);
+		assert_eq!(SubmittedProposals::<Test>::get(hash).unwrap().status, Status::Failed);
+	});
+}
+
+#[test]
+fn submit_proposal_checks_track() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+
+		// Alice starts with 100 tokens and bob with 5 tokens.
+		System::set_block_number(1);
+		assert_ok!(NativeBalance::set_balance(&alice, 100));
+		assert_ok!(NativeBalance::set_balance(&bob, 5));
+		let hash = BlakeTwo256::hash(&codec::Encode::encode(&"Treasury proposal."));
+
+		// Proposals can only be submitted on configured tracks.
+		assert_err!(
+			Voting::submit_proposal(RuntimeOrigin::signed(alice), hash, 9, Duration::Tier1, None),
+			Error::<Test>::UnknownTrack
+		);
+
+		// Only registered accounts can submit proposals on the treasury track.
+		assert_err!(
+			Voting::submit_proposal(RuntimeOrigin::signed(alice), hash, 1, Duration::Tier1, None),
+			Error::<Test>::NotRegistered
+		);
+
+		// Submitters need enough tokens for the track's deposit.
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), bob));
+		let result =
+			Voting::submit_proposal(RuntimeOrigin::signed(bob), hash, 1, Duration::Tier1, None);
+		assert!(result.is_err());
+
+		// The deposit is held from the submitter and the duration comes from the track.
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), alice));
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice),
+			hash,
+			1,
+			Duration::Tier1,
+			None
+		));
+
+		let proposal = SubmittedProposals::<Test>::get(hash).unwrap();
+		assert_eq!(proposal.track, 1);
+		assert_eq!(proposal.deposit, 10);
+		assert_eq!(proposal.deadline, 201_601);
+		assert_eq!(NativeBalance::balance_on_hold(&HoldReason::ProposalDeposit.into(), &alice), 10);
+	});
+}
+
+#[test]
+fn tracks_set_quorum_and_approval() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+		let charlie = 2;
+		let dave = 3;
+
+		// Four accounts are registered to vote with 100 tokens each.
+		System::set_block_number(1);
+		for who in [alice, bob, charlie, dave] {
+			assert_ok!(NativeBalance::set_balance(&who, 100));
+			assert_ok!(Voting::register_account(RuntimeOrigin::root(), who));
+		}
+
+		// Alice submits three proposals on the treasury track.
+		let low_turnout = BlakeTwo256::hash(&codec::Encode::encode(&"Low turnout."));
+		let low_approval = BlakeTwo256::hash(&codec::Encode::encode(&"Low approval."));
+		let approved = BlakeTwo256::hash(&codec::Encode::encode(&"Approved."));
+		for hash in [low_turnout, low_approval, approved] {
+			assert_ok!(Voting::submit_proposal(
+				RuntimeOrigin::signed(alice),
+				hash,
+				1,
+				Duration::Tier1,
+				None
+			));
+		}
+		assert_eq!(NativeBalance::balance_on_hold(&HoldReason::ProposalDeposit.into(), &alice), 30);
+
+		// One of four registered accounts votes, below the 50% quorum.
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), low_turnout, Vote::Aye(3), None));
+
+		// Three of five votes are aye, which does not exceed the 60% approval threshold.
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), low_approval, Vote::Aye(3), None));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(charlie),
+			low_approval,
+			Vote::Nay(2),
+			None
+		));
+
+		// Four of six votes are aye.
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), approved, Vote::Aye(4), None));
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(charlie), approved, Vote::Nay(2), None));
+
+		// The proposals are closed at their deadline and alice's deposits are returned.
+		go_to_block(201_601);
+		assert_eq!(SubmittedProposals::<Test>::get(low_turnout).unwrap().status, Status::Failed);
+		assert_eq!(SubmittedProposals::<Test>::get(low_approval).unwrap().status, Status::Failed);
+		assert_eq!(SubmittedProposals::<Test>::get(approved).unwrap().status, Status::Passed);
+		assert_eq!(NativeBalance::balance_on_hold(&HoldReason::ProposalDeposit.into(), &alice), 0);
+	});
+}
+
+#[test]
+fn events_report_votes_balances_and_outcome() {
+	build_and_execute(|| {
+		let alice = 0;
+		let bob = 1;
+		let charlie = 2;
+
+		// Alice submits proposal, bob registered to vote with 100 tokens.
+		let hash = submit_proposal_setup();
+		System::assert_has_event(RuntimeEvent::Voting(Event::ProposalCreated {
+			who: alice,
+			proposal: hash,
+			track: 0,
+			deadline: 100_802,
+			deposit: 0,
+		}));
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), charlie));
+
+		// Vote events report the account's total votes, frozen balance and unlock block.
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(bob), hash, Vote::Aye(3), None));
+		assert_ok!(Voting::cast_vote(
+			RuntimeOrigin::signed(bob),
+			hash,
+			Vote::Aye(2),
+			Some(Conviction::Locked2x)
+		));
+		System::assert_last_event(RuntimeEvent::Voting(Event::VotedAye {
+			who: bob,
+			proposal: hash,
+			votes: 4,
+			account_votes: 5,
+			frozen: 25,
+			unlock_block: 101_802,
+		}));
+
+		assert_ok!(Voting::cast_vote(RuntimeOrigin::signed(charlie), hash, Vote::Nay(2), None));
+		System::assert_last_event(RuntimeEvent::Voting(Event::VotedNay {
+			who: charlie,
+			proposal: hash,
+			votes: 2,
+			account_votes: 2,
+			frozen: 4,
+			unlock_block: 100_802,
+		}));
+
+		// Closing the proposal reports its outcome, final totals and the unfrozen tokens.
+		go_to_block(100_900);
+		assert_ok!(Voting::close_proposal(RuntimeOrigin::signed(alice), hash));
+		System::assert_has_event(RuntimeEvent::Voting(Event::TokensClaimed {
+			who: charlie,
+			proposal: hash,
+			amount: 4,
+			frozen: 0,
+		}));
+		System::assert_has_event(RuntimeEvent::Voting(Event::ProposalPassed {
+			proposal: hash,
+			aye_votes: 7,
+			nay_votes: 2,
+			turnout: Perbill::one(),
+		}));
+		System::assert_last_event(RuntimeEvent::Voting(Event::ProposalClosed {
+			who: alice,
+			hash,
+			status: Status::Passed,
+			aye_votes: 7,
+			nay_votes: 2,
+		}));
+
+		// Bob claims back the tokens once the conviction lock expires.
+		go_to_block(101_802);
+		assert_ok!(Voting::claim_back_tokens(RuntimeOrigin::signed(bob), hash));
+		System::assert_last_event(RuntimeEvent::Voting(Event::TokensClaimed {
+			who: bob,
+			proposal: hash,
+			amount: 25,
+			frozen: 0,
+		}));
+	});
+}
+
+#[test]
+fn voting_power_is_square_root_of_balance() {
+	build_and_execute(|| {
+		let alice = 0;
+
+		// Unregistered accounts have no voting power, whatever their balance.
+		assert_ok!(NativeBalance::set_balance(&alice, 110));
+		assert_eq!(Voting::voting_power(&alice), 0);
+
+		// Registered accounts can pay for the square root of their balance in votes.
+		assert_ok!(Voting::register_account(RuntimeOrigin::root(), alice));
+		assert_eq!(Voting::voting_power(&alice), 10);
+		assert_ok!(NativeBalance::set_balance(&alice, 121));
+		assert_eq!(Voting::voting_power(&alice), 11);
+	});
+}
@@ -0,0 +1,341 @@
+//! Weights for pallet_voting.
+//!
+//! THESE ARE HAND-ESTIMATED PLACEHOLDERS, NOT BENCHMARK OUTPUT. Each function counts the storage
+//! reads and writes of its extrinsic, listed above it, and adds a guessed execution time and
+//! proof size. They have never been measured, and must be replaced with the output of the
+//! pallet's benchmarks before the runtime is used on a live chain:
+//!
+//! ```text
+//! cargo build --release --features runtime-benchmarks
+//! ./target/release/node-template benchmark pallet --chain dev --pallet pallet_voting \
+//!     --extrinsic '*' --steps 50 --repeat 20 --output pallets/voting/src/weights.rs
+//! ```
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_voting.
+pub trait WeightInfo {
+	fn register_account() -> Weight;
+	fn circle_of_trust_register_account() -> Weight;
+	fn submit_proposal() -> Weight;
+	fn cast_vote(p: u32, ) -> Weight;
+	fn close_proposal(v: u32, ) -> Weight;
+	fn claim_back_tokens(p: u32, ) -> Weight;
+	fn checkpoint_balance() -> Weight;
+	fn activate_proposal() -> Weight;
+	fn enact_proposal() -> Weight;
+}
+
+/// Estimated weights for pallet_voting, using the runtime's database weights.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: Voting RegisteredAccounts (r:1 w:1)
+	/// Proof: Voting RegisteredAccounts (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Voting RegisteredAccountsCount (r:1 w:1)
+	/// Proof: Voting RegisteredAccountsCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	fn register_account() -> Weight {
+		Weight::from_parts(21_000_000, 3834)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: Voting RegisteredAccounts (r:2 w:1)
+	/// Proof: Voting RegisteredAccounts (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Voting RegisteredAccountsCount (r:1 w:1)
+	/// Proof: Voting RegisteredAccountsCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	fn circle_of_trust_register_account() -> Weight {
+		Weight::from_parts(27_000_000, 6038)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting Agenda (r:2 w:2)
+	/// Proof: Voting Agenda (max_values: None, max_size: Some(1614), added: 4089, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalCalls (r:0 w:1)
+	/// Proof: Voting ProposalCalls (max_values: None, max_size: Some(1074), added: 3549, mode: MaxEncodedLen)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Proof: Balances Holds (max_values: None, max_size: Some(1249), added: 3724, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn submit_proposal() -> Weight {
+		Weight::from_parts(41_000_000, 9158)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting RegisteredAccounts (r:1 w:0)
+	/// Proof: Voting RegisteredAccounts (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVotes (r:100 w:1)
+	/// Proof: Voting AccountVotes (max_values: None, max_size: Some(92), added: 2567, mode: MaxEncodedLen)
+	/// Storage: Voting AccountProposalsMap (r:1 w:1)
+	/// Proof: Voting AccountProposalsMap (max_values: None, max_size: Some(3253), added: 5728, mode: MaxEncodedLen)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Proof: Balances Freezes (max_values: None, max_size: Some(2251), added: 4726, mode: MaxEncodedLen)
+	/// Storage: Balances Locks (r:1 w:0)
+	/// Proof: Balances Locks (max_values: None, max_size: Some(1299), added: 3774, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVoteLocks (r:1 w:1)
+	/// Proof: Voting AccountVoteLocks (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalVoters (r:1 w:1)
+	/// Proof: Voting ProposalVoters (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	/// The range of component `p` is `[0, 99]`.
+	fn cast_vote(p: u32, ) -> Weight {
+		Weight::from_parts(64_512_340, 6718)
+			.saturating_add(Weight::from_parts(3_891_204, 0).saturating_mul(p.into()))
+			.saturating_add(T::DbWeight::get().reads(10_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+			.saturating_add(Weight::from_parts(0, 2567).saturating_mul(p.into()))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalVoters (r:257 w:0)
+	/// Proof: Voting ProposalVoters (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVotes (r:256 w:256)
+	/// Proof: Voting AccountVotes (max_values: None, max_size: Some(92), added: 2567, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVoteLocks (r:256 w:256)
+	/// Proof: Voting AccountVoteLocks (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Voting AccountProposalsMap (r:256 w:256)
+	/// Proof: Voting AccountProposalsMap (max_values: None, max_size: Some(3253), added: 5728, mode: MaxEncodedLen)
+	/// Storage: Balances Freezes (r:256 w:256)
+	/// Proof: Balances Freezes (max_values: None, max_size: Some(2251), added: 4726, mode: MaxEncodedLen)
+	/// Storage: Balances Locks (r:256 w:0)
+	/// Proof: Balances Locks (max_values: None, max_size: Some(1299), added: 3774, mode: MaxEncodedLen)
+	/// Storage: System Account (r:256 w:256)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:256 w:256)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	/// Storage: Voting Agenda (r:1 w:1)
+	/// Proof: Voting Agenda (max_values: None, max_size: Some(1614), added: 4089, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalCalls (r:1 w:1)
+	/// Proof: Voting ProposalCalls (max_values: None, max_size: Some(1074), added: 3549, mode: MaxEncodedLen)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Proof: Balances Holds (max_values: None, max_size: Some(1249), added: 3724, mode: MaxEncodedLen)
+	/// The range of component `v` is `[0, 256]`.
+	fn close_proposal(v: u32, ) -> Weight {
+		Weight::from_parts(22_104_870, 3562)
+			.saturating_add(Weight::from_parts(41_268_392, 0).saturating_mul(v.into()))
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().reads((8_u64).saturating_mul(v.into())))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+			.saturating_add(T::DbWeight::get().writes((6_u64).saturating_mul(v.into())))
+			.saturating_add(Weight::from_parts(0, 5728).saturating_mul(v.into()))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:0)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVoteLocks (r:1 w:1)
+	/// Proof: Voting AccountVoteLocks (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVotes (r:100 w:1)
+	/// Proof: Voting AccountVotes (max_values: None, max_size: Some(92), added: 2567, mode: MaxEncodedLen)
+	/// Storage: Voting AccountProposalsMap (r:1 w:1)
+	/// Proof: Voting AccountProposalsMap (max_values: None, max_size: Some(3253), added: 5728, mode: MaxEncodedLen)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Proof: Balances Freezes (max_values: None, max_size: Some(2251), added: 4726, mode: MaxEncodedLen)
+	/// Storage: Balances Locks (r:1 w:0)
+	/// Proof: Balances Locks (max_values: None, max_size: Some(1299), added: 3774, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	/// The range of component `p` is `[0, 99]`.
+	fn claim_back_tokens(p: u32, ) -> Weight {
+		Weight::from_parts(57_208_113, 6718)
+			.saturating_add(Weight::from_parts(3_744_512, 0).saturating_mul(p.into()))
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+			.saturating_add(Weight::from_parts(0, 2567).saturating_mul(p.into()))
+	}
+	/// Storage: System Account (r:1 w:0)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	fn checkpoint_balance() -> Weight {
+		Weight::from_parts(16_000_000, 3834)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	fn activate_proposal() -> Weight {
+		Weight::from_parts(11_000_000, 3562)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalCalls (r:1 w:1)
+	/// Proof: Voting ProposalCalls (max_values: None, max_size: Some(1074), added: 3549, mode: MaxEncodedLen)
+	fn enact_proposal() -> Weight {
+		Weight::from_parts(15_000_000, 4539)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	/// Storage: Voting RegisteredAccounts (r:1 w:1)
+	/// Proof: Voting RegisteredAccounts (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Voting RegisteredAccountsCount (r:1 w:1)
+	/// Proof: Voting RegisteredAccountsCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	fn register_account() -> Weight {
+		Weight::from_parts(21_000_000, 3834)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: Voting RegisteredAccounts (r:2 w:1)
+	/// Proof: Voting RegisteredAccounts (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Voting RegisteredAccountsCount (r:1 w:1)
+	/// Proof: Voting RegisteredAccountsCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	fn circle_of_trust_register_account() -> Weight {
+		Weight::from_parts(27_000_000, 6038)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting Agenda (r:2 w:2)
+	/// Proof: Voting Agenda (max_values: None, max_size: Some(1614), added: 4089, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalCalls (r:0 w:1)
+	/// Proof: Voting ProposalCalls (max_values: None, max_size: Some(1074), added: 3549, mode: MaxEncodedLen)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Proof: Balances Holds (max_values: None, max_size: Some(1249), added: 3724, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn submit_proposal() -> Weight {
+		Weight::from_parts(41_000_000, 9158)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting RegisteredAccounts (r:1 w:0)
+	/// Proof: Voting RegisteredAccounts (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVotes (r:100 w:1)
+	/// Proof: Voting AccountVotes (max_values: None, max_size: Some(92), added: 2567, mode: MaxEncodedLen)
+	/// Storage: Voting AccountProposalsMap (r:1 w:1)
+	/// Proof: Voting AccountProposalsMap (max_values: None, max_size: Some(3253), added: 5728, mode: MaxEncodedLen)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Proof: Balances Freezes (max_values: None, max_size: Some(2251), added: 4726, mode: MaxEncodedLen)
+	/// Storage: Balances Locks (r:1 w:0)
+	/// Proof: Balances Locks (max_values: None, max_size: Some(1299), added: 3774, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVoteLocks (r:1 w:1)
+	/// Proof: Voting AccountVoteLocks (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalVoters (r:1 w:1)
+	/// Proof: Voting ProposalVoters (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	/// The range of component `p` is `[0, 99]`.
+	fn cast_vote(p: u32, ) -> Weight {
+		Weight::from_parts(64_512_340, 6718)
+			.saturating_add(Weight::from_parts(3_891_204, 0).saturating_mul(p.into()))
+			.saturating_add(RocksDbWeight::get().reads(10_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+			.saturating_add(Weight::from_parts(0, 2567).saturating_mul(p.into()))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalVoters (r:257 w:0)
+	/// Proof: Voting ProposalVoters (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVotes (r:256 w:256)
+	/// Proof: Voting AccountVotes (max_values: None, max_size: Some(92), added: 2567, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVoteLocks (r:256 w:256)
+	/// Proof: Voting AccountVoteLocks (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Voting AccountProposalsMap (r:256 w:256)
+	/// Proof: Voting AccountProposalsMap (max_values: None, max_size: Some(3253), added: 5728, mode: MaxEncodedLen)
+	/// Storage: Balances Freezes (r:256 w:256)
+	/// Proof: Balances Freezes (max_values: None, max_size: Some(2251), added: 4726, mode: MaxEncodedLen)
+	/// Storage: Balances Locks (r:256 w:0)
+	/// Proof: Balances Locks (max_values: None, max_size: Some(1299), added: 3774, mode: MaxEncodedLen)
+	/// Storage: System Account (r:256 w:256)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:256 w:256)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	/// Storage: Voting Agenda (r:1 w:1)
+	/// Proof: Voting Agenda (max_values: None, max_size: Some(1614), added: 4089, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalCalls (r:1 w:1)
+	/// Proof: Voting ProposalCalls (max_values: None, max_size: Some(1074), added: 3549, mode: MaxEncodedLen)
+	/// Storage: Balances Holds (r:1 w:1)
+	/// Proof: Balances Holds (max_values: None, max_size: Some(1249), added: 3724, mode: MaxEncodedLen)
+	/// The range of component `v` is `[0, 256]`.
+	fn close_proposal(v: u32, ) -> Weight {
+		Weight::from_parts(22_104_870, 3562)
+			.saturating_add(Weight::from_parts(41_268_392, 0).saturating_mul(v.into()))
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().reads((8_u64).saturating_mul(v.into())))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+			.saturating_add(RocksDbWeight::get().writes((6_u64).saturating_mul(v.into())))
+			.saturating_add(Weight::from_parts(0, 5728).saturating_mul(v.into()))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:0)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVoteLocks (r:1 w:1)
+	/// Proof: Voting AccountVoteLocks (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Voting AccountVotes (r:100 w:1)
+	/// Proof: Voting AccountVotes (max_values: None, max_size: Some(92), added: 2567, mode: MaxEncodedLen)
+	/// Storage: Voting AccountProposalsMap (r:1 w:1)
+	/// Proof: Voting AccountProposalsMap (max_values: None, max_size: Some(3253), added: 5728, mode: MaxEncodedLen)
+	/// Storage: Balances Freezes (r:1 w:1)
+	/// Proof: Balances Freezes (max_values: None, max_size: Some(2251), added: 4726, mode: MaxEncodedLen)
+	/// Storage: Balances Locks (r:1 w:0)
+	/// Proof: Balances Locks (max_values: None, max_size: Some(1299), added: 3774, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	/// The range of component `p` is `[0, 99]`.
+	fn claim_back_tokens(p: u32, ) -> Weight {
+		Weight::from_parts(57_208_113, 6718)
+			.saturating_add(Weight::from_parts(3_744_512, 0).saturating_mul(p.into()))
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+			.saturating_add(Weight::from_parts(0, 2567).saturating_mul(p.into()))
+	}
+	/// Storage: System Account (r:1 w:0)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Voting BalanceCheckpoints (r:1 w:1)
+	/// Proof: Voting BalanceCheckpoints (max_values: None, max_size: Some(369), added: 2844, mode: MaxEncodedLen)
+	fn checkpoint_balance() -> Weight {
+		Weight::from_parts(16_000_000, 3834)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	fn activate_proposal() -> Weight {
+		Weight::from_parts(11_000_000, 3562)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: Voting SubmittedProposals (r:1 w:1)
+	/// Proof: Voting SubmittedProposals (max_values: None, max_size: Some(114), added: 2589, mode: MaxEncodedLen)
+	/// Storage: Voting ProposalCalls (r:1 w:1)
+	/// Proof: Voting ProposalCalls (max_values: None, max_size: Some(1074), added: 3549, mode: MaxEncodedLen)
+	fn enact_proposal() -> Weight {
+		Weight::from_parts(15_000_000, 4539)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+}
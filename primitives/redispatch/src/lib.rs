@@ -0,0 +1,64 @@
+//! Dispatch of calls from an account's signed origin on its behalf, shared by every pallet with a
+//! `redispatch` call. A single nesting depth is tracked for all of them, so `redispatch` calls of
+//! different pallets can't be nested inside each other to get around the limit.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::{
+	dispatch::{DispatchResultWithPostInfo, PostDispatchInfo},
+	sp_runtime::traits::Dispatchable,
+	weights::Weight,
+};
+
+// The number of redispatched calls being executed, nested inside each other. It lives in memory
+// for the duration of the outermost redispatched call, and never touches storage.
+environmental::environmental!(redispatch_depth: u32);
+
+/// The error returned when a call can't be redispatched.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TooDeeplyNested;
+
+/// The number of redispatched calls being executed, nested inside each other.
+pub fn depth() -> u32 {
+	redispatch_depth::with(|depth| *depth).unwrap_or_default()
+}
+
+/// Dispatch a call from an account's signed origin, as long as fewer than `max_depth`
+/// redispatched calls are being executed. `overhead` is charged on top of the call's actual
+/// weight, whether it succeeds or fails.
+pub fn dispatch_as_signed<Call, AccountId>(
+	who: AccountId,
+	call: Call,
+	max_depth: u32,
+	overhead: Weight,
+) -> Result<DispatchResultWithPostInfo, TooDeeplyNested>
+where
+	Call: Dispatchable<PostInfo = PostDispatchInfo>,
+	Call::RuntimeOrigin: From<frame_system::RawOrigin<AccountId>>,
+{
+	let depth = depth();
+	if depth >= max_depth {
+		return Err(TooDeeplyNested)
+	}
+
+	// The previous depth is restored once the call returns.
+	let mut nested_depth = depth.saturating_add(1);
+	let result = redispatch_depth::using(&mut nested_depth, || {
+		call.dispatch(frame_system::RawOrigin::Signed(who).into())
+	});
+
+	// Keep the call's post dispatch info, charging the overhead on top of its actual weight.
+	let add_overhead = |post_info: &mut PostDispatchInfo| {
+		post_info.actual_weight =
+			post_info.actual_weight.map(|weight| weight.saturating_add(overhead));
+	};
+	Ok(match result {
+		Ok(mut post_info) => {
+			add_overhead(&mut post_info);
+			Ok(post_info)
+		},
+		Err(mut err) => {
+			add_overhead(&mut err.post_info);
+			Err(err)
+		},
+	})
+}
@@ -7,8 +7,10 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use codec::{Decode, Encode};
-use frame_support::traits::AsEnsureOriginWithArg;
-use frame_system::{EnsureRoot, EnsureSigned};
+use frame_support::traits::{
+	AsEnsureOriginWithArg, Contains, Currency, EitherOfDiverse, Get, OnUnbalanced, SortedMembers,
+};
+use frame_system::{EnsureRoot, EnsureSigned, EnsureSignedBy};
 use pallet_grandpa::AuthorityId as GrandpaId;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -16,8 +18,8 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, Convert, IdentifyAccount, NumberFor, One,
-		Verify,
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, Convert,
+		IdentifyAccount, NumberFor, One, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
@@ -40,12 +42,12 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
-use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
+use pallet_transaction_payment::{ConstFeeMultiplier, Multiplier};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
@@ -103,12 +105,14 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// The version of the runtime specification. A full node will not attempt to use its native
 	//   runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
-	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
-	//   the compatible custom types.
-	spec_version: 100,
+	// This value started at 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
+	//   the compatible custom types, and must be raised by every runtime upgrade.
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	// Raised whenever `SignedExtra` or the encoding of existing calls changes, so wallets
+	//   re-read the metadata before signing.
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -242,33 +246,44 @@ impl pallet_balances::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type DustRemoval = ();
 	type ExistentialDeposit = ConstU128<EXISTENTIAL_DEPOSIT>;
-	type AccountStore = System;
+	type AccountStore = pallet_voting::CheckpointedAccountStore<Runtime, System>;
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 	type FreezeIdentifier = RuntimeFreezeReason;
 	type MaxFreezes = ConstU32<50>;
-	type RuntimeHoldReason = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type MaxHolds = ConstU32<50>;
 }
 
 parameter_types! {
 	pub FeeMultiplier: Multiplier = Multiplier::one();
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	/// The account transaction fees are paid to.
+	pub TreasuryAccount: AccountId = TreasuryPalletId::get().into_account_truncating();
+}
+
+/// Pays transaction fees to the treasury account, including fees paid in assets once they have
+/// been swapped into the native token.
+pub struct DealWithFees;
+impl OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for DealWithFees {
+	fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Runtime>) {
+		Balances::resolve_creating(&TreasuryAccount::get(), amount);
+	}
 }
 
 impl pallet_transaction_payment::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = pallet_dex::AssetFeeAdapter<
+		FeePrice,
+		Balances,
+		DealWithFees,
+		pallet_free_tx::SponsoredCurrencyAdapter<Balances, DealWithFees>,
+	>;
 	type OperationalFeeMultiplier = ConstU8<5>;
 	type WeightToFee = IdentityFee<Balance>;
 	type LengthToFee = IdentityFee<Balance>;
 	type FeeMultiplierUpdate = ConstFeeMultiplier<FeeMultiplier>;
 }
 
-impl pallet_sudo::Config for Runtime {
-	type RuntimeEvent = RuntimeEvent;
-	type RuntimeCall = RuntimeCall;
-	type WeightInfo = pallet_sudo::weights::SubstrateWeight<Runtime>;
-}
-
 parameter_types! {
 	pub const AssetDeposit: Balance = 100;
 	pub const ApprovalDeposit: Balance = 1;
@@ -291,7 +306,7 @@ impl pallet_assets::Config for Runtime {
 	type MetadataDepositPerByte = MetadataDepositPerByte;
 	type ApprovalDeposit = ApprovalDeposit;
 	type StringLimit = StringLimit;
-	type Freezer = ();
+	type Freezer = Dex;
 	type Extra = ();
 	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
 	type RemoveItemsLimit = ConstU32<1000>;
@@ -301,10 +316,115 @@ impl pallet_assets::Config for Runtime {
 }
 
 parameter_types! {
-	pub const ProposalDurationTier1: u8 = 1;
-	pub const ProposalDurationTier2: u8 = 2;
-	pub const ProposalDurationTier3: u8 = 4;
+	pub VotingTracks: Vec<(pallet_voting::TrackId, pallet_voting::TrackInfo<Balance>)> = vec![
+		(
+			0,
+			pallet_voting::TrackInfo {
+				name: "signal",
+				durations: [1, 2, 4],
+				quorum: Perbill::from_percent(5),
+				approval: Perbill::from_percent(50),
+				deposit: 10 * EXISTENTIAL_DEPOSIT,
+				submitter: pallet_voting::SubmitterOrigin::Registered,
+				// No origin in the runtime accepts the signal track's origin, so calls attached
+				// to signal proposals can't do anything.
+				enactment: pallet_voting::EnactmentOrigin::Track,
+			},
+		),
+		(
+			1,
+			pallet_voting::TrackInfo {
+				name: "treasury",
+				durations: [2, 4, 8],
+				quorum: Perbill::from_percent(10),
+				approval: Perbill::from_percent(60),
+				deposit: 100 * EXISTENTIAL_DEPOSIT,
+				submitter: pallet_voting::SubmitterOrigin::Registered,
+				enactment: pallet_voting::EnactmentOrigin::Root,
+			},
+		),
+		(
+			2,
+			pallet_voting::TrackInfo {
+				name: "runtime-upgrade",
+				durations: [2, 4, 8],
+				quorum: Perbill::from_percent(25),
+				approval: Perbill::from_percent(75),
+				deposit: 1_000 * EXISTENTIAL_DEPOSIT,
+				submitter: pallet_voting::SubmitterOrigin::Registered,
+				enactment: pallet_voting::EnactmentOrigin::Root,
+			},
+		),
+		(
+			3,
+			pallet_voting::TrackInfo {
+				name: "dex",
+				durations: [2, 4, 8],
+				quorum: Perbill::from_percent(10),
+				approval: Perbill::from_percent(60),
+				deposit: 100 * EXISTENTIAL_DEPOSIT,
+				submitter: pallet_voting::SubmitterOrigin::Registered,
+				enactment: pallet_voting::EnactmentOrigin::Track,
+			},
+		),
+	];
+	pub const DexTrack: pallet_voting::TrackId = 3;
 	pub const MaxProposalsAccountCanVote: u32 = 100;
+	pub const ConvictionBasePeriod: BlockNumber = 7 * DAYS;
+	pub const MaxVotersPerProposal: u32 = 256;
+	pub const MaxBalanceCheckpoints: u32 = 16;
+	pub const DiscussionPeriod: BlockNumber = 2 * DAYS;
+	pub const EnactmentDelay: BlockNumber = DAYS;
+	pub const MaxCallLength: u32 = 1024;
+	pub const MaxScheduledPerBlock: u32 = 50;
+	pub const MaxScheduleLookahead: u32 = 10;
+	/// Half of the block is left for extrinsics once the voting agenda has been serviced.
+	pub MaxServiceWeight: Weight = Perbill::from_percent(50) * BlockWeights::get().max_block;
+}
+
+/// The council's multisig account. The members of pallet-council are its signatories, and a
+/// majority of them must approve its operations.
+pub fn council_account() -> AccountId {
+	let signatories: Vec<(AccountId, u32)> = pallet_council::Members::<Runtime>::get()
+		.into_iter()
+		.map(|who| (who, 1))
+		.collect();
+	Multisig::multi_account_id(
+		&signatories,
+		Council::threshold(),
+		pallet_multisig::WeightSource::Fixed,
+	)
+}
+
+/// The council, a multisig account from pallet-multisig. Its signed origin can register voters
+/// and change authorities, but only root can change its members. Root itself is only reachable
+/// through a passed voting proposal.
+pub struct CouncilAccount;
+
+impl SortedMembers<AccountId> for CouncilAccount {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![council_account()]
+	}
+}
+
+/// The origin for administrative calls: root, or the council multisig.
+pub type RootOrCouncil =
+	EitherOfDiverse<EnsureRoot<AccountId>, EnsureSignedBy<CouncilAccount, AccountId>>;
+
+/// Configure the pallet-council in pallets/council.
+impl pallet_council::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type MaxMembers = MaxSignatories;
+}
+
+/// The accounts of the current authorities, which the council was derived from before
+/// pallet-council held its members.
+pub struct AuthorityAccounts;
+
+impl Get<Vec<AccountId>> for AuthorityAccounts {
+	fn get() -> Vec<AccountId> {
+		Aura::authorities().into_iter().map(AuthorityToAccount::convert).collect()
+	}
 }
 
 /// Configure the pallet-voting in pallets/voting.
@@ -313,24 +433,133 @@ impl pallet_voting::Config for Runtime {
 	type NativeBalance = Balances;
 	type BlockNumberToBalance = sp_runtime::traits::ConvertInto;
 	type RuntimeFreezeReason = RuntimeFreezeReason;
-	type ProposalDurationTier1 = ProposalDurationTier1;
-	type ProposalDurationTier2 = ProposalDurationTier2;
-	type ProposalDurationTier3 = ProposalDurationTier3;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type RegisterOrigin = RootOrCouncil;
+	type Tracks = VotingTracks;
 	type MaxProposalsAccountCanVote = MaxProposalsAccountCanVote;
+	type ConvictionBasePeriod = ConvictionBasePeriod;
+	type MaxVotersPerProposal = MaxVotersPerProposal;
+	type MaxBalanceCheckpoints = MaxBalanceCheckpoints;
+	type DiscussionPeriod = DiscussionPeriod;
+	type EnactmentDelay = EnactmentDelay;
+	type MaxCallLength = MaxCallLength;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type MaxScheduleLookahead = MaxScheduleLookahead;
+	type MaxServiceWeight = MaxServiceWeight;
+	type WeightInfo = pallet_voting::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const MaxSignatories: u32 = 32;
+	pub const OperationDeposit: Balance = 10 * EXISTENTIAL_DEPOSIT;
+	pub const MaxPendingBlocks: BlockNumber = 7 * DAYS;
+	pub const MaxExpiriesPerBlock: u32 = 50;
+	pub const MaxExpiryLookahead: u32 = 10;
+	pub const MaxNestingDepth: u32 = 4;
+	pub const FreeTxPerEra: u32 = 20;
+	pub const FreeTxEraLength: BlockNumber = DAYS;
+	pub const MaxSponsoredCalls: u32 = 16;
 }
 
 /// Configure the pallet-multisig in pallets/multisig.
 impl pallet_multisig::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type NativeBalance = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RuntimeFreezeReason = RuntimeFreezeReason;
 	type RuntimeCall = RuntimeCall;
+	type MaxSignatories = MaxSignatories;
+	type OperationDeposit = OperationDeposit;
+	type MaxPendingBlocks = MaxPendingBlocks;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type MaxExpiryLookahead = MaxExpiryLookahead;
+	type VotingPower = VotingPowerOf;
+	type CallFilter = RedispatchFilter;
+	type MaxNestingDepth = MaxNestingDepth;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+/// Weighs a multisig signatory by its quadratic voting power in pallet-voting.
+pub struct VotingPowerOf;
+
+impl Convert<AccountId, u32> for VotingPowerOf {
+	fn convert(who: AccountId) -> u32 {
+		Voting::voting_power(&who)
+	}
 }
 
 /// Configure the pallet-free-tx in pallets/free-tx.
 impl pallet_free_tx::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type NativeBalance = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type RuntimeCall = RuntimeCall;
+	type CallFilter = RedispatchFilter;
+	type MaxNestingDepth = MaxNestingDepth;
+	type FreeCalls = GovernanceCalls;
+	type FreeTxAccounts = RegisteredVoters;
+	type FreeTxPerEra = FreeTxPerEra;
+	type EraLength = FreeTxEraLength;
+	type MaxSponsoredCalls = MaxSponsoredCalls;
+}
+
+/// The calls that can be dispatched with the `redispatch` calls of pallet-multisig and
+/// pallet-free-tx. Calls that only root, the council or a governance track can make are left
+/// out, so they can't be wrapped in a `redispatch` and are only ever made directly.
+pub struct RedispatchFilter;
+
+impl Contains<RuntimeCall> for RedispatchFilter {
+	fn contains(call: &RuntimeCall) -> bool {
+		!matches!(
+			call,
+			RuntimeCall::System(
+				frame_system::Call::set_heap_pages { .. } |
+					frame_system::Call::set_code { .. } |
+					frame_system::Call::set_code_without_checks { .. } |
+					frame_system::Call::set_storage { .. } |
+					frame_system::Call::kill_storage { .. } |
+					frame_system::Call::kill_prefix { .. }
+			) | RuntimeCall::Council(_) |
+				RuntimeCall::Dpos(pallet_dpos::Call::force_change_authorities { .. }) |
+				RuntimeCall::Voting(pallet_voting::Call::register_account { .. }) |
+				RuntimeCall::Dex(
+					pallet_dex::Call::approve_fee_asset { .. } |
+						pallet_dex::Call::remove_fee_asset { .. } |
+						pallet_dex::Call::create_incentive { .. } |
+						pallet_dex::Call::set_swap_fee { .. } |
+						pallet_dex::Call::set_pool_creation_restricted { .. } |
+						pallet_dex::Call::set_pool_creator { .. } |
+						pallet_dex::Call::set_protocol_fee_recipient { .. }
+				)
+		)
+	}
+}
+
+/// The voting calls registered voters don't pay fees for, within their free transaction quota.
+pub struct GovernanceCalls;
+
+impl Contains<RuntimeCall> for GovernanceCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(
+			call,
+			RuntimeCall::Voting(
+				pallet_voting::Call::cast_vote { .. } |
+					pallet_voting::Call::close_proposal { .. } |
+					pallet_voting::Call::claim_back_tokens { .. }
+			)
+		)
+	}
+}
+
+/// The accounts registered to vote in pallet-voting.
+pub struct RegisteredVoters;
+
+impl Contains<AccountId> for RegisteredVoters {
+	fn contains(who: &AccountId) -> bool {
+		pallet_voting::RegisteredAccounts::<Runtime>::get(who)
+	}
 }
 
 pub struct AuthorityToAccount;
@@ -349,6 +578,29 @@ impl pallet_dpos::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type AuthorityToAccount = AuthorityToAccount;
 	type NativeBalance = Balances;
+	type ForceOrigin = RootOrCouncil;
+}
+
+parameter_types! {
+	pub const DexPalletId: PalletId = PalletId(*b"py/dexpl");
+	pub const DefaultSwapFee: Permill = Permill::from_parts(3_000);
+	pub ProtocolFeeShare: Permill = Permill::from_rational(1u32, 6u32);
+	pub const MintMinLiquidity: u128 = 1_000;
+	pub const ObservationPeriod: BlockNumber = 10 * MINUTES;
+	pub const FeePriceWindow: BlockNumber = HOURS;
+}
+
+/// Prices fees paid in assets at the hourly average price of their native token pool.
+pub type FeePrice = pallet_dex::TwapPrice<Runtime, FeePriceWindow>;
+
+/// Numbers LP tokens down from the top of the asset id range, away from the ids of assets
+/// created by users.
+pub struct LpTokenIds;
+
+impl Convert<u32, u32> for LpTokenIds {
+	fn convert(index: u32) -> u32 {
+		u32::MAX - index
+	}
 }
 
 /// Configure the pallet-dex in pallets/dex.
@@ -356,6 +608,23 @@ impl pallet_dex::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type NativeBalance = Balances;
 	type Fungibles = Assets;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type PalletId = DexPalletId;
+	type AdminOrigin = pallet_voting::EnsureTrack<DexTrack>;
+	type DefaultSwapFee = DefaultSwapFee;
+	type ProtocolFeeShare = ProtocolFeeShare;
+	type MintMinLiquidity = MintMinLiquidity;
+	type LpTokenId = LpTokenIds;
+	type MaxHops = ConstU32<4>;
+	type ObservationPeriod = ObservationPeriod;
+	type MaxObservations = ConstU32<144>;
+	type MaxPoolsObservedPerBlock = ConstU32<8>;
+	type MaxOrdersPerPair = ConstU32<64>;
+	type MaxOrdersPerAccount = ConstU32<16>;
+	type OrderDeposit = ConstU128<{ 10 * EXISTENTIAL_DEPOSIT }>;
+	type PoolDeposit = ConstU128<{ 100 * EXISTENTIAL_DEPOSIT }>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
 }
 
 /// Configure the pallet-treasury in pallets/treasury.
@@ -374,7 +643,6 @@ construct_runtime!(
 		Grandpa: pallet_grandpa,
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
-		Sudo: pallet_sudo,
 		Assets: pallet_assets,
 		Dex: pallet_dex,
 		Dpos: pallet_dpos,
@@ -382,6 +650,7 @@ construct_runtime!(
 		Multisig: pallet_multisig,
 		Treasury: pallet_treasury,
 		FreeTx: pallet_free_tx,
+		Council: pallet_council,
 	}
 );
 
@@ -400,7 +669,8 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_free_tx::CheckFreeTxQuota<Runtime>,
+	pallet_free_tx::ChargeTransactionPaymentOrFree<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -408,6 +678,24 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+
+parameter_types! {
+	pub const SudoPalletName: &'static str = "Sudo";
+}
+
+/// Migrations to apply on runtime upgrade.
+pub type Migrations = (
+	pallet_voting::migrations::v1::MigrateToV1<Runtime>,
+	pallet_voting::migrations::v2::MigrateToV2<Runtime>,
+	pallet_voting::migrations::v3::MigrateToV3<Runtime>,
+	pallet_voting::migrations::v4::MigrateToV4<Runtime>,
+	pallet_voting::migrations::v5::MigrateToV5<Runtime>,
+	// Keep the council's signatories, now that they are stored separately from the authorities.
+	pallet_council::migrations::v1::MigrateToV1<Runtime, AuthorityAccounts>,
+	// Remove the sudo key, now that root is reached through governance.
+	frame_support::migrations::RemovePallet<SudoPalletName, RocksDbWeight>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -415,8 +703,12 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+#[cfg(test)]
+mod tests;
+
 #[cfg(feature = "runtime-benchmarks")]
 #[macro_use]
 extern crate frame_benchmarking;
@@ -428,10 +720,11 @@ mod benches {
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
-		[pallet_sudo, Sudo]
 		[pallet_dex, Dex]
 		[pallet_dpos, Dpos]
 		[pallet_voting, Voting]
+		[pallet_multisig, Multisig]
+		[pallet_council, Council]
 	);
 }
 
@@ -603,6 +896,23 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_dex_runtime_api::DexApi<Block, u32, u128, BlockNumber> for Runtime {
+		fn quote_path(
+			path: Vec<pallet_dex::AssetKind<u32>>,
+			amount: u128,
+		) -> Option<u128> {
+			Dex::quote_path(&path, amount).ok().and_then(|amounts| amounts.last().copied())
+		}
+
+		fn twap(
+			base: pallet_dex::AssetKind<u32>,
+			quote: pallet_dex::AssetKind<u32>,
+			window: BlockNumber,
+		) -> Option<sp_runtime::FixedU128> {
+			<Dex as pallet_dex::PriceOracle<_, _>>::twap(base, quote, window)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -0,0 +1,560 @@
+//! Tests for the runtime's governance configuration.
+
+use crate::*;
+use frame_support::{
+	assert_err_ignore_postinfo, assert_noop, assert_ok,
+	dispatch::GetDispatchInfo,
+	sp_io::TestExternalities,
+	storage::unhashed,
+	traits::{Hooks, OnRuntimeUpgrade},
+};
+use pallet_multisig::WeightSource;
+use pallet_voting::{Duration, Status, SubmittedProposals, Vote};
+use sp_core::{
+	hashing::{blake2_256, twox_128},
+	sr25519,
+	storage::well_known_keys,
+	traits::{Externalities, ReadRuntimeVersion, ReadRuntimeVersionExt},
+};
+use sp_runtime::{
+	traits::{Hash, SignedExtension},
+	DispatchError,
+};
+
+/// Balance given to every voter at genesis.
+const FUNDS: Balance = 1_000_000 * EXISTENTIAL_DEPOSIT;
+
+/// The track runtime upgrades are submitted on.
+const RUNTIME_UPGRADE_TRACK: pallet_voting::TrackId = 2;
+
+fn account(seed: u8) -> AccountId {
+	AccountId::from([seed; 32])
+}
+
+fn authority(seed: u8) -> AuraId {
+	AuraId::from(sr25519::Public::from_raw([seed; 32]))
+}
+
+// Build genesis storage with funded voters registered in pallet-voting. The authorities and the
+// council's signatories are accounts 1, 2 and 3.
+fn new_test_ext(voters: Vec<AccountId>) -> TestExternalities {
+	let storage = RuntimeGenesisConfig {
+		aura: AuraConfig { authorities: (1..=3).map(authority).collect() },
+		council: CouncilConfig { members: (1..=3).map(account).collect() },
+		balances: BalancesConfig {
+			balances: voters.iter().cloned().map(|who| (who, FUNDS)).collect(),
+		},
+		voting: VotingConfig { registered_accounts: voters, initial_proposals: vec![] },
+		..Default::default()
+	}
+	.build_storage()
+	.unwrap();
+
+	let mut ext = TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+// Reports `version` as the version of any runtime code, standing in for the Wasm executor that
+// `set_code` reads the new code's version with.
+struct NewVersion(RuntimeVersion);
+
+impl ReadRuntimeVersion for NewVersion {
+	fn read_runtime_version(
+		&self,
+		_wasm_code: &[u8],
+		_ext: &mut dyn Externalities,
+	) -> Result<Vec<u8>, String> {
+		Ok(self.0.encode())
+	}
+}
+
+// Jump straight to a block and run pallet-voting's hooks for it.
+fn go_to_block(block: BlockNumber) {
+	System::set_block_number(block);
+	Voting::on_initialize(block);
+}
+
+#[test]
+fn runtime_upgrade_through_passed_proposal() {
+	let alice = account(1);
+	let bob = account(2);
+	let charlie = account(3);
+	let mut ext = new_test_ext(vec![alice.clone(), bob.clone(), charlie.clone()]);
+	let version = RuntimeVersion { spec_version: VERSION.spec_version + 1, ..VERSION };
+	ext.register_extension(ReadRuntimeVersionExt::new(NewVersion(version)));
+	ext.execute_with(|| {
+		// `set_code` only accepts code with a higher spec version than the running runtime.
+		let code = b"upgraded runtime".to_vec();
+		let call = RuntimeCall::System(frame_system::Call::set_code { code: code.clone() });
+
+		// Alice submits the upgrade on the runtime upgrade track.
+		let hash = BlakeTwo256::hash(&code);
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice.clone()),
+			hash,
+			RUNTIME_UPGRADE_TRACK,
+			Duration::Tier1,
+			Some(Box::new(call))
+		));
+
+		// Voting opens after the discussion period and every voter approves.
+		let proposal = SubmittedProposals::<Runtime>::get(hash).unwrap();
+		go_to_block(proposal.snapshot_block);
+		for who in [&alice, &bob, &charlie] {
+			assert_ok!(Voting::cast_vote(
+				RuntimeOrigin::signed(who.clone()),
+				hash,
+				Vote::Aye(10),
+				None
+			));
+		}
+
+		// The proposal passes at its deadline, but the code is only replaced once enacted.
+		go_to_block(proposal.deadline);
+		assert_eq!(SubmittedProposals::<Runtime>::get(hash).unwrap().status, Status::Passed);
+		assert_ne!(unhashed::get_raw(well_known_keys::CODE), Some(code.clone()));
+
+		go_to_block(proposal.deadline + EnactmentDelay::get());
+		assert_eq!(SubmittedProposals::<Runtime>::get(hash).unwrap().status, Status::Enacted);
+		System::assert_has_event(frame_system::Event::CodeUpdated.into());
+		assert_eq!(unhashed::get_raw(well_known_keys::CODE), Some(code));
+	});
+}
+
+#[test]
+fn signed_accounts_cannot_reach_root() {
+	let alice = account(1);
+	new_test_ext(vec![alice.clone()]).execute_with(|| {
+		let eve = account(5);
+
+		// Neither a registered account nor the council can dispatch root calls directly.
+		for who in [alice.clone(), council_account()] {
+			assert_noop!(
+				System::set_code_without_checks(RuntimeOrigin::signed(who), vec![]),
+				DispatchError::BadOrigin
+			);
+		}
+
+		// Administrative calls are limited to the council.
+		assert_noop!(
+			Voting::register_account(RuntimeOrigin::signed(alice.clone()), eve.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Voting::register_account(RuntimeOrigin::signed(council_account()), eve.clone()));
+		assert!(pallet_voting::RegisteredAccounts::<Runtime>::get(&eve));
+
+		let authority = authority(7);
+		assert_noop!(
+			Dpos::force_change_authorities(RuntimeOrigin::signed(alice.clone()), authority.clone()),
+			DispatchError::BadOrigin
+		);
+		let council = council_account();
+		assert_ok!(Dpos::force_change_authorities(
+			RuntimeOrigin::signed(council.clone()),
+			authority.clone()
+		));
+		assert_eq!(Aura::authorities().into_inner(), vec![authority]);
+
+		// Changing the authorities leaves the council's signatories unchanged.
+		assert_eq!(council_account(), council);
+	});
+}
+
+#[test]
+fn only_root_changes_council_members() {
+	let alice = account(1);
+	new_test_ext(vec![alice.clone()]).execute_with(|| {
+		let eve = account(5);
+
+		// Neither a member nor the council itself can change the council's members.
+		for who in [alice, council_account()] {
+			assert_noop!(
+				Council::set_members(RuntimeOrigin::signed(who), vec![eve.clone()]),
+				DispatchError::BadOrigin
+			);
+		}
+
+		// Root, reached through a passed proposal, replaces them.
+		assert_ok!(Council::set_members(RuntimeOrigin::root(), vec![eve.clone()]));
+		let council = Multisig::multi_account_id(&[(eve, 1)], 1, WeightSource::Fixed);
+		assert_eq!(council_account(), council);
+	});
+}
+
+#[test]
+fn council_multisig_dispatches_admin_calls() {
+	let alice = account(1);
+	let bob = account(2);
+	let charlie = account(3);
+	new_test_ext(vec![alice.clone(), bob.clone(), charlie.clone()]).execute_with(|| {
+		let eve = account(5);
+
+		// The authorities create the council multisig, a 2-of-3.
+		let mut signatories = vec![(alice.clone(), 1), (bob.clone(), 1), (charlie.clone(), 1)];
+		signatories.sort();
+		assert_ok!(Multisig::create_multisig(
+			RuntimeOrigin::signed(alice.clone()),
+			signatories,
+			2,
+			WeightSource::Fixed
+		));
+		let council = council_account();
+		assert!(pallet_multisig::Multisigs::<Runtime>::contains_key(&council));
+
+		// Alice proposes to register eve and bob approves.
+		let call = RuntimeCall::Voting(pallet_voting::Call::register_account { who: eve.clone() });
+		let call_hash = call.using_encoded(blake2_256);
+		let when = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(alice), council.clone(), call_hash));
+		assert_ok!(Multisig::approve(RuntimeOrigin::signed(bob), council.clone(), call_hash, when));
+
+		// Charlie dispatches the call from the council's origin.
+		assert_ok!(Multisig::execute(
+			RuntimeOrigin::signed(charlie),
+			council,
+			Box::new(call),
+			when
+		));
+		assert!(pallet_voting::RegisteredAccounts::<Runtime>::get(&eve));
+	});
+}
+
+#[test]
+fn sudo_key_is_removed_on_upgrade() {
+	new_test_ext(vec![]).execute_with(|| {
+		let key = [twox_128(b"Sudo"), twox_128(b"Key")].concat();
+		unhashed::put(&key, &account(1));
+
+		Migrations::on_runtime_upgrade();
+
+		assert!(unhashed::get_raw(&key).is_none());
+	});
+}
+
+#[test]
+fn multisig_weighs_signatories_by_voting_power() {
+	let alice = account(1);
+	let bob = account(2);
+	new_test_ext(vec![alice.clone(), bob.clone()]).execute_with(|| {
+		let eve = account(5);
+
+		// Registered voters are weighed by the square root of their balance, others by nothing.
+		let power = VotingPowerOf::convert(bob.clone());
+		assert_eq!(power, Voting::voting_power(&bob));
+		assert!(power > 0);
+		assert_eq!(VotingPowerOf::convert(eve.clone()), 0);
+
+		// A multisig of alice, bob and eve that no single voter can operate alone.
+		let mut signatories = vec![(alice.clone(), 0), (bob.clone(), 0), (eve.clone(), 0)];
+		signatories.sort();
+		let threshold = power + 1;
+		assert_ok!(Multisig::create_multisig(
+			RuntimeOrigin::signed(alice.clone()),
+			signatories.clone(),
+			threshold,
+			WeightSource::VotingPower
+		));
+		let multisig =
+			Multisig::multi_account_id(&signatories, threshold, WeightSource::VotingPower);
+
+		// Alice's proposal counts as her approval, but her power alone is not enough.
+		let call = RuntimeCall::System(frame_system::Call::remark_with_event { remark: vec![1] });
+		let call_hash = call.using_encoded(blake2_256);
+		let when = Multisig::timepoint();
+		assert_ok!(Multisig::propose(RuntimeOrigin::signed(alice), multisig.clone(), call_hash));
+		assert_noop!(
+			Multisig::execute(
+				RuntimeOrigin::signed(eve.clone()),
+				multisig.clone(),
+				Box::new(call.clone()),
+				when
+			),
+			pallet_multisig::Error::<Runtime>::ThresholdNotReached
+		);
+
+		assert_ok!(Multisig::approve(
+			RuntimeOrigin::signed(bob),
+			multisig.clone(),
+			call_hash,
+			when
+		));
+		assert_ok!(Multisig::execute(RuntimeOrigin::signed(eve), multisig, Box::new(call), when));
+	});
+}
+
+#[test]
+fn redispatch_nesting_is_limited_across_pallets() {
+	let alice = account(1);
+	new_test_ext(vec![alice.clone()]).execute_with(|| {
+		let remark = Box::new(RuntimeCall::System(frame_system::Call::remark { remark: vec![] }));
+		let free_tx = |call: Box<RuntimeCall>| {
+			Box::new(RuntimeCall::FreeTx(pallet_free_tx::Call::redispatch { call }))
+		};
+		let multisig = |call: Box<RuntimeCall>| {
+			Box::new(RuntimeCall::Multisig(pallet_multisig::Call::redispatch { call }))
+		};
+
+		// The pallets share one nesting depth, so alternating between them doesn't reset it.
+		assert_ok!(Multisig::redispatch(
+			RuntimeOrigin::signed(alice.clone()),
+			free_tx(multisig(free_tx(remark.clone())))
+		));
+		assert_err_ignore_postinfo!(
+			Multisig::redispatch(
+				RuntimeOrigin::signed(alice),
+				free_tx(multisig(free_tx(multisig(remark))))
+			),
+			pallet_multisig::Error::<Runtime>::TooDeeplyNested
+		);
+	});
+}
+
+#[test]
+fn privileged_calls_cannot_be_redispatched() {
+	let alice = account(1);
+	new_test_ext(vec![alice.clone()]).execute_with(|| {
+		let set_code =
+			Box::new(RuntimeCall::System(frame_system::Call::set_code { code: vec![1, 2, 3] }));
+		let set_members = Box::new(RuntimeCall::Council(pallet_council::Call::set_members {
+			members: vec![alice.clone()],
+		}));
+		let register = Box::new(RuntimeCall::Voting(pallet_voting::Call::register_account {
+			who: account(5),
+		}));
+
+		for call in [set_code.clone(), set_members] {
+			assert_err_ignore_postinfo!(
+				Multisig::redispatch(RuntimeOrigin::signed(alice.clone()), call.clone()),
+				pallet_multisig::Error::<Runtime>::CallFiltered
+			);
+			assert_err_ignore_postinfo!(
+				FreeTx::redispatch(RuntimeOrigin::signed(alice.clone()), call),
+				pallet_free_tx::Error::<Runtime>::CallFiltered
+			);
+		}
+
+		// The council's account can register voters directly, but not through a `redispatch`.
+		assert_err_ignore_postinfo!(
+			Multisig::redispatch(RuntimeOrigin::signed(council_account()), register),
+			pallet_multisig::Error::<Runtime>::CallFiltered
+		);
+
+		// Nesting the call doesn't get it past the filter.
+		assert_err_ignore_postinfo!(
+			Multisig::redispatch(
+				RuntimeOrigin::signed(alice.clone()),
+				Box::new(RuntimeCall::FreeTx(pallet_free_tx::Call::redispatch { call: set_code }))
+			),
+			pallet_free_tx::Error::<Runtime>::CallFiltered
+		);
+
+		let remark = Box::new(RuntimeCall::System(frame_system::Call::remark { remark: vec![] }));
+		assert_ok!(Multisig::redispatch(RuntimeOrigin::signed(alice), remark));
+	});
+}
+
+#[test]
+fn registered_voters_vote_without_fees() {
+	let alice = account(1);
+	let eve = account(5);
+	new_test_ext(vec![alice.clone()]).execute_with(|| {
+		let ext = pallet_free_tx::ChargeTransactionPaymentOrFree::<Runtime>::from(0);
+		let vote = RuntimeCall::Voting(pallet_voting::Call::cast_vote {
+			proposal: BlakeTwo256::hash(b"proposal"),
+			vote: Vote::Aye(1),
+			conviction: None,
+		});
+		let info = vote.get_dispatch_info();
+
+		// Alice is registered, so her vote is free.
+		assert!(ext.clone().pre_dispatch(&alice, &vote, &info, 100).unwrap().is_none());
+		assert_eq!(Balances::free_balance(&alice), FUNDS);
+		assert_eq!(FreeTx::free_tx_remaining(&alice), FreeTxPerEra::get() - 1);
+
+		// Eve isn't registered and has no funds to pay with.
+		assert!(ext.validate(&eve, &vote, &info, 100).is_err());
+	});
+}
+
+#[test]
+fn fees_are_paid_in_assets_at_the_average_pool_price() {
+	let alice = account(1);
+	let eve = account(5);
+	new_test_ext(vec![alice.clone()]).execute_with(|| {
+		let asset = pallet_dex::AssetKind::Asset(1);
+		assert_ok!(Assets::force_create(
+			RuntimeOrigin::root(),
+			1.into(),
+			alice.clone().into(),
+			true,
+			1
+		));
+		for who in [&alice, &eve] {
+			assert_ok!(Assets::mint(
+				RuntimeOrigin::signed(alice.clone()),
+				1.into(),
+				who.clone().into(),
+				1_000_000_000_000
+			));
+		}
+
+		// One native token is worth two of the asset.
+		let native = pallet_dex::AssetKind::Native;
+		assert_ok!(Dex::create_pool(RuntimeOrigin::signed(alice.clone()), native, asset));
+		assert_ok!(Dex::add_liquidity(
+			RuntimeOrigin::signed(alice.clone()),
+			native,
+			asset,
+			100_000_000,
+			200_000_000,
+			0,
+			0
+		));
+		let dex_track = RuntimeOrigin::from(pallet_voting::Origin::Track(DexTrack::get()));
+		assert_ok!(Dex::approve_fee_asset(dex_track, 1));
+		assert_ok!(Dex::set_fee_asset(RuntimeOrigin::signed(eve.clone()), Some(1)));
+
+		// The asset has no price until the pool has been observed for an hour.
+		let ext = pallet_free_tx::ChargeTransactionPaymentOrFree::<Runtime>::from(0);
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		let info = remark.get_dispatch_info();
+		assert!(ext.clone().pre_dispatch(&eve, &remark, &info, 100).is_err());
+		System::set_block_number(1 + HOURS);
+
+		// Eve has no native tokens, and pays the fee in the asset instead.
+		let pre = ext.pre_dispatch(&eve, &remark, &info, 100).unwrap();
+		let fee = TransactionPayment::compute_fee(100, &info, 0);
+		assert_eq!(Assets::balance(1, &eve), 1_000_000_000_000 - 2 * fee);
+		assert_eq!(Balances::free_balance(&eve), 0);
+
+		// After dispatch, the fee is swapped into native tokens for the treasury.
+		let pool = Dex::pool_account(&Dex::pool_id(native, asset));
+		let reserve = Balances::free_balance(&pool);
+		assert_ok!(pallet_free_tx::ChargeTransactionPaymentOrFree::<Runtime>::post_dispatch(
+			Some(pre),
+			&info,
+			&Default::default(),
+			100,
+			&Ok(())
+		));
+		let swapped = reserve - Balances::free_balance(&pool);
+		assert!(swapped > 0);
+		assert_eq!(Balances::free_balance(&TreasuryAccount::get()), swapped);
+	});
+}
+
+#[test]
+fn dex_parameters_are_set_by_dex_track_proposals() {
+	new_test_ext(vec![]).execute_with(|| {
+		let fee = Permill::from_percent(1);
+		assert_noop!(Dex::set_swap_fee(RuntimeOrigin::root(), fee), DispatchError::BadOrigin);
+		assert_noop!(
+			Dex::set_swap_fee(RuntimeOrigin::from(pallet_voting::Origin::Track(1)), fee),
+			DispatchError::BadOrigin
+		);
+
+		let dex_track = RuntimeOrigin::from(pallet_voting::Origin::Track(DexTrack::get()));
+		assert_ok!(Dex::set_swap_fee(dex_track, fee));
+		assert_eq!(pallet_dex::SwapFee::<Runtime>::get(), fee);
+	});
+}
+
+#[test]
+fn low_turnout_proposals_fail_on_every_track() {
+	let voters: Vec<_> = (10..40).map(account).collect();
+	let alice = voters[0].clone();
+	new_test_ext(voters).execute_with(|| {
+		let eve = account(5);
+		let call = RuntimeCall::Balances(pallet_balances::Call::force_set_balance {
+			who: Address::Id(eve.clone()),
+			new_free: FUNDS,
+		});
+
+		// One voter out of 30 is short of every track's quorum, even voting all aye.
+		let mut proposals = vec![];
+		for track in [0, 1, RUNTIME_UPGRADE_TRACK] {
+			let hash = BlakeTwo256::hash(&[track]);
+			assert_ok!(Voting::submit_proposal(
+				RuntimeOrigin::signed(alice.clone()),
+				hash,
+				track,
+				Duration::Tier1,
+				Some(Box::new(call.clone()))
+			));
+			proposals.push((hash, SubmittedProposals::<Runtime>::get(hash).unwrap()));
+		}
+		for (hash, proposal) in &proposals {
+			go_to_block(proposal.snapshot_block);
+			assert_ok!(Voting::cast_vote(
+				RuntimeOrigin::signed(alice.clone()),
+				*hash,
+				Vote::Aye(10),
+				None
+			));
+		}
+		for (hash, proposal) in &proposals {
+			go_to_block(proposal.deadline);
+			assert_eq!(SubmittedProposals::<Runtime>::get(hash).unwrap().status, Status::Failed);
+		}
+		go_to_block(proposals[2].1.deadline + EnactmentDelay::get());
+		assert_eq!(Balances::free_balance(&eve), 0);
+	});
+}
+
+#[test]
+fn signal_proposals_cannot_dispatch_root_calls() {
+	let alice = account(1);
+	let bob = account(2);
+	new_test_ext(vec![alice.clone(), bob.clone()]).execute_with(|| {
+		let eve = account(5);
+		let call = RuntimeCall::Balances(pallet_balances::Call::force_set_balance {
+			who: Address::Id(eve.clone()),
+			new_free: FUNDS,
+		});
+
+		// Only registered accounts submit signal proposals.
+		let hash = BlakeTwo256::hash(b"signal");
+		assert_noop!(
+			Voting::submit_proposal(
+				RuntimeOrigin::signed(eve.clone()),
+				hash,
+				0,
+				Duration::Tier1,
+				None
+			),
+			pallet_voting::Error::<Runtime>::NotRegistered
+		);
+
+		// A signal proposal that every voter approves still enacts its call with the signal
+		// track's origin, which can't set balances.
+		assert_ok!(Voting::submit_proposal(
+			RuntimeOrigin::signed(alice.clone()),
+			hash,
+			0,
+			Duration::Tier1,
+			Some(Box::new(call))
+		));
+		let proposal = SubmittedProposals::<Runtime>::get(hash).unwrap();
+		go_to_block(proposal.snapshot_block);
+		for who in [&alice, &bob] {
+			assert_ok!(Voting::cast_vote(
+				RuntimeOrigin::signed(who.clone()),
+				hash,
+				Vote::Aye(10),
+				None
+			));
+		}
+		go_to_block(proposal.deadline);
+		assert_eq!(SubmittedProposals::<Runtime>::get(hash).unwrap().status, Status::Passed);
+		go_to_block(proposal.deadline + EnactmentDelay::get());
+		System::assert_has_event(
+			pallet_voting::Event::ProposalEnacted {
+				proposal: hash,
+				result: Err(DispatchError::BadOrigin),
+			}
+			.into(),
+		);
+		assert_eq!(Balances::free_balance(&eve), 0);
+	});
+}